@@ -0,0 +1,764 @@
+/*
+	Copyright 2019 Supercomputing Systems AG
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+*/
+
+//! Generates a typed api of a runtime from its SCALE encoded metadata.
+//!
+//! For every pallet, a module named after the pallet in snake case is generated, containing:
+//!
+//! * `calls`: A struct per call, annotated with [`pallet_call`], so that the `Api` can compose
+//!   the call with e.g. `api.transfer(dest, value)`, `api.try_transfer(dest, value)`.
+//! * `events`: A struct per event, implementing `StaticEvent`, e.g. for `api.wait_for_event`.
+//! * `storage`: A function per storage entry, returning its `StorageAddress` validated against the
+//!   metadata of the node, for `api.get_storage_by_address`. Entries with more than two keys
+//!   are skipped, because `StorageAddress` does not support them.
+//! * `constants`: A function per constant, reading it with `api.get_constant`.
+//! * The type aliases `Call`, `Event` and `Error` of the pallet's enums.
+//!
+//! All other types of the `PortableRegistry` are generated below a `types` module, following
+//! their path in the runtime, e.g. `types::pallet_balances::AccountData`. Accounts and addresses
+//! use the `AccountId` and `MultiAddress` of the api client.
+//!
+//! The generated code is meant to be written to a file once per runtime upgrade, with the
+//! `ac-codegen` binary or from a build script, and included as a module:
+//!
+//! ```ignore
+//! #[path = "runtime.rs"]
+//! mod runtime;
+//! ```
+//!
+//! [`pallet_call`]: https://docs.rs/ac-compose-macros
+
+use ac_node_api::{InvalidMetadataError, Metadata};
+use codec::Decode;
+use frame_metadata::{
+	v14::{PalletMetadata, StorageEntryType},
+	RuntimeMetadataPrefixed,
+};
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use scale_info::{form::PortableForm, TypeDef, Variant};
+use types::{Scope, TypeGenerator};
+
+mod types;
+
+#[derive(Debug)]
+pub enum CodegenError {
+	Codec(codec::Error),
+	Hex(hex::FromHexError),
+	InvalidMetadata(InvalidMetadataError),
+	/// Type is missing from the type registry.
+	TypeNotFound(u32),
+	/// Composites and variants need a path to be generated.
+	AnonymousType(u32),
+	/// The calls or events of a pallet are not an enum.
+	TypeNotVariant(u32),
+	Syn(syn::Error),
+}
+
+impl From<codec::Error> for CodegenError {
+	fn from(error: codec::Error) -> Self {
+		Self::Codec(error)
+	}
+}
+
+impl From<hex::FromHexError> for CodegenError {
+	fn from(error: hex::FromHexError) -> Self {
+		Self::Hex(error)
+	}
+}
+
+impl From<InvalidMetadataError> for CodegenError {
+	fn from(error: InvalidMetadataError) -> Self {
+		Self::InvalidMetadata(error)
+	}
+}
+
+impl From<syn::Error> for CodegenError {
+	fn from(error: syn::Error) -> Self {
+		Self::Syn(error)
+	}
+}
+
+/// Decode the metadata, SCALE encoded or hex encoded as returned by the `state_getMetadata` rpc.
+pub fn decode_metadata(bytes: &[u8]) -> Result<Metadata, CodegenError> {
+	let hex = std::str::from_utf8(bytes).ok().and_then(|hex| hex.trim().strip_prefix("0x"));
+	let bytes = match hex {
+		Some(hex) => hex::decode(hex)?,
+		None => bytes.to_vec(),
+	};
+	let metadata = RuntimeMetadataPrefixed::decode(&mut bytes.as_slice())?;
+	Ok(Metadata::try_from(metadata)?)
+}
+
+/// Generate the api of the runtime as a formatted source file. `krate` is the path of the api
+/// client in the code using the generated api, usually `::substrate_api_client`.
+pub fn generate_file(metadata: &Metadata, krate: &syn::Path) -> Result<String, CodegenError> {
+	let file = syn::parse2(generate(metadata, krate)?)?;
+	Ok(format!(
+		"// Generated by ac-codegen from the runtime metadata, do not edit.\n\n{}",
+		prettyplease::unparse(&file)
+	))
+}
+
+/// Generate the api of the runtime.
+pub fn generate(metadata: &Metadata, krate: &syn::Path) -> Result<TokenStream, CodegenError> {
+	let krate = quote!(#krate);
+	let types = TypeGenerator::new(&metadata.metadata.types, &krate);
+	let pallets = metadata
+		.metadata
+		.pallets
+		.iter()
+		.map(|pallet| generate_pallet(pallet, &types, &krate))
+		.collect::<Result<Vec<_>, _>>()?;
+	let types = types.generate()?;
+
+	Ok(quote! {
+		#(
+			#[allow(dead_code, unused_imports, non_camel_case_types, non_snake_case, clippy::all)]
+			#pallets
+		)*
+		#[allow(dead_code, unused_imports, non_camel_case_types, non_snake_case, clippy::all)]
+		#types
+	})
+}
+
+fn generate_pallet(
+	pallet: &PalletMetadata<PortableForm>,
+	types: &TypeGenerator,
+	krate: &TokenStream,
+) -> Result<TokenStream, CodegenError> {
+	let name = &pallet.name;
+	let module = ident(&name.to_snake_case());
+	let scope = Scope::module(1);
+
+	let mut aliases = Vec::new();
+	let mut modules = Vec::new();
+	if let Some(calls) = &pallet.calls {
+		let call = types.type_path(calls.ty.id(), &scope)?;
+		aliases.push(quote!(pub type Call = #call;));
+		modules.push(generate_calls(name, variants(types, calls.ty.id())?, types, krate)?);
+	}
+	if let Some(event) = &pallet.event {
+		let event_ty = types.type_path(event.ty.id(), &scope)?;
+		aliases.push(quote!(pub type Event = #event_ty;));
+		modules.push(generate_events(name, variants(types, event.ty.id())?, types, krate)?);
+	}
+	if let Some(error) = &pallet.error {
+		let error = types.type_path(error.ty.id(), &scope)?;
+		aliases.push(quote!(pub type Error = #error;));
+	}
+	if let Some(storage) = &pallet.storage {
+		modules.push(generate_storage(name, &storage.entries, types, krate)?);
+	}
+	if !pallet.constants.is_empty() {
+		modules.push(generate_constants(name, pallet, types, krate)?);
+	}
+
+	Ok(quote! {
+		pub mod #module {
+			pub const PALLET: &str = #name;
+
+			#(#aliases)*
+
+			#(#modules)*
+		}
+	})
+}
+
+fn generate_calls(
+	pallet: &str,
+	calls: &[Variant<PortableForm>],
+	types: &TypeGenerator,
+	krate: &TokenStream,
+) -> Result<TokenStream, CodegenError> {
+	let scope = Scope::module(2);
+	let krate_str = krate.to_string().replace(' ', "");
+	let calls = calls
+		.iter()
+		.map(|call| {
+			let name = call.name();
+			let struct_ident = format_ident!("{}", name.to_upper_camel_case());
+			// `pallet_call` needs named fields, but the arguments are encoded by position anyway.
+			let fields = call
+				.fields()
+				.iter()
+				.enumerate()
+				.map(|(i, field)| {
+					let name = match field.name() {
+						Some(name) => ident(name),
+						None => format_ident!("arg{}", i),
+					};
+					let (attrs, ty) = types.field_type(field, &scope)?;
+					Ok(quote!(#attrs pub #name: #ty))
+				})
+				.collect::<Result<Vec<_>, CodegenError>>()?;
+			let docs = docs(call.docs());
+			Ok(quote! {
+				#docs
+				#[#krate::pallet_call(pallet = #pallet, call = #name, method = #name, krate = #krate_str)]
+				#[derive(Clone, Debug)]
+				pub struct #struct_ident {
+					#(#fields,)*
+				}
+			})
+		})
+		.collect::<Result<Vec<_>, CodegenError>>()?;
+
+	Ok(quote! {
+		pub mod calls {
+			#(#calls)*
+		}
+	})
+}
+
+fn generate_events(
+	pallet: &str,
+	events: &[Variant<PortableForm>],
+	types: &TypeGenerator,
+	krate: &TokenStream,
+) -> Result<TokenStream, CodegenError> {
+	let scope = Scope::module(2);
+	let derives = types.derives();
+	let events = events
+		.iter()
+		.map(|event| {
+			let name = event.name();
+			let ident = format_ident!("{}", name);
+			let fields = types.fields(event.fields(), &scope, quote!(pub))?;
+			let semicolon = match event.fields().first() {
+				Some(field) if field.name().is_some() => quote!(),
+				_ => quote!(;),
+			};
+			let docs = docs(event.docs());
+			Ok(quote! {
+				#docs
+				#derives
+				pub struct #ident #fields #semicolon
+
+				impl #krate::StaticEvent for #ident {
+					const PALLET: &'static str = #pallet;
+					const EVENT: &'static str = #name;
+				}
+			})
+		})
+		.collect::<Result<Vec<_>, CodegenError>>()?;
+
+	Ok(quote! {
+		pub mod events {
+			#(#events)*
+		}
+	})
+}
+
+fn generate_storage(
+	pallet: &str,
+	entries: &[frame_metadata::v14::StorageEntryMetadata<PortableForm>],
+	types: &TypeGenerator,
+	krate: &TokenStream,
+) -> Result<TokenStream, CodegenError> {
+	let scope = Scope::module(2);
+	let mut functions = Vec::new();
+	for entry in entries {
+		let (constructor, key, value) = match &entry.ty {
+			StorageEntryType::Plain(value) =>
+				(quote!(value), quote!(()), types.type_path(value.id(), &scope)?),
+			StorageEntryType::Map { hashers, key, value } => {
+				let constructor = match hashers.len() {
+					1 => quote!(map),
+					2 => quote!(double_map),
+					_ => continue,
+				};
+				(
+					constructor,
+					types.type_path(key.id(), &scope)?,
+					types.type_path(value.id(), &scope)?,
+				)
+			},
+		};
+		let name = &entry.name;
+		let function = ident(&name.to_snake_case());
+		let docs = docs(&entry.docs);
+		functions.push(quote! {
+			#docs
+			pub fn #function(
+				metadata: &#krate::Metadata,
+			) -> ::core::result::Result<#krate::StorageAddress<#key, #value>, #krate::MetadataError> {
+				#krate::StorageAddress::#constructor(metadata, #pallet, #name)
+			}
+		});
+	}
+
+	Ok(quote! {
+		pub mod storage {
+			#(#functions)*
+		}
+	})
+}
+
+fn generate_constants(
+	pallet: &str,
+	metadata: &PalletMetadata<PortableForm>,
+	types: &TypeGenerator,
+	krate: &TokenStream,
+) -> Result<TokenStream, CodegenError> {
+	let scope = Scope::module(2);
+	let constants = metadata
+		.constants
+		.iter()
+		.map(|constant| {
+			let name = &constant.name;
+			let function = ident(&name.to_snake_case());
+			let ty = types.type_path(constant.ty.id(), &scope)?;
+			let docs = docs(&constant.docs);
+			Ok(quote! {
+				#docs
+				pub fn #function<Hash>(
+					api: &impl #krate::GetStorage<Hash>,
+				) -> #krate::ApiResult<#ty> {
+					api.get_constant(#pallet, #name)
+				}
+			})
+		})
+		.collect::<Result<Vec<_>, CodegenError>>()?;
+
+	Ok(quote! {
+		pub mod constants {
+			#(#constants)*
+		}
+	})
+}
+
+/// The variants of the enum type `id`.
+fn variants<'a>(
+	types: &TypeGenerator<'a>,
+	id: u32,
+) -> Result<&'a [Variant<PortableForm>], CodegenError> {
+	match types.resolve(id)?.type_def() {
+		TypeDef::Variant(variant) => Ok(variant.variants()),
+		_ => Err(CodegenError::TypeNotVariant(id)),
+	}
+}
+
+/// An identifier for a name of the metadata, escaped if it is a keyword.
+pub(crate) fn ident(name: &str) -> Ident {
+	match syn::parse_str::<Ident>(name) {
+		Ok(ident) => ident,
+		Err(_) => Ident::new_raw(name, Span::call_site()),
+	}
+}
+
+/// The doc comments. Code blocks of the runtime docs are marked as text, so they are not run as
+/// doc tests of the crate including the generated code.
+pub(crate) fn docs(docs: &[String]) -> TokenStream {
+	let mut in_code_block = false;
+	let docs: Vec<_> = docs
+		.iter()
+		.map(|line| {
+			let code = line.trim_start();
+			if !code.starts_with("```") {
+				return line.clone()
+			}
+			in_code_block = !in_code_block;
+			match in_code_block {
+				true => format!("{}```text", &line[..line.len() - code.len()]),
+				false => line.clone(),
+			}
+		})
+		.collect();
+	quote!(#(#[doc = #docs])*)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_metadata::{
+		v14::{
+			ExtrinsicMetadata, PalletCallMetadata, PalletConstantMetadata, PalletErrorMetadata,
+			PalletEventMetadata, PalletStorageMetadata, RuntimeMetadataV14, StorageEntryMetadata,
+			StorageEntryModifier, StorageHasher,
+		},
+		RuntimeMetadataPrefixed,
+	};
+	use scale_info::{build::Fields, meta_type, type_params, Path, Type, TypeInfo};
+	use std::{collections::BTreeMap, marker::PhantomData};
+
+	#[allow(unused)]
+	#[derive(TypeInfo)]
+	struct AccountData<Balance> {
+		free: Balance,
+		reserved: Balance,
+	}
+
+	#[allow(unused)]
+	#[derive(TypeInfo)]
+	struct BalanceLock<Balance> {
+		amount: Balance,
+		id: u128,
+	}
+
+	#[allow(unused)]
+	#[derive(TypeInfo)]
+	struct Perbill(u32);
+
+	#[allow(unused)]
+	#[derive(TypeInfo)]
+	struct ValidatorIndex(u32);
+
+	/// Has a hand written `TypeInfo` without type names of the fields, like `BTreeMap`.
+	#[allow(unused)]
+	struct Opaque<T>(PhantomData<T>);
+
+	impl<T: TypeInfo + 'static> TypeInfo for Opaque<T> {
+		type Identity = Self;
+
+		fn type_info() -> Type {
+			Type::builder()
+				.path(Path::new("Opaque", module_path!()))
+				.type_params(type_params!(T))
+				.composite(Fields::unnamed().field(|field| field.ty::<T>()))
+		}
+	}
+
+	#[allow(unused, non_camel_case_types)]
+	#[derive(TypeInfo)]
+	enum BalancesCall<T> {
+		#[codec(index = 3)]
+		transfer {
+			dest: [u8; 32],
+			#[codec(compact)]
+			value: u128,
+		},
+		set_fee {
+			fee: Perbill,
+		},
+		#[codec(skip)]
+		__Ignore(PhantomData<T>),
+	}
+
+	#[allow(unused)]
+	#[derive(TypeInfo)]
+	enum BalancesEvent<T> {
+		Transfer {
+			from: [u8; 32],
+			to: [u8; 32],
+			amount: u128,
+		},
+		Dust(Option<u64>),
+		#[codec(skip)]
+		__Ignore(PhantomData<T>),
+	}
+
+	#[allow(unused)]
+	#[derive(TypeInfo)]
+	enum BalancesError<T> {
+		InsufficientBalance,
+		#[codec(skip)]
+		__Ignore(PhantomData<T>),
+	}
+
+	#[derive(TypeInfo)]
+	struct Runtime;
+
+	/// Build fake metadata consisting of a single pallet `Balances`.
+	fn metadata() -> Metadata {
+		let entry = |name, ty| StorageEntryMetadata {
+			name,
+			modifier: StorageEntryModifier::Optional,
+			ty,
+			default: vec![],
+			docs: vec!["The balances of the accounts."],
+		};
+		let entries = vec![
+			entry("TotalIssuance", StorageEntryType::Plain(meta_type::<u128>())),
+			entry(
+				"Account",
+				StorageEntryType::Map {
+					hashers: vec![StorageHasher::Blake2_128Concat],
+					key: meta_type::<[u8; 32]>(),
+					value: meta_type::<AccountData<u128>>(),
+				},
+			),
+			entry(
+				"Locks",
+				StorageEntryType::Map {
+					hashers: vec![StorageHasher::Twox64Concat, StorageHasher::Blake2_128Concat],
+					key: meta_type::<(u32, [u8; 32])>(),
+					value: meta_type::<Vec<BalanceLock<u128>>>(),
+				},
+			),
+			entry("Opaque", StorageEntryType::Plain(meta_type::<Opaque<bool>>())),
+			entry(
+				"Reserves",
+				StorageEntryType::Plain(meta_type::<BTreeMap<ValidatorIndex, u128>>()),
+			),
+		];
+		let pallets = vec![frame_metadata::v14::PalletMetadata {
+			name: "Balances",
+			storage: Some(PalletStorageMetadata { prefix: "Balances", entries }),
+			calls: Some(PalletCallMetadata { ty: meta_type::<BalancesCall<Runtime>>() }),
+			event: Some(PalletEventMetadata { ty: meta_type::<BalancesEvent<Runtime>>() }),
+			constants: vec![PalletConstantMetadata {
+				name: "ExistentialDeposit",
+				ty: meta_type::<u128>(),
+				value: vec![0; 16],
+				docs: vec!["The minimum balance.", "```", "let deposit = 1;", "```"],
+			}],
+			error: Some(PalletErrorMetadata { ty: meta_type::<BalancesError<Runtime>>() }),
+			index: 5,
+		}];
+		let extrinsic =
+			ExtrinsicMetadata { ty: meta_type::<()>(), version: 4, signed_extensions: vec![] };
+		let v14 = RuntimeMetadataV14::new(pallets, extrinsic, meta_type::<()>());
+		let runtime_metadata: RuntimeMetadataPrefixed = v14.into();
+		Metadata::try_from(runtime_metadata).unwrap()
+	}
+
+	fn generated() -> syn::File {
+		syn::parse2(generate(&metadata(), &syn::parse_quote!(::substrate_api_client)).unwrap())
+			.unwrap()
+	}
+
+	/// The tokens of the item at `path` in the module tree of the `file`.
+	fn item(file: &syn::File, path: &[&str]) -> String {
+		let mut items = &file.items;
+		for (i, name) in path.iter().enumerate() {
+			let item = items
+				.iter()
+				.find(|item| {
+					let ident = match item {
+						syn::Item::Mod(item) => &item.ident,
+						syn::Item::Struct(item) => &item.ident,
+						syn::Item::Enum(item) => &item.ident,
+						syn::Item::Type(item) => &item.ident,
+						syn::Item::Fn(item) => &item.sig.ident,
+						syn::Item::Const(item) => &item.ident,
+						_ => return false,
+					};
+					ident == name
+				})
+				.unwrap_or_else(|| panic!("{} not found", path[..=i].join("::")));
+			match item {
+				syn::Item::Mod(module) if i + 1 < path.len() =>
+					items = &module.content.as_ref().unwrap().1,
+				item => return quote!(#item).to_string(),
+			}
+		}
+		unreachable!("empty path")
+	}
+
+	/// The tokens of the `expected` item, like [`item`] renders them.
+	fn tokens(expected: TokenStream) -> String {
+		let item: syn::Item = syn::parse2(expected).unwrap();
+		quote!(#item).to_string()
+	}
+
+	fn derives() -> TokenStream {
+		quote! {
+			#[derive(
+				::substrate_api_client::codec::Decode,
+				::substrate_api_client::codec::Encode,
+				::substrate_api_client::scale_info::TypeInfo,
+				Clone,
+				Debug,
+				PartialEq,
+				Eq,
+			)]
+			#[codec(crate = ::substrate_api_client::codec)]
+			#[scale_info(crate = ::substrate_api_client::scale_info)]
+		}
+	}
+
+	#[test]
+	fn generates_calls_of_pallets() {
+		let file = generated();
+
+		assert_eq!(
+			item(&file, &["balances", "PALLET"]),
+			tokens(quote!(
+				pub const PALLET: &str = "Balances";
+			))
+		);
+		assert_eq!(
+			item(&file, &["balances", "calls", "Transfer"]),
+			tokens(quote! {
+				#[::substrate_api_client::pallet_call(
+					pallet = "Balances",
+					call = "transfer",
+					method = "transfer",
+					krate = "::substrate_api_client"
+				)]
+				#[derive(Clone, Debug)]
+				pub struct Transfer {
+					pub dest: [u8; 32],
+					#[codec(compact)]
+					pub value: u128,
+				}
+			})
+		);
+	}
+
+	#[test]
+	fn generates_events_storage_and_constants_of_pallets() {
+		let file = generated();
+		let derives = derives();
+
+		assert_eq!(
+			item(&file, &["balances", "events", "Dust"]),
+			tokens(quote!(#derives pub struct Dust(pub ::core::option::Option<u64>,);))
+		);
+		assert_eq!(
+			item(&file, &["balances", "storage", "locks"]),
+			tokens(quote! {
+				#[doc = "The balances of the accounts."]
+				pub fn locks(
+					metadata: &::substrate_api_client::Metadata,
+				) -> ::core::result::Result<
+					::substrate_api_client::StorageAddress<
+						(u32, [u8; 32],),
+						::std::vec::Vec<super::super::types::ac_codegen::tests::BalanceLock<u128>>
+					>,
+					::substrate_api_client::MetadataError
+				> {
+					::substrate_api_client::StorageAddress::double_map(metadata, "Balances", "Locks")
+				}
+			})
+		);
+		assert_eq!(
+			item(&file, &["balances", "constants", "existential_deposit"]),
+			tokens(quote! {
+				#[doc = "The minimum balance."]
+				#[doc = "```text"]
+				#[doc = "let deposit = 1;"]
+				#[doc = "```"]
+				pub fn existential_deposit<Hash>(
+					api: &impl ::substrate_api_client::GetStorage<Hash>,
+				) -> ::substrate_api_client::ApiResult<u128> {
+					api.get_constant("Balances", "ExistentialDeposit")
+				}
+			})
+		);
+	}
+
+	#[test]
+	fn generates_types_without_unused_generic_params() {
+		let file = generated();
+		let derives = derives();
+
+		assert_eq!(
+			item(&file, &["balances", "Call"]),
+			quote!(
+				pub type Call = super::types::ac_codegen::tests::BalancesCall;
+			)
+			.to_string()
+		);
+		assert_eq!(
+			item(&file, &["types", "ac_codegen", "tests", "AccountData"]),
+			tokens(quote! {
+				#derives
+				pub struct AccountData<Balance> {
+					pub free: Balance,
+					pub reserved: Balance,
+				}
+			})
+		);
+		assert_eq!(
+			item(&file, &["types", "ac_codegen", "tests", "Perbill"]),
+			tokens(quote! {
+				#derives
+				#[derive(::substrate_api_client::codec::CompactAs)]
+				pub struct Perbill(pub u32,);
+			})
+		);
+	}
+
+	#[test]
+	fn uses_generic_params_only_for_fields_declared_with_them() {
+		let file = generated();
+		let derives = derives();
+
+		assert_eq!(
+			item(&file, &["types", "ac_codegen", "tests", "BalanceLock"]),
+			tokens(quote! {
+				#derives
+				pub struct BalanceLock<Balance> {
+					pub amount: Balance,
+					pub id: u128,
+				}
+			})
+		);
+	}
+
+	#[test]
+	fn leaves_fields_without_type_names_concrete() {
+		let file = generated();
+		let derives = derives();
+
+		assert_eq!(
+			item(&file, &["types", "ac_codegen", "tests", "Opaque"]),
+			tokens(quote! {
+				#derives
+				pub struct Opaque(pub bool,);
+			})
+		);
+	}
+
+	#[test]
+	fn uses_btree_maps_of_the_standard_library_with_ord_keys() {
+		let file = generated();
+		let derives = derives();
+
+		assert_eq!(
+			item(&file, &["balances", "storage", "reserves"]),
+			tokens(quote! {
+				#[doc = "The balances of the accounts."]
+				pub fn reserves(
+					metadata: &::substrate_api_client::Metadata,
+				) -> ::core::result::Result<
+					::substrate_api_client::StorageAddress<
+						(),
+						::std::collections::BTreeMap<
+							super::super::types::ac_codegen::tests::ValidatorIndex,
+							u128
+						>
+					>,
+					::substrate_api_client::MetadataError
+				> {
+					::substrate_api_client::StorageAddress::value(metadata, "Balances", "Reserves")
+				}
+			})
+		);
+		assert_eq!(
+			item(&file, &["types", "ac_codegen", "tests", "ValidatorIndex"]),
+			tokens(quote! {
+				#derives
+				#[derive(::substrate_api_client::codec::CompactAs)]
+				#[derive(PartialOrd, Ord)]
+				pub struct ValidatorIndex(pub u32,);
+			})
+		);
+	}
+
+	#[test]
+	fn marks_code_blocks_of_docs_as_text() {
+		let docs = docs(&["Example:".into(), " ```".into(), " let a = 1;".into(), " ```".into()]);
+
+		assert_eq!(
+			docs.to_string(),
+			quote!(#[doc = "Example:"] #[doc = " ```text"] #[doc = " let a = 1;"] #[doc = " ```"])
+				.to_string()
+		);
+	}
+}
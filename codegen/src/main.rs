@@ -0,0 +1,66 @@
+/*
+	Copyright 2019 Supercomputing Systems AG
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+*/
+
+//! Generates the typed api of a runtime from a metadata file:
+//!
+//! ```text
+//! ac-codegen <metadata file> [<output file>] [--crate <path of the api client>]
+//! ```
+//!
+//! The metadata file contains the SCALE encoded metadata, or the hex encoded metadata as returned
+//! by the `state_getMetadata` rpc. Without an output file, the code is written to stdout.
+
+use ac_codegen::{decode_metadata, generate_file};
+use std::{env, fs, process};
+
+const USAGE: &str = "usage: ac-codegen <metadata file> [<output file>] [--crate <path>]";
+
+fn main() {
+	let mut files = Vec::new();
+	let mut krate = String::from("::substrate_api_client");
+	let mut args = env::args().skip(1);
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--crate" => krate = args.next().unwrap_or_else(|| exit(USAGE)),
+			"-h" | "--help" => exit(USAGE),
+			_ => files.push(arg),
+		}
+	}
+	let (metadata_file, output_file) = match files.as_slice() {
+		[metadata_file] => (metadata_file, None),
+		[metadata_file, output_file] => (metadata_file, Some(output_file)),
+		_ => exit(USAGE),
+	};
+
+	let krate: syn::Path =
+		syn::parse_str(&krate).unwrap_or_else(|e| exit(&format!("invalid crate path: {}", e)));
+	let bytes = fs::read(metadata_file)
+		.unwrap_or_else(|e| exit(&format!("could not read {}: {}", metadata_file, e)));
+	let metadata =
+		decode_metadata(&bytes).unwrap_or_else(|e| exit(&format!("invalid metadata: {:?}", e)));
+	let code = generate_file(&metadata, &krate)
+		.unwrap_or_else(|e| exit(&format!("could not generate the api: {:?}", e)));
+
+	match output_file {
+		Some(output_file) => fs::write(output_file, code)
+			.unwrap_or_else(|e| exit(&format!("could not write {}: {}", output_file, e))),
+		None => print!("{}", code),
+	}
+}
+
+fn exit(message: &str) -> ! {
+	eprintln!("{}", message);
+	process::exit(1)
+}
@@ -0,0 +1,646 @@
+/*
+	Copyright 2019 Supercomputing Systems AG
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+*/
+
+//! Rust types mirroring the named types of the `PortableRegistry`.
+//!
+//! Every type with a path is generated once, in a module tree following its path below the
+//! `types` module, e.g. `types::pallet_balances::AccountData<Balance>`. Anonymous types like
+//! sequences and tuples are rendered inline wherever they are used.
+
+use crate::{docs, ident, CodegenError};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote};
+use scale_info::{
+	form::PortableForm, Field, PortableRegistry, Type, TypeDef, TypeDefPrimitive, Variant,
+};
+use std::{
+	cell::RefCell,
+	collections::{BTreeMap, BTreeSet},
+};
+use syn::{GenericArgument, PathArguments};
+
+/// Types that are not generated, but replaced by a type of the standard library or of the api
+/// client, with the same encoding and type info.
+const SUBSTITUTES: &[(&str, &str)] = &[
+	("Option", "::core::option::Option"),
+	("Result", "::core::result::Result"),
+	// Their fields have no type names, so they would be generated for their first instance only.
+	("BTreeMap", "::std::collections::BTreeMap"),
+	("BTreeSet", "::std::collections::BTreeSet"),
+	("sp_core::crypto::AccountId32", "AccountId"),
+	("sp_runtime::multiaddress::MultiAddress", "MultiAddress"),
+	("bitvec::order::Lsb0", "bitvec::order::Lsb0"),
+	("bitvec::order::Msb0", "bitvec::order::Msb0"),
+];
+
+/// A generic type parameter of a generated type, with the id of its concrete type in the first
+/// instance of the type found in the registry.
+///
+/// Fields use a parameter where their type name in the runtime, like `Balance` or `Vec<Balance>`,
+/// names it. Matching the concrete type alone would also replace fields declared with the same
+/// concrete type, like `b` in `Foo<T = u32> { a: T, b: u32 }`. Fields without a type name are
+/// left concrete.
+struct TypeParam {
+	name: String,
+	ty: u32,
+}
+
+pub(crate) struct TypeGenerator<'a> {
+	types: &'a PortableRegistry,
+	krate: &'a TokenStream,
+	/// The generated types by path.
+	generated: BTreeMap<Vec<String>, GeneratedType<'a>>,
+	/// The paths of the generated types that need to implement `Ord`.
+	ord: BTreeSet<Vec<String>>,
+}
+
+/// The first instance of a type found in the registry and its generic parameters.
+struct GeneratedType<'a> {
+	id: u32,
+	ty: &'a Type<PortableForm>,
+	params: Vec<TypeParam>,
+}
+
+/// Where a type is rendered: the path from there to the root of the generated code and the
+/// generic parameters in scope.
+pub(crate) struct Scope<'a> {
+	root: TokenStream,
+	params: &'a [TypeParam],
+	/// The names of the parameters rendered so far.
+	used: RefCell<BTreeSet<String>>,
+}
+
+impl Scope<'_> {
+	/// The scope of code in a module `depth` levels below the root, without generic parameters.
+	pub(crate) fn module(depth: usize) -> Self {
+		Self::new(depth, &[])
+	}
+
+	fn new(depth: usize, params: &[TypeParam]) -> Scope<'_> {
+		let supers = (0..depth).map(|_| quote!(super));
+		Scope { root: quote!(#(#supers)::*), params, used: RefCell::default() }
+	}
+
+	/// The parameter of the scope that the type `id` named `name` is.
+	fn find(&self, name: TypeName, id: u32) -> Option<&TypeParam> {
+		match name {
+			TypeName::Declared(syn::Type::Path(path)) if path.qself.is_none() => self
+				.params
+				.iter()
+				.find(|param| path.path.is_ident(&param.name) && param.ty == id),
+			_ => None,
+		}
+	}
+
+	/// Like [`Self::find`], and records the parameter as used.
+	fn param(&self, name: TypeName, id: u32) -> Option<&TypeParam> {
+		let param = self.find(name, id)?;
+		self.used.borrow_mut().insert(param.name.clone());
+		Some(param)
+	}
+}
+
+/// The name of a type in the runtime, which is followed along the type to find the generic
+/// parameters of the scope in it.
+#[derive(Clone, Copy)]
+enum TypeName<'a> {
+	/// The type is declared like this, e.g. `Vec<Balance>`.
+	Declared(&'a syn::Type),
+	/// The field has no type name, e.g. in hand written `TypeInfo` implementations like the one
+	/// of `BTreeMap`, or the declared name does not follow the type, e.g. for type aliases.
+	/// No parameters are used, the type is concrete.
+	Unknown,
+}
+
+impl<'a> TypeName<'a> {
+	/// The names of the `count` generic arguments, like `Balance` of `Vec<Balance>`.
+	fn args(self, count: usize) -> Vec<TypeName<'a>> {
+		match self {
+			Self::Declared(name) => {
+				let args = generic_args(name);
+				if args.len() == count {
+					args.into_iter().map(Self::Declared).collect()
+				} else {
+					vec![Self::Unknown; count]
+				}
+			},
+			Self::Unknown => vec![Self::Unknown; count],
+		}
+	}
+
+	/// The names of the `count` elements of a tuple.
+	fn elems(self, count: usize) -> Vec<TypeName<'a>> {
+		match self {
+			Self::Declared(syn::Type::Tuple(tuple)) if tuple.elems.len() == count =>
+				tuple.elems.iter().map(Self::Declared).collect(),
+			_ => vec![Self::Unknown; count],
+		}
+	}
+
+	/// The name of the element of an array.
+	fn elem(self) -> TypeName<'a> {
+		match self {
+			Self::Declared(syn::Type::Array(array)) => Self::Declared(&array.elem),
+			_ => Self::Unknown,
+		}
+	}
+
+	/// The name without `Box`es, which are transparent in the type info.
+	fn unboxed(self) -> TypeName<'a> {
+		match self {
+			Self::Declared(name) => Self::Declared(unboxed(name)),
+			name => name,
+		}
+	}
+}
+
+impl<'a> TypeGenerator<'a> {
+	pub(crate) fn new(types: &'a PortableRegistry, krate: &'a TokenStream) -> Self {
+		let mut generated = BTreeMap::new();
+		for (id, ty) in types.types().iter().map(|ty| (ty.id(), ty.ty())) {
+			let path = ty.path().segments().to_vec();
+			if path.is_empty() || substitute(&path).is_some() {
+				continue
+			}
+			generated.entry(path).or_insert_with(|| {
+				let params = ty
+					.type_params()
+					.iter()
+					.filter_map(|param| {
+						let ty = param.ty()?.id();
+						Some(TypeParam { name: param.name().clone(), ty })
+					})
+					.collect();
+				GeneratedType { id, ty, params }
+			});
+		}
+
+		let mut generator = Self { types, krate, generated, ord: BTreeSet::new() };
+		generator.remove_unused_params();
+		generator.ord = generator.ord_types();
+		generator
+	}
+
+	/// The generated types used in the keys of a `BTreeMap` or `BTreeSet`, which need to
+	/// implement `Ord` to be decoded.
+	fn ord_types(&self) -> BTreeSet<Vec<String>> {
+		let mut pending: Vec<u32> = self
+			.types
+			.types()
+			.iter()
+			.filter(
+				|ty| matches!(ty.ty().path().segments(), [name] if name == "BTreeMap" || name == "BTreeSet"),
+			)
+			.filter_map(|ty| Some(ty.ty().type_params().first()?.ty()?.id()))
+			.collect();
+		let mut visited = BTreeSet::new();
+		let mut ord = BTreeSet::new();
+		while let Some(id) = pending.pop() {
+			let ty = match self.types.resolve(id) {
+				Some(ty) if visited.insert(id) => ty,
+				_ => continue,
+			};
+			if self.generated.contains_key(ty.path().segments()) {
+				ord.insert(ty.path().segments().to_vec());
+			}
+			pending.extend(self.fields_of(ty).iter().map(|field| field.ty().id()));
+			pending.extend(match ty.type_def() {
+				TypeDef::Sequence(seq) => vec![seq.type_param().id()],
+				TypeDef::Array(array) => vec![array.type_param().id()],
+				TypeDef::Tuple(tuple) => tuple.fields().iter().map(|field| field.id()).collect(),
+				TypeDef::Compact(compact) => vec![compact.type_param().id()],
+				_ => Vec::new(),
+			});
+		}
+		ord
+	}
+
+	/// Drops the generic parameters not used by the fields of their type, which would not
+	/// compile. Dropping a parameter of one type can leave a parameter of another type unused, so
+	/// this is repeated until no more parameters are dropped.
+	fn remove_unused_params(&mut self) {
+		loop {
+			let mut unused = Vec::new();
+			for (path, GeneratedType { ty, params, .. }) in &self.generated {
+				let scope = Scope::new(path.len(), params);
+				for field in self.fields_of(ty) {
+					// Fields which can not be rendered fail again when the type is generated.
+					let _ = self.field_type(field, &scope);
+				}
+				let used = scope.used.into_inner();
+				unused.extend(
+					params
+						.iter()
+						.filter(|param| !used.contains(&param.name))
+						.map(|param| (path.clone(), param.name.clone())),
+				);
+			}
+			if unused.is_empty() {
+				return
+			}
+			for (path, name) in unused {
+				if let Some(generated) = self.generated.get_mut(&path) {
+					generated.params.retain(|param| param.name != name);
+				}
+			}
+		}
+	}
+
+	/// The names of the generic parameters kept for a named type.
+	fn kept_params(&self, ty: &Type<PortableForm>) -> Vec<String> {
+		match substitute(ty.path().segments()) {
+			Some(_) => ty.type_params().iter().map(|param| param.name().clone()).collect(),
+			None => self
+				.generated
+				.get(ty.path().segments())
+				.map(|generated| generated.params.iter().map(|param| param.name.clone()).collect())
+				.unwrap_or_default(),
+		}
+	}
+
+	fn fields_of(&self, ty: &'a Type<PortableForm>) -> Vec<&'a Field<PortableForm>> {
+		match ty.type_def() {
+			TypeDef::Composite(composite) => composite.fields().iter().collect(),
+			TypeDef::Variant(variant) =>
+				variant.variants().iter().flat_map(|variant| variant.fields()).collect(),
+			_ => Vec::new(),
+		}
+	}
+
+	pub(crate) fn resolve(&self, id: u32) -> Result<&'a Type<PortableForm>, CodegenError> {
+		self.types.resolve(id).ok_or(CodegenError::TypeNotFound(id))
+	}
+
+	/// The path to the type `id` from the `scope`.
+	pub(crate) fn type_path(&self, id: u32, scope: &Scope) -> Result<TokenStream, CodegenError> {
+		self.named_type_path(id, TypeName::Unknown, scope)
+	}
+
+	/// The path to the type `id` from the `scope`, where the type is named `name` in the runtime.
+	fn named_type_path(
+		&self,
+		id: u32,
+		name: TypeName,
+		scope: &Scope,
+	) -> Result<TokenStream, CodegenError> {
+		let name = name.unboxed();
+		if let Some(param) = scope.param(name, id) {
+			let param = format_ident!("{}", param.name);
+			return Ok(quote!(#param))
+		}
+		let ty = self.resolve(id)?;
+		let segments = ty.path().segments();
+		if !segments.is_empty() {
+			let kept = self.kept_params(ty);
+			let args = ty
+				.type_params()
+				.iter()
+				.zip(name.args(ty.type_params().len()))
+				.filter(|(param, _)| kept.contains(param.name()))
+				.map(|(param, name)| match param.ty() {
+					Some(param) => self.named_type_path(param.id(), name, scope),
+					None => Ok(quote!(())),
+				})
+				.collect::<Result<Vec<_>, _>>()?;
+			let args = if args.is_empty() { quote!() } else { quote!(<#(#args),*>) };
+			let path = match substitute(segments) {
+				Some(substitute) if substitute.starts_with("::") => substitute.parse().unwrap(),
+				Some(substitute) => {
+					let substitute: TokenStream = substitute.parse().unwrap();
+					let krate = self.krate;
+					quote!(#krate::#substitute)
+				},
+				None => {
+					let root = &scope.root;
+					let modules =
+						segments[..segments.len() - 1].iter().map(|segment| ident(segment));
+					let name = format_ident!("{}", segments[segments.len() - 1]);
+					quote!(#root::types::#(#modules::)*#name)
+				},
+			};
+			return Ok(quote!(#path #args))
+		}
+		self.type_def_path(id, ty.type_def(), name, scope)
+	}
+
+	/// The type of an anonymous type definition, like a sequence or tuple, named `name` in the
+	/// runtime.
+	fn type_def_path(
+		&self,
+		id: u32,
+		type_def: &TypeDef<PortableForm>,
+		name: TypeName,
+		scope: &Scope,
+	) -> Result<TokenStream, CodegenError> {
+		Ok(match type_def {
+			TypeDef::Sequence(seq) => {
+				let inner = self.named_type_path(seq.type_param().id(), name.args(1)[0], scope)?;
+				quote!(::std::vec::Vec<#inner>)
+			},
+			TypeDef::Array(array) => {
+				let inner = self.named_type_path(array.type_param().id(), name.elem(), scope)?;
+				let len = Literal::u32_unsuffixed(array.len());
+				quote!([#inner; #len])
+			},
+			TypeDef::Tuple(tuple) => {
+				let fields = tuple
+					.fields()
+					.iter()
+					.zip(name.elems(tuple.fields().len()))
+					.map(|(field, name)| self.named_type_path(field.id(), name, scope))
+					.collect::<Result<Vec<_>, _>>()?;
+				quote!((#(#fields,)*))
+			},
+			TypeDef::Primitive(primitive) => primitive_path(primitive),
+			TypeDef::Compact(compact) => {
+				let inner =
+					self.named_type_path(compact.type_param().id(), name.args(1)[0], scope)?;
+				let krate = self.krate;
+				quote!(#krate::codec::Compact<#inner>)
+			},
+			TypeDef::BitSequence(bits) => {
+				let names = name.args(2);
+				let store = self.named_type_path(bits.bit_store_type().id(), names[0], scope)?;
+				let order = self.named_type_path(bits.bit_order_type().id(), names[1], scope)?;
+				let krate = self.krate;
+				quote!(#krate::bitvec::vec::BitVec<#store, #order>)
+			},
+			TypeDef::Composite(_) | TypeDef::Variant(_) =>
+				return Err(CodegenError::AnonymousType(id)),
+		})
+	}
+
+	/// The type of a struct or variant field, with the `#[codec(compact)]` attribute if needed.
+	/// Fields which are boxed in the runtime are boxed here too, to keep recursive types sized.
+	pub(crate) fn field_type(
+		&self,
+		field: &Field<PortableForm>,
+		scope: &Scope,
+	) -> Result<(TokenStream, TokenStream), CodegenError> {
+		let id = field.ty().id();
+		let ty = self.resolve(id)?;
+		// Type names which do not parse are treated like missing ones.
+		let declared = field.type_name().and_then(|name| syn::parse_str::<syn::Type>(name).ok());
+		let name = declared.as_ref().map_or(TypeName::Unknown, TypeName::Declared);
+		let (attrs, ty) = match ty.type_def() {
+			// A compact field is named by the type of its value, like `#[codec(compact)] value:
+			// Balance`, unless it is a parameter with a compact concrete type.
+			TypeDef::Compact(compact) if scope.find(name, id).is_none() => (
+				quote!(#[codec(compact)]),
+				self.named_type_path(compact.type_param().id(), name, scope)?,
+			),
+			_ => (quote!(), self.named_type_path(id, name, scope)?),
+		};
+		let boxed = matches!(field.type_name(), Some(name) if name.contains("Box<"));
+		Ok((attrs, if boxed { quote!(::std::boxed::Box<#ty>) } else { ty }))
+	}
+
+	/// The fields of a struct or variant, including their visibility.
+	pub(crate) fn fields(
+		&self,
+		fields: &[Field<PortableForm>],
+		scope: &Scope,
+		vis: TokenStream,
+	) -> Result<TokenStream, CodegenError> {
+		let is_named = matches!(fields.first(), Some(field) if field.name().is_some());
+		let fields = fields
+			.iter()
+			.map(|field| {
+				let (attrs, ty) = self.field_type(field, scope)?;
+				let docs = docs(field.docs());
+				Ok(match field.name() {
+					Some(name) if is_named => {
+						let name = ident(name);
+						quote!(#docs #attrs #vis #name: #ty)
+					},
+					_ => quote!(#docs #attrs #vis #ty),
+				})
+			})
+			.collect::<Result<Vec<_>, CodegenError>>()?;
+
+		Ok(if fields.is_empty() {
+			quote!()
+		} else if is_named {
+			quote!({ #(#fields,)* })
+		} else {
+			quote!(( #(#fields,)* ))
+		})
+	}
+
+	/// The derives shared by all generated types.
+	pub(crate) fn derives(&self) -> TokenStream {
+		let krate = self.krate;
+		quote! {
+			#[derive(
+				#krate::codec::Decode,
+				#krate::codec::Encode,
+				#krate::scale_info::TypeInfo,
+				Clone,
+				Debug,
+				PartialEq,
+				Eq,
+			)]
+			#[codec(crate = #krate::codec)]
+			#[scale_info(crate = #krate::scale_info)]
+		}
+	}
+
+	/// The `types` module with all generated types.
+	pub(crate) fn generate(&self) -> Result<TokenStream, CodegenError> {
+		let mut root = Module::default();
+		for (path, GeneratedType { id, ty, params }) in &self.generated {
+			let module = path[..path.len() - 1].iter().fold(&mut root, |module, segment| {
+				module.children.entry(segment.clone()).or_default()
+			});
+			module.types.push(self.generate_type(path, *id, ty, params)?);
+		}
+		Ok(root.into_tokens(format_ident!("types")))
+	}
+
+	fn generate_type(
+		&self,
+		path: &[String],
+		id: u32,
+		ty: &Type<PortableForm>,
+		params: &[TypeParam],
+	) -> Result<TokenStream, CodegenError> {
+		// The types module and all modules of the path.
+		let scope = Scope::new(path.len(), params);
+		let name = format_ident!("{}", path[path.len() - 1]);
+		let generics = params.iter().map(|param| format_ident!("{}", param.name));
+		let generics = if params.is_empty() { quote!() } else { quote!(<#(#generics),*>) };
+		let docs = docs(ty.docs());
+		let derives = self.derives();
+		let ord =
+			if self.ord.contains(path) { quote!(#[derive(PartialOrd, Ord)]) } else { quote!() };
+
+		Ok(match ty.type_def() {
+			TypeDef::Variant(variant) => {
+				let variants = variant
+					.variants()
+					.iter()
+					.map(|variant| self.generate_variant(variant, &scope))
+					.collect::<Result<Vec<_>, _>>()?;
+				quote! {
+					#docs
+					#derives
+					#ord
+					pub enum #name #generics {
+						#(#variants,)*
+					}
+				}
+			},
+			TypeDef::Composite(composite) => {
+				let fields = self.fields(composite.fields(), &scope, quote!(pub))?;
+				let compact_as = self.compact_as(composite.fields(), params);
+				let semicolon = match composite.fields().first() {
+					Some(field) if field.name().is_some() => quote!(),
+					_ => quote!(;),
+				};
+				quote! {
+					#docs
+					#derives
+					#compact_as
+					#ord
+					pub struct #name #generics #fields #semicolon
+				}
+			},
+			// A named type that is not a composite or variant, which FRAME does not produce.
+			_ => {
+				let ty = self.type_def_path(id, ty.type_def(), TypeName::Unknown, &scope)?;
+				quote!(#docs pub type #name #generics = #ty;)
+			},
+		})
+	}
+
+	fn generate_variant(
+		&self,
+		variant: &Variant<PortableForm>,
+		scope: &Scope,
+	) -> Result<TokenStream, CodegenError> {
+		let name = format_ident!("{}", variant.name());
+		let index = Literal::u8_unsuffixed(variant.index());
+		let fields = self.fields(variant.fields(), scope, quote!())?;
+		let docs = docs(variant.docs());
+		Ok(quote! {
+			#docs
+			#[codec(index = #index)]
+			#name #fields
+		})
+	}
+
+	/// Types wrapping a single unsigned integer, like `Perbill`, can be compact encoded in the
+	/// runtime, so they need to implement `CompactAs`.
+	fn compact_as(&self, fields: &[Field<PortableForm>], params: &[TypeParam]) -> TokenStream {
+		let is_unsigned = |field: &Field<PortableForm>| {
+			matches!(
+				self.types.resolve(field.ty().id()).map(|ty| ty.type_def()),
+				Some(TypeDef::Primitive(
+					TypeDefPrimitive::U8
+						| TypeDefPrimitive::U16 | TypeDefPrimitive::U32
+						| TypeDefPrimitive::U64 | TypeDefPrimitive::U128
+				))
+			)
+		};
+		match fields {
+			[field] if params.is_empty() && is_unsigned(field) => {
+				let krate = self.krate;
+				quote!(#[derive(#krate::codec::CompactAs)])
+			},
+			_ => quote!(),
+		}
+	}
+}
+
+/// A module of the `types` tree.
+#[derive(Default)]
+struct Module {
+	types: Vec<TokenStream>,
+	children: BTreeMap<String, Module>,
+}
+
+impl Module {
+	fn into_tokens(self, name: Ident) -> TokenStream {
+		let types = self.types;
+		let children =
+			self.children.into_iter().map(|(name, module)| module.into_tokens(ident(&name)));
+		quote! {
+			pub mod #name {
+				#(#children)*
+				#(#types)*
+			}
+		}
+	}
+}
+
+/// The generic type arguments of a type name like `Vec<T>`.
+fn generic_args(name: &syn::Type) -> Vec<&syn::Type> {
+	let arguments = match name {
+		syn::Type::Path(path) => path.path.segments.last().map(|segment| &segment.arguments),
+		_ => None,
+	};
+	match arguments {
+		Some(PathArguments::AngleBracketed(args)) => args
+			.args
+			.iter()
+			.filter_map(|arg| match arg {
+				GenericArgument::Type(ty) => Some(ty),
+				_ => None,
+			})
+			.collect(),
+		_ => Vec::new(),
+	}
+}
+
+/// The type name without `Box`es around it.
+fn unboxed(name: &syn::Type) -> &syn::Type {
+	let is_box = match name {
+		syn::Type::Path(path) =>
+			matches!(path.path.segments.last(), Some(last) if last.ident == "Box"),
+		_ => false,
+	};
+	match generic_args(name).as_slice() {
+		[inner] if is_box => unboxed(inner),
+		_ => name,
+	}
+}
+
+fn substitute(path: &[String]) -> Option<&'static str> {
+	let path = path.join("::");
+	SUBSTITUTES
+		.iter()
+		.find(|(name, _)| *name == path)
+		.map(|(_, substitute)| *substitute)
+}
+
+fn primitive_path(primitive: &TypeDefPrimitive) -> TokenStream {
+	match primitive {
+		TypeDefPrimitive::Bool => quote!(bool),
+		TypeDefPrimitive::Char => quote!(char),
+		TypeDefPrimitive::Str => quote!(::std::string::String),
+		TypeDefPrimitive::U8 => quote!(u8),
+		TypeDefPrimitive::U16 => quote!(u16),
+		TypeDefPrimitive::U32 => quote!(u32),
+		TypeDefPrimitive::U64 => quote!(u64),
+		TypeDefPrimitive::U128 => quote!(u128),
+		TypeDefPrimitive::I8 => quote!(i8),
+		TypeDefPrimitive::I16 => quote!(i16),
+		TypeDefPrimitive::I32 => quote!(i32),
+		TypeDefPrimitive::I64 => quote!(i64),
+		TypeDefPrimitive::I128 => quote!(i128),
+		// Encoded as 32 bytes, there are no 256 bit integers in Rust.
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => quote!([u8; 32]),
+	}
+}
@@ -0,0 +1,30 @@
+/*
+	Copyright 2019 Supercomputing Systems AG
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+*/
+
+use ac_codegen::{decode_metadata, generate_file};
+use std::{env, fs, path::Path};
+
+const METADATA_FILE: &str = "../../node-api/test_data/polkadot_metadata_v14.scale";
+
+fn main() {
+	println!("cargo:rerun-if-changed={}", METADATA_FILE);
+
+	let metadata = decode_metadata(&fs::read(METADATA_FILE).unwrap()).unwrap();
+	let krate: syn::Path = syn::parse_str("::substrate_api_client").unwrap();
+	let code = generate_file(&metadata, &krate).unwrap();
+
+	let out_dir = env::var("OUT_DIR").unwrap();
+	fs::write(Path::new(&out_dir).join("polkadot.rs"), code).unwrap();
+}
@@ -0,0 +1,47 @@
+/*
+	Copyright 2019 Supercomputing Systems AG
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+*/
+
+//! The api generated by the build script from the polkadot metadata of the node-api test data,
+//! such that changes to the generator are compiled against the api client.
+
+include!(concat!(env!("OUT_DIR"), "/polkadot.rs"));
+
+#[cfg(test)]
+mod tests {
+	use super::balances::calls::TransferAllowDeath;
+	use ac_codegen::decode_metadata;
+	use substrate_api_client::{
+		codec::{Compact, Encode},
+		AccountId, MultiAddress,
+	};
+
+	#[test]
+	fn generated_calls_encode_like_their_arguments() {
+		let dest = MultiAddress::Id(AccountId::new([1; 32]));
+		let call = TransferAllowDeath { dest: dest.clone(), value: 42 };
+
+		assert_eq!(call.encode(), (dest, Compact(42u128)).encode());
+	}
+
+	#[test]
+	fn generated_calls_find_their_index_in_the_metadata() {
+		let metadata = decode_metadata(include_bytes!(
+			"../../../node-api/test_data/polkadot_metadata_v14.scale"
+		))
+		.unwrap();
+
+		assert_eq!(TransferAllowDeath::call_index(&metadata).unwrap(), [5, 0]);
+	}
+}
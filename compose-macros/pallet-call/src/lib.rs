@@ -0,0 +1,274 @@
+/*
+   Copyright 2019 Supercomputing Systems AG
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+	   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+*/
+
+//! The `#[pallet_call]` attribute, which generates a typed extrinsic builder for a call
+//! from a struct of its arguments. Re-exported by `ac-compose-macros` and the api client.
+
+use heck::ToSnakeCase;
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+	parse_macro_input, parse_quote, AttributeArgs, Error, Fields, GenericParam, Ident, ItemStruct,
+	Lit, Meta, NestedMeta, Path,
+};
+
+/// Generates a typed extrinsic builder for a call from a struct of its arguments:
+///
+/// ```ignore
+/// #[pallet_call(pallet = "Balances", call = "transfer", method = "balance_transfer")]
+/// #[derive(Clone)]
+/// pub struct BalanceTransfer<Balance> {
+///     pub dest: GenericAddress,
+///     #[codec(compact)]
+///     pub value: Balance,
+/// }
+/// ```
+///
+/// generates
+///
+/// * `Encode` for the struct with the codec derive, which encodes the fields in order like the
+///   call arguments. Fields with a `#[codec(compact)]` attribute are compact encoded.
+/// * `BalanceTransfer::PALLET`, `BalanceTransfer::CALL` and `BalanceTransfer::call_index(&metadata)`.
+/// * The type aliases `BalanceTransferFn<Balance>` of the call and `BalanceTransferXt<SignedExtra,
+///   Balance, Address, Signature>` of the extrinsic.
+/// * The trait `ComposeBalanceTransfer`, implemented for the `Api`, with the methods
+///   `balance_transfer(dest, value)` and `try_balance_transfer(dest, value)`.
+///
+/// The struct must derive `Clone`. The `method` defaults to the struct name in snake case.
+/// Generic parameters of the extrinsic alias and the `Api` impl get a trailing underscore if the
+/// struct declares a parameter of the same name.
+/// Generated paths start with `::substrate_api_client`; set `krate = "crate"` or another path
+/// if the api client is not a direct dependency under this name.
+#[proc_macro_attribute]
+pub fn pallet_call(args: TokenStream, input: TokenStream) -> TokenStream {
+	let args = parse_macro_input!(args as AttributeArgs);
+	let item = parse_macro_input!(input as ItemStruct);
+	PalletCallArgs::parse(args, &item)
+		.and_then(|args| expand(args, item))
+		.unwrap_or_else(Error::into_compile_error)
+		.into()
+}
+
+struct PalletCallArgs {
+	pallet: String,
+	call: String,
+	method: Ident,
+	krate: Path,
+}
+
+impl PalletCallArgs {
+	fn parse(args: AttributeArgs, item: &ItemStruct) -> syn::Result<Self> {
+		let (mut pallet, mut call, mut method, mut krate) = (None, None, None, None);
+		for arg in args {
+			let name_value = match arg {
+				NestedMeta::Meta(Meta::NameValue(name_value)) => name_value,
+				arg => return Err(Error::new_spanned(arg, "expected `name = \"value\"`")),
+			};
+			let value = match &name_value.lit {
+				Lit::Str(value) => value,
+				lit => return Err(Error::new_spanned(lit, "expected a string literal")),
+			};
+			match name_value.path.get_ident().map(ToString::to_string).as_deref() {
+				Some("pallet") => pallet = Some(value.value()),
+				Some("call") => call = Some(value.value()),
+				Some("method") => method = Some(value.parse()?),
+				Some("krate") => krate = Some(value.parse()?),
+				_ =>
+					return Err(Error::new_spanned(
+						&name_value.path,
+						"unknown argument, expected `pallet`, `call`, `method` or `krate`",
+					)),
+			}
+		}
+
+		let missing = |name| Error::new(Span::call_site(), format!("missing `{}` argument", name));
+		Ok(Self {
+			pallet: pallet.ok_or_else(|| missing("pallet"))?,
+			call: call.ok_or_else(|| missing("call"))?,
+			method: method
+				.unwrap_or_else(|| format_ident!("{}", item.ident.to_string().to_snake_case())),
+			krate: krate.unwrap_or_else(|| parse_quote!(::substrate_api_client)),
+		})
+	}
+}
+
+fn expand(args: PalletCallArgs, mut item: ItemStruct) -> syn::Result<TokenStream2> {
+	let PalletCallArgs { pallet, call, method, krate } = args;
+	if !matches!(item.fields, Fields::Named(_)) {
+		return Err(Error::new_spanned(&item, "expected a struct with named fields"))
+	}
+	// The derive must precede the `codec` attributes, which are its helper attributes.
+	item.attrs.splice(
+		0..0,
+		[
+			parse_quote!(#[derive(#krate::codec::Encode)]),
+			parse_quote!(#[codec(crate = #krate::codec)]),
+		],
+	);
+	let fields = &item.fields;
+	let field_names: Vec<_> = fields.iter().filter_map(|field| field.ident.as_ref()).collect();
+	let field_types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+
+	let ident = &item.ident;
+	let vis = &item.vis;
+	let fn_alias = format_ident!("{}Fn", ident);
+	let xt_alias = format_ident!("{}Xt", ident);
+	let trait_ident = format_ident!("Compose{}", ident);
+	let try_method = format_ident!("try_{}", method);
+	let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+	// Generic parameters without bounds, to declare and apply them in the type aliases.
+	let (param_decls, params): (Vec<_>, Vec<_>) = item
+		.generics
+		.params
+		.iter()
+		.map(|param| match param {
+			GenericParam::Type(param) => {
+				let ident = &param.ident;
+				(quote!(#ident), quote!(#ident))
+			},
+			GenericParam::Lifetime(param) => {
+				let lifetime = &param.lifetime;
+				(quote!(#lifetime), quote!(#lifetime))
+			},
+			GenericParam::Const(param) => {
+				let (ident, ty) = (&param.ident, &param.ty);
+				(quote!(const #ident: #ty), quote!(#ident))
+			},
+		})
+		.unzip();
+
+	// Generic parameters added by the macro, renamed if the struct declares them already.
+	let [signed_extra, address, signature, signer, client, extrinsic_params, runtime] =
+		["SignedExtra", "Address", "Signature", "Signer", "Client", "Params", "Runtime"]
+			.map(|name| unused_ident(name, &item));
+
+	let mut api_generics = item.generics.clone();
+	api_generics.params.extend::<[GenericParam; 4]>([
+		parse_quote!(#signer),
+		parse_quote!(#client),
+		parse_quote!(#extrinsic_params),
+		parse_quote!(#runtime),
+	]);
+	api_generics.make_where_clause().predicates.extend::<[syn::WherePredicate; 5]>([
+		parse_quote!(#signer: #krate::SignExtrinsic<#runtime::AccountId>),
+		parse_quote!(#client: #krate::rpc::Request),
+		parse_quote!(#extrinsic_params: #krate::ExtrinsicParams<#runtime::Index, #runtime::Hash>),
+		parse_quote!(#extrinsic_params::OtherParams: #krate::WithEra<#runtime::Hash>),
+		parse_quote!(#runtime: #krate::FrameSystemConfig),
+	]);
+	let (api_impl_generics, _, api_where_clause) = api_generics.split_for_impl();
+
+	// Bound on the methods instead of the impl, so that the generic parameters can be inferred
+	// from the arguments before the bound is checked.
+	let call_bound = quote!(#ident #ty_generics: #krate::codec::Encode + ::core::clone::Clone);
+
+	let trait_doc = format!("Compose the `{}::{}` extrinsic with the api signer.", pallet, call);
+	let try_method_doc =
+		format!("Like [`Self::{}`], but returns an error instead of panicking.", method);
+
+	Ok(quote! {
+		#item
+
+		impl #impl_generics #ident #ty_generics #where_clause {
+			pub const PALLET: &'static str = #pallet;
+			pub const CALL: &'static str = #call;
+
+			/// The index of the call in the `metadata` of the node.
+			pub fn call_index(
+				metadata: &#krate::Metadata,
+			) -> ::core::result::Result<#krate::CallIndex, #krate::MetadataError> {
+				let pallet = metadata.pallet(Self::PALLET)?;
+				Ok([pallet.index, pallet.call_index(Self::CALL)?])
+			}
+		}
+
+		#vis type #fn_alias<#(#param_decls),*> = (#krate::CallIndex, #ident<#(#params),*>);
+
+		#vis type #xt_alias<
+			#signed_extra,
+			#(#param_decls,)*
+			#address = #krate::GenericAddress,
+			#signature = #krate::MultiSignature,
+		> = #krate::UncheckedExtrinsicV4<
+			#fn_alias<#(#params),*>,
+			#signed_extra,
+			#address,
+			#signature,
+		>;
+
+		#[doc = #trait_doc]
+		#vis trait #trait_ident #impl_generics #where_clause {
+			type Extrinsic;
+
+			#[doc = #try_method_doc]
+			fn #try_method(
+				&self,
+				#(#field_names: #field_types),*
+			) -> #krate::ApiResult<Self::Extrinsic>
+			where
+				#call_bound;
+
+			fn #method(&self, #(#field_names: #field_types),*) -> Self::Extrinsic
+			where
+				#call_bound,
+			{
+				self.#try_method(#(#field_names),*).unwrap()
+			}
+		}
+
+		impl #api_impl_generics #trait_ident #ty_generics
+			for #krate::Api<#signer, #client, #extrinsic_params, #runtime> #api_where_clause
+		{
+			type Extrinsic = #xt_alias<
+				#extrinsic_params::SignedExtra,
+				#(#params,)*
+				#signer::ExtrinsicAddress,
+				#signer::Signature,
+			>;
+
+			fn #try_method(
+				&self,
+				#(#field_names: #field_types),*
+			) -> #krate::ApiResult<Self::Extrinsic>
+			where
+				#call_bound,
+			{
+				#krate::try_compose_extrinsic!(
+					self,
+					<#ident #ty_generics>::PALLET,
+					<#ident #ty_generics>::CALL,
+					#ident { #(#field_names),* }
+				)
+			}
+		}
+	})
+}
+
+/// `name`, or `name` with trailing underscores if the struct has a generic parameter of this name.
+fn unused_ident(name: &str, item: &ItemStruct) -> Ident {
+	let mut ident = format_ident!("{}", name);
+	while item.generics.params.iter().any(|param| match param {
+		GenericParam::Type(param) => param.ident == ident,
+		GenericParam::Const(param) => param.ident == ident,
+		GenericParam::Lifetime(_) => false,
+	}) {
+		ident = format_ident!("{}_", ident);
+	}
+	ident
+}
@@ -0,0 +1,9 @@
+use ac_pallet_call::pallet_call;
+
+#[pallet_call(pallet = "Balances")]
+#[derive(Clone)]
+struct BalanceTransfer {
+	value: u128,
+}
+
+fn main() {}
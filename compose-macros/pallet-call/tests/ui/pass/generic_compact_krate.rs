@@ -0,0 +1,31 @@
+use ac_pallet_call::pallet_call;
+use client::{
+	codec::{Compact, Encode},
+	AccountId, GenericAddress,
+};
+
+/// The api client under another path than `::substrate_api_client`.
+mod client {
+	pub use substrate_api_client::*;
+}
+
+#[pallet_call(pallet = "Balances", call = "transfer", krate = "crate::client")]
+#[derive(Clone)]
+pub struct BalanceTransfer<Address, Balance> {
+	pub dest: Address,
+	#[codec(compact)]
+	pub value: Balance,
+}
+
+fn main() {
+	let dest = GenericAddress::Id(AccountId::new([1; 32]));
+	let call = BalanceTransfer { dest: dest.clone(), value: 42u128 };
+	assert_eq!(call.encode(), (dest, Compact(42u128)).encode());
+	assert_eq!(BalanceTransfer::<GenericAddress, u128>::PALLET, "Balances");
+	assert_eq!(BalanceTransfer::<GenericAddress, u128>::CALL, "transfer");
+
+	// The method defaults to the struct name in snake case.
+	fn compose<Api: ComposeBalanceTransfer<GenericAddress, u128>>(api: &Api) -> Api::Extrinsic {
+		api.balance_transfer(GenericAddress::Id(AccountId::new([1; 32])), 42)
+	}
+}
@@ -0,0 +1,7 @@
+use ac_pallet_call::pallet_call;
+
+#[pallet_call(pallet = "Balances", call = "transfer")]
+#[derive(Clone)]
+struct BalanceTransfer(u128);
+
+fn main() {}
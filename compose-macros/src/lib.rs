@@ -19,6 +19,8 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub use ac_pallet_call::pallet_call;
+
 // re-export for macro resolution
 pub use ac_primitives as primitives;
 #[cfg(feature = "std")]
@@ -38,7 +40,8 @@ mod rpc;
 /// * 'module' - Module name as &str for which the call is composed.
 /// * 'call' - Call name as &str
 /// * 'args' - Optional sequence of arguments of the call. They are not checked against the metadata.
-/// As of now the user needs to check himself that the correct arguments are supplied.
+/// As of now the user needs to check himself that the correct arguments are supplied, or compose
+/// the call with `Metadata::encode_dynamic_call`, which checks them.
 #[macro_export]
 macro_rules! compose_call {
 ($node_metadata: expr, $pallet: expr, $call_name: expr $(, $args: expr) *) => {
@@ -52,10 +55,30 @@ macro_rules! compose_call {
     };
 }
 
+/// Generates the extrinsic's call field like `compose_call!`, but returns a
+/// `Result<_, MetadataError>` instead of panicking if the pallet or the call is not in the metadata.
+/// # Arguments
+///
+/// * 'node_metadata' - This crate's parsed node metadata as field of the API.
+/// * 'module' - Module name as &str for which the call is composed.
+/// * 'call' - Call name as &str
+/// * 'args' - Optional sequence of arguments of the call. They are not checked against the metadata.
+#[macro_export]
+macro_rules! try_compose_call {
+($node_metadata: expr, $pallet: expr, $call_name: expr $(, $args: expr) *) => {
+        {
+            $node_metadata.pallet($pallet).and_then(|pallet| {
+                let call_index = pallet.call_index($call_name)?;
+                Ok(([pallet.index, call_index] $(, ($args)) *))
+            })
+        }
+    };
+}
+
 /// Generates an Unchecked extrinsic for a given call
 /// # Arguments
 ///
-/// * 'signer' - AccountKey that is used to sign the extrinsic.
+/// * 'signer' - Signer of the extrinsic implementing `SignExtrinsic`, e.g. a key pair, or a reference to it.
 /// * 'call' - call as returned by the compose_call! macro or via substrate's call enums.
 /// * 'nonce' - signer's account nonce: u32
 /// * 'era' - Era for extrinsic to be valid
@@ -66,26 +89,19 @@ macro_rules! compose_extrinsic_offline {
 	($signer: expr,
     $call: expr,
     $params: expr) => {{
-		use $crate::{
-			primitives::{ExtrinsicParams, GenericAddress, SignedPayload, UncheckedExtrinsicV4},
-			sp_core::{crypto::Pair, Public},
-			sp_runtime::{generic::Era, traits::IdentifyAccount, MultiSigner},
+		use $crate::primitives::{
+			ExtrinsicParams, SignExtrinsic, SignExtrinsicExt, SignedPayload, UncheckedExtrinsicV4,
 		};
 
+		// Method calls dereference the signer, if it is passed by reference.
+		let signer = &$signer;
 		let extra = $params.signed_extra();
 		let raw_payload =
-			SignedPayload::from_raw($call.clone(), extra, $params.additional_signed());
+			SignedPayload::from_raw($call.clone(), extra.clone(), $params.additional_signed());
 
-		let signature = raw_payload.using_encoded(|payload| $signer.sign(payload));
+		let signature = raw_payload.using_encoded(|payload| signer.sign_extrinsic(payload));
 
-		let multi_signer: MultiSigner = $signer.public().into();
-
-		UncheckedExtrinsicV4::new_signed(
-			$call,
-			GenericAddress::from(multi_signer.into_account()),
-			signature.into(),
-			extra,
-		)
+		UncheckedExtrinsicV4::new_signed($call, signer.extrinsic_address(), signature, extra)
 	}};
 }
 
@@ -96,10 +112,38 @@ macro_rules! compose_extrinsic_offline {
 /// * 'module' - Module name as &str for which the call is composed.
 /// * 'call' - Call name as &str
 /// * 'args' - Optional sequence of arguments of the call. They are not checked against the metadata.
-/// As of now the user needs to check himself that the correct arguments are supplied.
+/// As of now the user needs to check himself that the correct arguments are supplied, or compose
+/// the call with `Metadata::encode_dynamic_call`, which checks them.
+///
+/// Panics where `try_compose_extrinsic!` returns an error, e.g. if the api has a mortal policy,
+/// but the checkpoint of the era can not be fetched from the node.
 #[macro_export]
 #[cfg(feature = "std")]
 macro_rules! compose_extrinsic {
+	($api: expr,
+	$module: expr,
+	$call: expr
+	$(, $args: expr) *) => {
+		$crate::try_compose_extrinsic!($api, $module, $call $(, $args) *).unwrap()
+	};
+}
+
+/// Generates an Unchecked extrinsic like `compose_extrinsic!`, but returns a
+/// `Result<_, ApiClientError>` instead of panicking if the pallet or the call is not in the
+/// metadata, if the nonce of the signer can not be fetched or if the checkpoint of a mortal era
+/// can not be fetched. In the latter case, the reserved nonce is given back.
+///
+/// The nonce of a signed extrinsic is reserved in the nonce manager of the api. If the extrinsic
+/// is not submitted, give it back with `api.nonce_manager().rollback_extrinsic(&xt.encode())`.
+/// # Arguments
+///
+/// * 'api' - This instance of API. If the *signer* field is not set, an unsigned extrinsic will be generated.
+/// * 'module' - Module name as &str for which the call is composed.
+/// * 'call' - Call name as &str
+/// * 'args' - Optional sequence of arguments of the call. They are not checked against the metadata.
+#[macro_export]
+#[cfg(feature = "std")]
+macro_rules! try_compose_extrinsic {
 	($api: expr,
 	$module: expr,
 	$call: expr
@@ -109,22 +153,33 @@ macro_rules! compose_extrinsic {
             use $crate::codec::Compact;
             use $crate::log::debug;
             use $crate::primitives::UncheckedExtrinsicV4;
+            #[allow(unused_imports)] // For when extrinsic does not use Era
             use $crate::sp_runtime::generic::Era;
 
             debug!("Composing generic extrinsic for module {:?} and call {:?}", $module, $call);
-            let call = $crate::compose_call!($api.metadata().clone(), $module, $call $(, ($args)) *);
-            if let Some(signer) = $api.signer() {
-                $crate::compose_extrinsic_offline!(
-                    signer,
-                    call.clone(),
-                    $api.extrinsic_params($api.get_nonce().unwrap())
-                )
-            } else {
-                UncheckedExtrinsicV4 {
-                    signature: None,
-                    function: call.clone(),
-                }
-            }
+            $crate::try_compose_call!($api.metadata(), $module, $call $(, ($args)) *)
+                .map_err(Into::into)
+                .and_then(|call| {
+                    if let Some(signer) = $api.signer() {
+                        $api.next_nonce().and_then(|nonce| {
+                            let params = match $api.extrinsic_params_with_mortality(nonce) {
+                                Ok(params) => params,
+                                Err(error) => {
+                                    // Give the reserved nonce back.
+                                    $api.nonce_manager().rollback(nonce);
+                                    return Err(error)
+                                },
+                            };
+                            let extrinsic =
+                                $crate::compose_extrinsic_offline!(*signer, call, params);
+                            $api.nonce_manager()
+                                .track_extrinsic(nonce, &$crate::codec::Encode::encode(&extrinsic));
+                            Ok(extrinsic)
+                        })
+                    } else {
+                        Ok(UncheckedExtrinsicV4 { signature: None, function: call })
+                    }
+                })
 		}
     };
 }
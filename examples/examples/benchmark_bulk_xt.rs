@@ -40,9 +40,11 @@ async fn main() {
 	// define the recipient
 	let to = AccountKeyring::Bob.to_account_id();
 
-	let mut nonce = api.get_nonce().unwrap();
-	let first_nonce = nonce;
-	while nonce < first_nonce + 500 {
+	for _ in 0..500 {
+		// the nonce manager of the api hands out increasing nonces, accounting for the
+		// extrinsics that are still in the pool
+		let nonce = api.next_nonce().unwrap();
+
 		// compose the extrinsic with all the element
 		#[allow(clippy::redundant_clone)]
 		let xt: UncheckedExtrinsicV4<_, _> = compose_extrinsic_offline!(
@@ -56,7 +58,6 @@ async fn main() {
 
 		println!("sending extrinsic with nonce {}", nonce);
 		let _tx_hash = api.submit_extrinsic(xt.hex_encode()).unwrap();
-
-		nonce += 1;
 	}
+	println!("nonces in flight: {:?}", api.nonce_manager().in_flight());
 }
@@ -20,7 +20,7 @@ use kitchensink_runtime::{BalancesCall, Runtime, RuntimeCall};
 use sp_keyring::AccountKeyring;
 use sp_runtime::{generic::Era, MultiAddress};
 use substrate_api_client::{
-	compose_extrinsic_offline, rpc::JsonrpseeClient, Api, AssetTipExtrinsicParams,
+	compose_extrinsic_offline, rpc::JsonrpseeClient, Api, ApiClientError, AssetTipExtrinsicParams,
 	AssetTipExtrinsicParamsBuilder, GetHeader, SubmitAndWatch, UncheckedExtrinsicV4, XtStatus,
 };
 
@@ -40,9 +40,8 @@ async fn main() {
 	let head = api.get_finalized_head().unwrap().unwrap();
 	let h = api.get_header(Some(head)).unwrap().unwrap();
 	let period = 5;
-	let tx_params = AssetTipExtrinsicParamsBuilder::<Runtime>::new()
-		.era(Era::mortal(period, h.number.into()), head)
-		.tip(0);
+	let era = Era::mortal(period, h.number.into());
+	let tx_params = AssetTipExtrinsicParamsBuilder::<Runtime>::new().era(era, head).tip(0);
 
 	// Set the custom parmas builder:
 	api.set_extrinsic_params_builder(tx_params);
@@ -64,12 +63,22 @@ async fn main() {
 
 	println!("[+] Composed Extrinsic:\n {:?}\n", xt);
 
-	// Send and watch extrinsic until InBlock.
-	match api.submit_and_watch_extrinsic_until(&xt.hex_encode(), XtStatus::InBlock) {
+	// Send and watch extrinsic until InBlock. It stays in the future queue, so we only
+	// wait as long as the extrinsic could be included at all.
+	let timeout = api.mortality_timeout(&era).unwrap();
+	match api.submit_and_watch_extrinsic_until_with_timeout(
+		&xt.hex_encode(),
+		XtStatus::InBlock,
+		timeout,
+	) {
 		Err(error) => {
 			println!("Retrieved error {:?}", error);
-			assert!(format!("{:?}", error).contains("Future"));
+			// The pool may also prune the extrinsic as invalid once its era is over.
+			assert!(matches!(
+				error,
+				ApiClientError::TransactionTimeout(_) | ApiClientError::TransactionInvalid
+			));
 		},
-		_ => panic!("Expected an error upon a future extrinsic"),
+		_ => panic!("Expected a timeout upon a future extrinsic"),
 	}
 }
@@ -0,0 +1,69 @@
+/*
+	Copyright 2019 Supercomputing Systems AG
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+*/
+
+//! This examples shows how to use the pallet_call attribute to generate a typed api method
+//! for a call from a struct of its arguments.
+
+use kitchensink_runtime::Runtime;
+use sp_keyring::AccountKeyring;
+use substrate_api_client::{
+	pallet_call, rpc::JsonrpseeClient, Api, AssetTipExtrinsicParams, GenericAddress,
+	SubmitAndWatch, XtStatus,
+};
+
+#[pallet_call(pallet = "System", call = "remark_with_event")]
+#[derive(Clone)]
+struct RemarkWithEvent {
+	remark: Vec<u8>,
+}
+
+#[pallet_call(pallet = "Balances", call = "transfer", method = "transfer")]
+#[derive(Clone)]
+struct Transfer<Balance> {
+	dest: GenericAddress,
+	#[codec(compact)]
+	value: Balance,
+}
+
+#[tokio::main]
+async fn main() {
+	env_logger::init();
+
+	// initialize api and set the signer (sender) that is used to sign the extrinsics
+	let client = JsonrpseeClient::with_default_url().unwrap();
+	let mut api = Api::<_, _, AssetTipExtrinsicParams<Runtime>, Runtime>::new(client).unwrap();
+	api.set_signer(AccountKeyring::Alice.pair());
+
+	let call_index = RemarkWithEvent::call_index(api.metadata()).unwrap();
+	println!(
+		"[+] {}::{} has the call index {:?}",
+		RemarkWithEvent::PALLET,
+		RemarkWithEvent::CALL,
+		call_index
+	);
+
+	let xt = api.remark_with_event(b"Hello".to_vec());
+	let block_hash = api
+		.submit_and_watch_extrinsic_until(&xt.hex_encode(), XtStatus::InBlock)
+		.unwrap();
+	println!("[+] Remark got included. Hash: {:?}", block_hash);
+
+	let to = GenericAddress::Id(AccountKeyring::Bob.to_account_id());
+	let xt = api.try_transfer(to, 42_u128).unwrap();
+	let block_hash = api
+		.submit_and_watch_extrinsic_until(&xt.hex_encode(), XtStatus::InBlock)
+		.unwrap();
+	println!("[+] Transfer got included. Hash: {:?}", block_hash);
+}
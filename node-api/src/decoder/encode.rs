@@ -103,6 +103,15 @@ fn encode_composite_value<T>(
 	bytes: &mut Vec<u8>,
 ) -> Result<(), EncodeError<T>> {
 	match value.value {
+		ValueDef::Composite(composite)
+			if ty.fields().len() == 1
+				&& composite.len() != 1
+				&& is_bytes_wrapper(type_id, types) =>
+		{
+			// A bytes wrapper type given its inner bytes directly.
+			let inner = Value { value: ValueDef::Composite(composite), context: value.context };
+			encode_value_as_type(inner, ty.fields()[0].ty(), types, bytes)
+		},
 		ValueDef::Composite(composite) =>
 			encode_composite_fields(composite, ty.fields(), type_id, types, bytes),
 		_ => {
@@ -116,6 +125,17 @@ fn encode_composite_value<T>(
 	}
 }
 
+/// 1-field wrapper types which may be given their inner bytes directly, e.g. `AccountId32` as
+/// `Value::from_bytes(account_id)`.
+const BYTES_WRAPPERS: &[&str] = &["AccountId32"];
+
+fn is_bytes_wrapper(type_id: TypeId, types: &PortableRegistry) -> bool {
+	types
+		.resolve(type_id.id())
+		.and_then(|ty| ty.path().ident())
+		.map_or(false, |ident| BYTES_WRAPPERS.contains(&ident.as_ref()))
+}
+
 fn encode_sequence_value<T>(
 	value: Value<T>,
 	type_id: TypeId,
@@ -702,6 +722,31 @@ mod test {
 		assert_can_encode_to_type(named_value, Foo { hello: "world".into(), foo: true });
 	}
 
+	#[test]
+	fn can_encode_bytes_to_account_id() {
+		let account_id = sp_core::crypto::AccountId32::new([7u8; 32]);
+
+		assert_can_encode_to_type(Value::from_bytes([7u8; 32]), account_id);
+		assert_can_encode_to_type(Value::from_bytes(vec![1u8, 2, 3]), vec![1u8, 2, 3]);
+	}
+
+	#[test]
+	fn one_field_structs_are_not_unwrapped() {
+		#[derive(Encode, scale_info::TypeInfo)]
+		struct Wrapper([u8; 3]);
+		let (ty_id, types) = make_type::<Wrapper>();
+		let bytes = Wrapper([1, 2, 3]).encode();
+
+		let value = crate::decode_value_as_type(&mut &*bytes, ty_id, &types).unwrap();
+		let mut encoded = Vec::new();
+		encode_value_as_type(value, ty_id, &types, &mut encoded).unwrap();
+		assert_eq!(encoded, bytes);
+
+		let result =
+			encode_value_as_type(Value::from_bytes([1u8, 2, 3]), ty_id, &types, &mut vec![]);
+		assert!(matches!(result, Err(EncodeError::CompositeIsWrongLength { .. })));
+	}
+
 	#[test]
 	fn can_encode_tuples_from_named_composite() {
 		let named_value = Value::named_composite(vec![
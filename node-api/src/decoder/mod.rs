@@ -13,6 +13,7 @@
 mod bit_sequence;
 mod decode;
 mod encode;
+mod serialize;
 mod value;
 
 /// The portable version of [`scale_info::Type`]
@@ -0,0 +1,106 @@
+// This file is based on the serde implementations of scale-value (Parity Technologies (UK))
+// https://github.com/paritytech/scale-value/
+// And was adapted by Supercomputing Systems AG and Integritee AG.
+//
+// Copyright 2019-2022 Parity Technologies (UK) Ltd, Supercomputing Systems AG and Integritee AG.
+// This file is licensed as Apache-2.0
+// see LICENSE for license details.
+
+//! Serialization of [`Value`]s, e.g. to print them as JSON.
+//! The context of a value is not serialized.
+
+use super::value::{Composite, Primitive, Value, ValueDef, Variant};
+use serde::{
+	ser::{SerializeMap, SerializeSeq},
+	Serialize, Serializer,
+};
+
+impl<T> Serialize for Value<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.value.serialize(serializer)
+	}
+}
+
+impl<T> Serialize for ValueDef<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match self {
+			ValueDef::Composite(val) => val.serialize(serializer),
+			ValueDef::Variant(val) => val.serialize(serializer),
+			ValueDef::Primitive(val) => val.serialize(serializer),
+			ValueDef::BitSequence(val) => {
+				let mut seq = serializer.serialize_seq(Some(val.len()))?;
+				for bit in val.iter().by_vals() {
+					seq.serialize_element(&bit)?;
+				}
+				seq.end()
+			},
+		}
+	}
+}
+
+/// Named composites are serialized as maps, unnamed composites as sequences.
+impl<T> Serialize for Composite<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match self {
+			Composite::Named(values) => {
+				let mut map = serializer.serialize_map(Some(values.len()))?;
+				for (name, value) in values {
+					map.serialize_entry(name, value)?;
+				}
+				map.end()
+			},
+			Composite::Unnamed(values) => {
+				let mut seq = serializer.serialize_seq(Some(values.len()))?;
+				for value in values {
+					seq.serialize_element(value)?;
+				}
+				seq.end()
+			},
+		}
+	}
+}
+
+/// Variants are serialized as `{ "name": .., "values": .. }`.
+impl<T> Serialize for Variant<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut map = serializer.serialize_map(Some(2))?;
+		map.serialize_entry("name", &self.name)?;
+		map.serialize_entry("values", &self.values)?;
+		map.end()
+	}
+}
+
+impl Serialize for Primitive {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match self {
+			Primitive::Bool(val) => serializer.serialize_bool(*val),
+			Primitive::Char(val) => serializer.serialize_char(*val),
+			Primitive::String(val) => serializer.serialize_str(val),
+			Primitive::U128(val) => serializer.serialize_u128(*val),
+			Primitive::I128(val) => serializer.serialize_i128(*val),
+			Primitive::U256(val) | Primitive::I256(val) => val.serialize(serializer),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::alloc::string::ToString;
+
+	#[test]
+	fn serialize_composites_and_variants_to_json() {
+		let value = Value::named_composite(vec![
+			("id".to_string(), Value::uint(1u8)),
+			("flags".to_string(), Value::unnamed_composite(vec![Value::bool(true)])),
+			("kind".to_string(), Value::unnamed_variant("Foo", vec![Value::string("bar")])),
+		]);
+
+		let json = serde_json::to_string(&value).unwrap();
+
+		assert_eq!(
+			json,
+			r#"{"id":1,"flags":[true],"kind":{"name":"Foo","values":["bar"]}}"#.to_string()
+		);
+	}
+}
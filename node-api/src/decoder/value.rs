@@ -74,6 +74,12 @@ impl Value<()> {
 	pub fn int<N: Into<i128>>(val: N) -> Value<()> {
 		Value { value: ValueDef::Primitive(Primitive::int(val)), context: () }
 	}
+	/// Create a new unnamed composite of `u8`s from some bytes, without additional context.
+	/// This is the shape byte arrays such as account ids or hashes are decoded to.
+	pub fn from_bytes<B: AsRef<[u8]>>(bytes: B) -> Value<()> {
+		let values = bytes.as_ref().iter().map(|&b| Value::uint(b)).collect();
+		Value::unnamed_composite(values)
+	}
 }
 
 impl Value<()> {
@@ -60,6 +60,8 @@ pub enum Error {
 	Transaction(TransactionError),
 	/// Block related error.
 	Block(BlockError),
+	/// Error decoding an extrinsic.
+	Extrinsic(ExtrinsicError),
 	/// An error encoding a storage address.
 	StorageAddress(StorageAddressError),
 	/// Other error.
@@ -194,6 +196,28 @@ impl BlockError {
 	}
 }
 
+/// Something went wrong trying to decode an extrinsic with the metadata.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExtrinsicError {
+	/// The length prefix does not match the number of extrinsic bytes.
+	LengthMismatch {
+		/// The length given by the prefix.
+		expected: usize,
+		/// The actual number of bytes after the prefix.
+		actual: usize,
+	},
+	/// Bytes are left over after decoding the call.
+	UnconsumedBytes(usize),
+	/// The first byte encodes an unsupported version or extrinsic type.
+	UnsupportedVersion(u8),
+	/// The extrinsic type of the metadata has no type parameter with this name.
+	TypeParamNotFound(&'static str),
+	/// The pallet of the call has no calls.
+	PalletHasNoCalls(String),
+	/// The call type of the pallet with this type id is not an enum.
+	CallTypeNotVariant(u32),
+}
+
 /// Transaction error.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum TransactionError {
@@ -0,0 +1,491 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+		http://www.apache.org/licenses/LICENSE-2.0
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+*/
+
+//! Dynamic decoding of extrinsics and encoding of calls with the runtime metadata, without
+//! knowing the concrete extrinsic and call types of the runtime at compile time.
+
+use crate::{
+	alloc::{
+		string::{String, ToString},
+		vec,
+		vec::Vec,
+	},
+	decoder::{
+		decode_as_type, encode_value_as_type, Composite, EncodeError, TypeId, Value, ValueDef,
+		Variant,
+	},
+	error::{Error, ExtrinsicError},
+	metadata::{Metadata, MetadataError, PalletMetadata},
+	Encoded,
+};
+use codec::{Compact, Decode};
+use scale_info::TypeDef;
+
+const VERSION_MASK: u8 = 0b0011_1111;
+const TYPE_MASK: u8 = 0b1100_0000;
+const BARE_EXTRINSIC: u8 = 0b0000_0000;
+const SIGNED_EXTRINSIC: u8 = 0b1000_0000;
+const GENERAL_EXTRINSIC: u8 = 0b0100_0000;
+
+/// A dynamically decoded extrinsic.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtrinsicDetails {
+	/// The version of the extrinsic format.
+	pub version: u8,
+	/// The address of the signer, if the extrinsic is signed.
+	pub address: Option<Value<TypeId>>,
+	/// The signature, if the extrinsic is signed.
+	pub signature: Option<Value<TypeId>>,
+	/// The version of the transaction extensions of a general (v5) extrinsic.
+	pub extension_version: Option<u8>,
+	/// The signed extension values by identifier. Empty for bare extrinsics.
+	pub signed_extensions: Vec<(String, Value<TypeId>)>,
+	/// The dispatched call.
+	pub call: CallDetails,
+}
+
+impl ExtrinsicDetails {
+	/// Returns true if the extrinsic carries a signature.
+	pub fn is_signed(&self) -> bool {
+		self.signature.is_some()
+	}
+
+	/// Returns the value of the signed extension with the given identifier, e.g. `CheckNonce`.
+	pub fn signed_extension(&self, identifier: &str) -> Option<&Value<TypeId>> {
+		self.signed_extensions
+			.iter()
+			.find(|(name, _)| name == identifier)
+			.map(|(_, value)| value)
+	}
+}
+
+/// A dynamically decoded call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallDetails {
+	pub pallet_index: u8,
+	pub pallet_name: String,
+	pub call_index: u8,
+	pub call_name: String,
+	pub fields: Composite<TypeId>,
+}
+
+impl Metadata {
+	/// Returns a reference to the [`PalletMetadata`] of the pallet with the given index.
+	pub fn pallet_by_index(&self, index: u8) -> Result<&PalletMetadata, MetadataError> {
+		self.pallets
+			.values()
+			.find(|pallet| pallet.index == index)
+			.ok_or(MetadataError::PalletIndexNotFound(index))
+	}
+
+	/// Decode the SCALE encoded (length prefixed) `extrinsic`, e.g. an opaque extrinsic of a block.
+	pub fn decode_extrinsic(&self, extrinsic: &[u8]) -> Result<ExtrinsicDetails, Error> {
+		let input = &mut &*extrinsic;
+
+		let length = <Compact<u32>>::decode(input)?.0 as usize;
+		if length != input.len() {
+			return Err(
+				ExtrinsicError::LengthMismatch { expected: length, actual: input.len() }.into()
+			)
+		}
+
+		let first_byte = u8::decode(input)?;
+		let version = first_byte & VERSION_MASK;
+		let extrinsic_type = first_byte & TYPE_MASK;
+
+		let (address, signature, extension_version) = match (version, extrinsic_type) {
+			(4 | 5, BARE_EXTRINSIC) => (None, None, None),
+			(4, SIGNED_EXTRINSIC) => {
+				let address =
+					decode_as_type(input, self.extrinsic_type_param("Address")?, self.types())?;
+				let signature =
+					decode_as_type(input, self.extrinsic_type_param("Signature")?, self.types())?;
+				(Some(address), Some(signature), None)
+			},
+			(5, GENERAL_EXTRINSIC) => (None, None, Some(u8::decode(input)?)),
+			_ => return Err(ExtrinsicError::UnsupportedVersion(first_byte).into()),
+		};
+
+		let signed_extensions = match (&signature, extension_version) {
+			(None, None) => Vec::new(),
+			_ => self.decode_signed_extensions(input)?,
+		};
+
+		let call = self.decode_call(input)?;
+		if !input.is_empty() {
+			return Err(ExtrinsicError::UnconsumedBytes(input.len()).into())
+		}
+
+		Ok(ExtrinsicDetails {
+			version,
+			address,
+			signature,
+			extension_version,
+			signed_extensions,
+			call,
+		})
+	}
+
+	/// Decode a SCALE encoded call, prefixed by its pallet and call index.
+	pub fn decode_call(&self, input: &mut &[u8]) -> Result<CallDetails, Error> {
+		let pallet_index = u8::decode(input)?;
+		let pallet = self.pallet_by_index(pallet_index)?;
+		let call_ty_id = pallet
+			.call_ty_id()
+			.ok_or_else(|| ExtrinsicError::PalletHasNoCalls(pallet.name().to_string()))?;
+
+		let call_index = *input.first().ok_or(codec::Error::from("Missing call index"))?;
+		let call = decode_as_type(input, call_ty_id, self.types())?;
+		match call.value {
+			ValueDef::Variant(variant) => Ok(CallDetails {
+				pallet_index,
+				pallet_name: pallet.name().to_string(),
+				call_index,
+				call_name: variant.name,
+				fields: variant.values,
+			}),
+			_ => Err(ExtrinsicError::CallTypeNotVariant(call_ty_id).into()),
+		}
+	}
+
+	/// Encode the call `call` of `pallet` with the given arguments. The arguments are checked
+	/// against the call's fields in the metadata: Named arguments must match the field names,
+	/// unnamed ones are encoded in the order of the fields.
+	pub fn encode_dynamic_call(
+		&self,
+		pallet: &str,
+		call: &str,
+		fields: Composite<()>,
+	) -> Result<Encoded, Error> {
+		let pallet = self.pallet(pallet)?;
+		let call_ty_id = pallet
+			.call_ty_id()
+			.ok_or_else(|| ExtrinsicError::PalletHasNoCalls(pallet.name().to_string()))?;
+		let call_variants = match self.resolve_type(call_ty_id).map(|ty| ty.type_def()) {
+			Some(TypeDef::Variant(variants)) => variants.variants(),
+			Some(_) => return Err(ExtrinsicError::CallTypeNotVariant(call_ty_id).into()),
+			None => return Err(MetadataError::TypeNotFound(call_ty_id).into()),
+		};
+		let call_variant = match call_variants.iter().find(|variant| variant.name() == call) {
+			Some(call_variant) => call_variant,
+			None => {
+				let actual = Variant { name: call.to_string(), values: fields };
+				return Err(
+					EncodeError::VariantNotFound { actual, expected: call_ty_id.into() }.into()
+				)
+			},
+		};
+
+		let call_fields = call_variant.fields();
+		if call_fields.len() != fields.len() {
+			return Err(EncodeError::CompositeIsWrongLength {
+				actual: fields,
+				expected: call_ty_id.into(),
+				expected_len: call_fields.len(),
+			}
+			.into())
+		}
+
+		let mut encoded_call = vec![pallet.index, call_variant.index()];
+		match fields {
+			Composite::Named(mut values) =>
+				for field in call_fields {
+					let field_name = field.name().cloned().unwrap_or_default();
+					let value = match values.iter().position(|(name, _)| *name == field_name) {
+						Some(position) => values.swap_remove(position).1,
+						None =>
+							return Err(EncodeError::CompositeFieldIsMissing {
+								missing_field_name: field_name,
+								expected: call_ty_id.into(),
+							}
+							.into()),
+					};
+					encode_value_as_type(value, field.ty(), self.types(), &mut encoded_call)?;
+				},
+			Composite::Unnamed(values) =>
+				for (field, value) in call_fields.iter().zip(values) {
+					encode_value_as_type(value, field.ty(), self.types(), &mut encoded_call)?;
+				},
+		}
+		Ok(Encoded(encoded_call))
+	}
+
+	fn decode_signed_extensions(
+		&self,
+		input: &mut &[u8],
+	) -> Result<Vec<(String, Value<TypeId>)>, Error> {
+		self.metadata
+			.extrinsic
+			.signed_extensions
+			.iter()
+			.map(|signed_extension| {
+				let value = decode_as_type(input, signed_extension.ty.id(), self.types())?;
+				Ok((signed_extension.identifier.clone(), value))
+			})
+			.collect()
+	}
+
+	/// The type id of the generic type parameter `name` of the runtime's extrinsic type.
+	fn extrinsic_type_param(&self, name: &'static str) -> Result<u32, Error> {
+		self.resolve_type(self.metadata.extrinsic.ty.id())
+			.and_then(|ty| ty.type_params().iter().find(|param| param.name() == name))
+			.and_then(|param| param.ty())
+			.map(|ty| ty.id())
+			.ok_or_else(|| ExtrinsicError::TypeParamNotFound(name).into())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use core::marker::PhantomData;
+	use frame_metadata::{
+		v14::{
+			ExtrinsicMetadata, PalletCallMetadata, PalletMetadata, RuntimeMetadataV14,
+			SignedExtensionMetadata,
+		},
+		RuntimeMetadataPrefixed,
+	};
+	use scale_info::{meta_type, TypeInfo};
+
+	#[allow(unused)]
+	#[derive(TypeInfo)]
+	struct Extrinsic<Address, Call, Signature, Extra>(
+		PhantomData<(Address, Call, Signature, Extra)>,
+	);
+
+	#[allow(unused)]
+	#[derive(Encode, TypeInfo)]
+	enum BalancesCall {
+		#[codec(index = 3)]
+		Transfer {
+			dest: [u8; 2],
+			#[codec(compact)]
+			value: u128,
+		},
+	}
+
+	#[allow(unused)]
+	#[derive(Encode, TypeInfo)]
+	enum RuntimeCall {
+		#[codec(index = 5)]
+		Balances(BalancesCall),
+	}
+
+	fn metadata() -> Metadata {
+		let pallets = vec![
+			PalletMetadata {
+				name: "System",
+				storage: None,
+				calls: None,
+				event: None,
+				constants: vec![],
+				error: None,
+				index: 0,
+			},
+			PalletMetadata {
+				name: "Balances",
+				storage: None,
+				calls: Some(PalletCallMetadata { ty: meta_type::<BalancesCall>() }),
+				event: None,
+				constants: vec![],
+				error: None,
+				index: 5,
+			},
+		];
+		let extrinsic = ExtrinsicMetadata {
+			ty: meta_type::<Extrinsic<[u8; 2], RuntimeCall, [u8; 4], u32>>(),
+			version: 4,
+			signed_extensions: vec![
+				SignedExtensionMetadata {
+					identifier: "CheckNonce",
+					ty: meta_type::<Compact<u32>>(),
+					additional_signed: meta_type::<()>(),
+				},
+				SignedExtensionMetadata {
+					identifier: "CheckGenesis",
+					ty: meta_type::<()>(),
+					additional_signed: meta_type::<[u8; 32]>(),
+				},
+			],
+		};
+		let v14 = RuntimeMetadataV14::new(pallets, extrinsic, meta_type::<()>());
+		Metadata::try_from(RuntimeMetadataPrefixed::from(v14)).unwrap()
+	}
+
+	fn call() -> RuntimeCall {
+		RuntimeCall::Balances(BalancesCall::Transfer { dest: [1, 2], value: 1000 })
+	}
+
+	fn expected_call() -> CallDetails {
+		CallDetails {
+			pallet_index: 5,
+			pallet_name: "Balances".into(),
+			call_index: 3,
+			call_name: "Transfer".into(),
+			fields: Composite::Named(vec![
+				("dest".into(), Value::from_bytes([1u8, 2])),
+				("value".into(), Value::uint(1000u128)),
+			]),
+		}
+	}
+
+	fn assert_call_eq(call: &CallDetails, expected: CallDetails) {
+		assert_eq!(
+			(&call.pallet_name, call.pallet_index, &call.call_name, call.call_index),
+			(
+				&expected.pallet_name,
+				expected.pallet_index,
+				&expected.call_name,
+				expected.call_index
+			)
+		);
+		assert_eq!(call.fields.clone().map_context(|_| ()), expected.fields);
+	}
+
+	fn without_context(details: &ExtrinsicDetails) -> Vec<Value<()>> {
+		details
+			.address
+			.iter()
+			.chain(details.signature.iter())
+			.chain(details.signed_extensions.iter().map(|(_, value)| value))
+			.map(|value| value.clone().remove_context())
+			.collect()
+	}
+
+	#[test]
+	fn decode_signed_extrinsic() {
+		let metadata = metadata();
+		let mut xt = vec![0b1000_0100];
+		xt.extend(([7u8, 8], [9u8; 4], Compact(12u32), call()).encode());
+
+		let details = metadata.decode_extrinsic(&xt.encode()).unwrap();
+
+		assert_eq!(details.version, 4);
+		assert!(details.is_signed());
+		assert_eq!(
+			without_context(&details),
+			vec![
+				Value::from_bytes([7u8, 8]),
+				Value::from_bytes([9u8; 4]),
+				Value::uint(12u32),
+				Value::unnamed_composite(vec![]),
+			]
+		);
+		assert!(details.signed_extension("CheckNonce").is_some());
+		assert_call_eq(&details.call, expected_call());
+	}
+
+	#[test]
+	fn decode_bare_and_general_extrinsics() {
+		let metadata = metadata();
+		let mut bare = vec![0b0000_0101];
+		bare.extend(call().encode());
+		let mut general = vec![0b0100_0101, 0];
+		general.extend((Compact(12u32), call()).encode());
+
+		let bare = metadata.decode_extrinsic(&bare.encode()).unwrap();
+		let general = metadata.decode_extrinsic(&general.encode()).unwrap();
+
+		assert_eq!((bare.version, bare.is_signed()), (5, false));
+		assert!(bare.signed_extensions.is_empty());
+		assert_eq!((general.version, general.extension_version), (5, Some(0)));
+		assert!(!general.is_signed());
+		assert_eq!(
+			without_context(&general),
+			vec![Value::uint(12u32), Value::unnamed_composite(vec![])]
+		);
+		assert_call_eq(&general.call, expected_call());
+	}
+
+	#[test]
+	fn invalid_extrinsics_are_rejected() {
+		let metadata = metadata();
+		let mut signed_v5 = vec![0b1000_0101];
+		signed_v5.extend(call().encode());
+		let mut unknown_pallet = vec![0b0000_0100];
+		unknown_pallet.extend([1u8, 0]);
+
+		assert!(matches!(
+			metadata.decode_extrinsic(&signed_v5.encode()),
+			Err(Error::Extrinsic(ExtrinsicError::UnsupportedVersion(0b1000_0101)))
+		));
+		assert!(matches!(
+			metadata.decode_extrinsic(&unknown_pallet.encode()),
+			Err(Error::Metadata(MetadataError::PalletIndexNotFound(1)))
+		));
+		assert!(matches!(
+			metadata.decode_extrinsic(&[12, 4, 5]),
+			Err(Error::Extrinsic(ExtrinsicError::LengthMismatch { expected: 3, actual: 2 }))
+		));
+	}
+
+	#[test]
+	fn encode_dynamic_call_equals_static_call() {
+		let metadata = metadata();
+		let named = Composite::Named(vec![
+			("value".into(), Value::uint(1000u128)),
+			("dest".into(), Value::from_bytes([1u8, 2])),
+		]);
+		let unnamed = Composite::Unnamed(vec![Value::from_bytes([1u8, 2]), Value::uint(1000u128)]);
+
+		let named = metadata.encode_dynamic_call("Balances", "Transfer", named).unwrap();
+		let unnamed = metadata.encode_dynamic_call("Balances", "Transfer", unnamed).unwrap();
+
+		assert_eq!(named.encode(), call().encode());
+		assert_eq!(unnamed.encode(), call().encode());
+		let decoded = metadata.decode_call(&mut named.0.as_slice()).unwrap();
+		assert_call_eq(&decoded, expected_call());
+	}
+
+	#[test]
+	fn encode_dynamic_call_checks_arguments() {
+		let metadata = metadata();
+		let wrong_name = Composite::Named(vec![
+			("to".into(), Value::from_bytes([1u8, 2])),
+			("value".into(), Value::uint(1000u128)),
+		]);
+		let missing_value = Composite::Unnamed(vec![Value::from_bytes([1u8, 2])]);
+
+		assert!(matches!(
+			metadata.encode_dynamic_call("Balances", "Transfer", wrong_name),
+			Err(Error::EncodeValue(EncodeError::CompositeFieldIsMissing { .. }))
+		));
+		assert!(matches!(
+			metadata.encode_dynamic_call("Balances", "Transfer", missing_value),
+			Err(Error::EncodeValue(EncodeError::CompositeIsWrongLength { expected_len: 2, .. }))
+		));
+		assert!(matches!(
+			metadata.encode_dynamic_call("Balances", "Burn", Composite::Unnamed(vec![])),
+			Err(Error::EncodeValue(EncodeError::VariantNotFound { .. }))
+		));
+		assert!(matches!(
+			metadata.encode_dynamic_call("System", "remark", Composite::Unnamed(vec![])),
+			Err(Error::Extrinsic(ExtrinsicError::PalletHasNoCalls(_)))
+		));
+	}
+
+	#[test]
+	fn call_index_is_found_by_a_name_known_at_runtime() {
+		let metadata = metadata();
+		let pallet = metadata.pallet("Balances").unwrap();
+		let call_name = String::from("Transfer");
+
+		assert_eq!(pallet.call_index(&call_name), Ok(3));
+		assert_eq!(
+			pallet.call_index(&format!("{}s", call_name)),
+			Err(MetadataError::CallNotFound("Transfers".into()))
+		);
+	}
+}
@@ -23,14 +23,23 @@ use codec::{Decode, Encode};
 pub use decoder::*;
 pub use error::*;
 pub use events::*;
+pub use extrinsics::*;
 pub use metadata::*;
+pub use metadata_hash::{MetadataDigest, MetadataHashInfo};
 pub use storage::*;
+pub use storage_address::*;
+// re-export for the code generated by `ac-codegen`
+pub use bitvec;
+pub use scale_info;
 
 pub mod decoder;
 pub mod error;
 pub mod events;
+pub mod extrinsics;
 pub mod metadata;
+pub mod metadata_hash;
 pub mod storage;
+pub mod storage_address;
 
 #[cfg(feature = "std")]
 mod print_metadata;
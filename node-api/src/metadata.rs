@@ -10,7 +10,7 @@
 //!
 //! This file is mostly subxt.
 
-use crate::{alloc::borrow::ToOwned, storage::GetStorageTypes, Encoded};
+use crate::{alloc::borrow::ToOwned, error::Error, storage::GetStorageTypes, Encoded, Value};
 use codec::{Decode, Encode, Error as CodecError};
 use frame_metadata::{
 	PalletConstantMetadata, RuntimeMetadata, RuntimeMetadataLastVersion, RuntimeMetadataPrefixed,
@@ -40,13 +40,15 @@ pub enum MetadataError {
 	/// Pallet is not in metadata.
 	PalletIndexNotFound(u8),
 	/// Call is not in metadata.
-	CallNotFound(&'static str),
+	CallNotFound(String),
 	/// Event is not in metadata.
 	EventNotFound(u8, u8),
 	/// Error is not in metadata.
 	ErrorNotFound(u8, u8),
 	/// Storage is not in metadata.
 	StorageNotFound(&'static str),
+	/// Storage, looked up by a name known at runtime only, is not in metadata.
+	StorageEntryNotFound(String),
 	/// Storage type does not match requested type.
 	StorageTypeError,
 	/// Default error.
@@ -65,6 +67,8 @@ pub enum MetadataError {
 	IncompatibleStorageMetadata(String, String),
 	/// Runtime metadata is not fully compatible with the static one.
 	IncompatibleMetadata,
+	/// Runtime metadata can not be merkleized to calculate the metadata hash.
+	MerkleizationError(String),
 }
 
 #[derive(Clone, Debug, Encode, Decode)]
@@ -88,7 +92,7 @@ pub struct Metadata {
 
 impl Metadata {
 	/// Returns a reference to [`PalletMetadata`].
-	pub fn pallet(&self, name: &'static str) -> Result<&PalletMetadata, MetadataError> {
+	pub fn pallet(&self, name: &str) -> Result<&PalletMetadata, MetadataError> {
 		self.pallets
 			.get(name)
 			.ok_or_else(|| MetadataError::PalletNotFound(name.to_string()))
@@ -160,6 +164,22 @@ impl Metadata {
 		&self.metadata
 	}
 
+	/// Return the version of the extrinsic format of the runtime.
+	pub fn extrinsic_version(&self) -> u8 {
+		self.metadata.extrinsic.version
+	}
+
+	/// Return the identifiers of the signed extensions of the runtime, in the order
+	/// their data is encoded in an extrinsic.
+	pub fn signed_extension_identifiers(&self) -> Vec<String> {
+		self.metadata
+			.extrinsic
+			.signed_extensions
+			.iter()
+			.map(|signed_extension| signed_extension.identifier.clone())
+			.collect()
+	}
+
 	#[cfg(feature = "std")]
 	pub fn pretty_format<Metadata: Serialize>(metadata: &Metadata) -> Option<String> {
 		let buf = Vec::new();
@@ -200,9 +220,11 @@ impl PalletMetadata {
 
 	/// Attempt to resolve a call into an index in this pallet, failing
 	/// if the call is not found in this pallet.
-	pub fn call_index(&self, function: &'static str) -> Result<u8, MetadataError> {
-		let fn_index =
-			*self.call_indexes.get(function).ok_or(MetadataError::CallNotFound(function))?;
+	pub fn call_index(&self, function: &str) -> Result<u8, MetadataError> {
+		let fn_index = *self
+			.call_indexes
+			.get(function)
+			.ok_or_else(|| MetadataError::CallNotFound(function.to_string()))?;
 		Ok(fn_index)
 	}
 
@@ -213,6 +235,16 @@ impl PalletMetadata {
 		self.storage.get(key).ok_or(MetadataError::StorageNotFound(key))
 	}
 
+	/// Get a storage entry's metadata by a name, which is not known at compile time.
+	pub fn storage_entry(
+		&self,
+		key: &str,
+	) -> Result<&StorageEntryMetadata<PortableForm>, MetadataError> {
+		self.storage
+			.get(key)
+			.ok_or_else(|| MetadataError::StorageEntryNotFound(key.to_string()))
+	}
+
 	/// Get a constant's metadata by name
 	pub fn constant(
 		&self,
@@ -221,7 +253,7 @@ impl PalletMetadata {
 		self.constants.get(key).ok_or(MetadataError::ConstantNotFound(key))
 	}
 
-	pub fn encode_call<C>(&self, call_name: &'static str, args: C) -> Result<Encoded, MetadataError>
+	pub fn encode_call<C>(&self, call_name: &str, args: C) -> Result<Encoded, MetadataError>
 	where
 		C: Encode,
 	{
@@ -503,4 +535,50 @@ impl Metadata {
 			.get_double_map::<K, Q>(storage_prefix)?
 			.key(first, second))
 	}
+
+	/// Returns the storage key of any storage entry, with the map keys given as dynamic [`Value`]s.
+	/// The keys are encoded with the key types found in the metadata.
+	///
+	/// Less keys than hashers may be supplied, in which case the returned key is the prefix of
+	/// all entries beginning with the given keys.
+	pub fn storage_key_from_values(
+		&self,
+		storage_prefix: &str,
+		storage_key_name: &str,
+		keys: Vec<Value>,
+	) -> Result<StorageKey, Error> {
+		self.pallet(storage_prefix)?
+			.storage_entry(storage_key_name)?
+			.get_key_from_values(storage_prefix, keys, self.types())
+	}
+
+	/// Returns the type id of the value stored in the given storage entry.
+	pub fn storage_value_ty_id(
+		&self,
+		storage_prefix: &str,
+		storage_key_name: &str,
+	) -> Result<u32, MetadataError> {
+		Ok(self.pallet(storage_prefix)?.storage_entry(storage_key_name)?.get_value_ty_id())
+	}
+
+	/// Returns the pallet and the storage entry the given storage key belongs to, found by
+	/// matching the `twox_128` hashed pallet and storage names the key starts with.
+	pub fn storage_entry_for_key(
+		&self,
+		key: &[u8],
+	) -> Option<(&PalletMetadata, &StorageEntryMetadata<PortableForm>)> {
+		if key.len() < 32 {
+			return None
+		}
+		self.pallets.values().find_map(|pallet| {
+			if sp_core::twox_128(pallet.name.as_bytes()) != key[..16] {
+				return None
+			}
+			pallet
+				.storage
+				.values()
+				.find(|entry| sp_core::twox_128(entry.name.as_bytes()) == key[16..32])
+				.map(|entry| (pallet, entry))
+		})
+	}
 }
@@ -0,0 +1,57 @@
+// This file was taken from merkleized-metadata (Bastian Köcher)
+// https://github.com/bkchr/merkleized-metadata
+// And was adapted by Supercomputing Systems AG.
+//
+// This file is licensed as Apache-2.0
+// see LICENSE for license details.
+
+//! Root of the merkle tree over the types of the metadata.
+
+use super::types::{Hash, Type};
+use alloc::collections::VecDeque;
+use codec::Encode;
+
+/// Calculate the root of the complete binary tree with the given `leaves`, in order.
+///
+/// The two last nodes of the queue are hashed together and pushed to its front, until a
+/// single node is left. The root of an empty tree is zero.
+pub(crate) fn merkle_root(leaves: impl IntoIterator<Item = Type>) -> Hash {
+	let mut nodes = leaves.into_iter().map(|ty| ty.hash()).collect::<VecDeque<_>>();
+
+	while nodes.len() > 1 {
+		let right = nodes.pop_back().expect("There are at least two nodes; qed");
+		let left = nodes.pop_back().expect("There are at least two nodes; qed");
+		nodes.push_front(blake3::hash(&(left, right).encode()).into());
+	}
+
+	nodes.pop_back().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::metadata_hash::types::{TypeDef, TypeRef};
+	use alloc::vec;
+
+	fn leaf(id: u32) -> Type {
+		Type { path: vec![], type_def: TypeDef::Sequence(TypeRef::U8), type_id: id.into() }
+	}
+
+	fn node(left: Hash, right: Hash) -> Hash {
+		blake3::hash(&(left, right).encode()).into()
+	}
+
+	#[test]
+	fn root_of_single_leaf_is_its_hash() {
+		assert_eq!(merkle_root(vec![leaf(0)]), leaf(0).hash());
+		assert_eq!(merkle_root(vec![]), [0u8; 32]);
+	}
+
+	#[test]
+	fn last_nodes_are_hashed_first() {
+		let (a, b, c) = (leaf(0).hash(), leaf(1).hash(), leaf(2).hash());
+
+		assert_eq!(merkle_root(vec![leaf(0), leaf(1)]), node(a, b));
+		assert_eq!(merkle_root(vec![leaf(0), leaf(1), leaf(2)]), node(node(b, c), a));
+	}
+}
@@ -0,0 +1,203 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+		http://www.apache.org/licenses/LICENSE-2.0
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+*/
+
+//! Metadata hash as committed to by the `CheckMetadataHash` signed extension.
+//!
+//! The types needed to decode extrinsics are merkleized as described in
+//! [RFC-78](https://polkadot-fellows.github.io/RFCs/approved/0078-merkleized-metadata.html),
+//! which allows offline signers to verify what they sign with a proof of the types used by
+//! an extrinsic, instead of the full metadata.
+
+use crate::metadata::{Metadata, MetadataError};
+use alloc::string::String;
+use merkle_tree::merkle_root;
+use type_information::TypeInformation;
+
+pub use types::MetadataDigest;
+
+mod merkle_tree;
+mod type_information;
+pub mod types;
+
+/// Chain information that is part of the [`MetadataDigest`], next to the merkleized types.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetadataHashInfo {
+	/// The spec version of the runtime.
+	pub spec_version: u32,
+	/// The spec name of the runtime.
+	pub spec_name: String,
+	/// The SS58 prefix of addresses.
+	pub base58_prefix: u16,
+	/// The number of decimals of the native token.
+	pub decimals: u8,
+	/// The symbol of the native token.
+	pub token_symbol: String,
+}
+
+impl Metadata {
+	/// Return the RFC-78 digest of the metadata.
+	pub fn metadata_digest(&self, info: MetadataHashInfo) -> Result<MetadataDigest, MetadataError> {
+		let type_information = TypeInformation::new(&self.metadata)?;
+		Ok(MetadataDigest::V1 {
+			types_tree_root: merkle_root(type_information.types),
+			extrinsic_metadata_hash: type_information.extrinsic_metadata.hash(),
+			spec_version: info.spec_version,
+			spec_name: info.spec_name,
+			base58_prefix: info.base58_prefix,
+			decimals: info.decimals,
+			token_symbol: info.token_symbol,
+		})
+	}
+
+	/// Return the metadata hash the `CheckMetadataHash` signed extension commits to,
+	/// which is the hash of the [`MetadataDigest`].
+	pub fn metadata_hash(&self, info: MetadataHashInfo) -> Result<[u8; 32], MetadataError> {
+		Ok(self.metadata_digest(info)?.hash())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{types::*, *};
+	use codec::{Compact, Decode, Encode};
+	use core::marker::PhantomData;
+	use frame_metadata::{
+		v14::{ExtrinsicMetadata, RuntimeMetadataV14, SignedExtensionMetadata},
+		RuntimeMetadataPrefixed,
+	};
+	use scale_info::{meta_type, TypeInfo};
+	use std::convert::TryFrom;
+
+	#[allow(dead_code)]
+	#[derive(TypeInfo)]
+	struct Extrinsic<Address, Call, Signature, Extra>(
+		PhantomData<(Address, Call, Signature, Extra)>,
+	);
+
+	#[allow(dead_code)]
+	#[derive(Encode, Decode, TypeInfo)]
+	enum Call {
+		#[codec(index = 3)]
+		Transfer { dest: [u8; 32], value: Compact<u128> },
+		#[codec(index = 1)]
+		Remark(Vec<u8>),
+	}
+
+	#[allow(dead_code)]
+	#[derive(Encode, Decode, TypeInfo)]
+	struct CheckNonce(Compact<u32>);
+
+	fn info(spec_version: u32) -> MetadataHashInfo {
+		MetadataHashInfo {
+			spec_version,
+			spec_name: "test".into(),
+			base58_prefix: 42,
+			decimals: 12,
+			token_symbol: "UNIT".into(),
+		}
+	}
+
+	fn metadata() -> Metadata {
+		let extrinsic = ExtrinsicMetadata {
+			ty: meta_type::<Extrinsic<u32, Call, [u8; 64], ()>>(),
+			version: 4,
+			signed_extensions: vec![SignedExtensionMetadata {
+				identifier: "CheckNonce",
+				ty: meta_type::<CheckNonce>(),
+				additional_signed: meta_type::<()>(),
+			}],
+		};
+		let v14 = RuntimeMetadataV14::new(vec![], extrinsic, meta_type::<()>());
+		let runtime_metadata: RuntimeMetadataPrefixed = v14.into();
+		Metadata::try_from(runtime_metadata).unwrap()
+	}
+
+	#[test]
+	fn extrinsic_types_are_merkleized_per_enum_variant() {
+		let type_information = TypeInformation::new(&metadata().metadata).unwrap();
+
+		let extrinsic_metadata = type_information.extrinsic_metadata;
+		assert_eq!(extrinsic_metadata.address_ty, TypeRef::U32);
+		assert!(matches!(extrinsic_metadata.call_ty, TypeRef::ById(_)));
+		assert!(matches!(extrinsic_metadata.signature_ty, TypeRef::ById(_)));
+		assert!(matches!(
+			extrinsic_metadata.signed_extensions[0].included_in_extrinsic,
+			TypeRef::ById(_)
+		));
+		assert_eq!(extrinsic_metadata.signed_extensions[0].included_in_signed_data, TypeRef::Void);
+
+		let call_variants: Vec<_> = type_information
+			.types
+			.iter()
+			.filter_map(|ty| match &ty.type_def {
+				TypeDef::Enumeration(variant) => Some((variant.name.as_str(), variant.index.0)),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(call_variants, vec![("Remark", 1), ("Transfer", 3)]);
+
+		let transfer_value = type_information.types.iter().find_map(|ty| match &ty.type_def {
+			TypeDef::Enumeration(variant) if variant.name == "Transfer" =>
+				Some(variant.fields[1].ty),
+			_ => None,
+		});
+		assert_eq!(transfer_value, Some(TypeRef::CompactU128));
+	}
+
+	#[test]
+	fn metadata_hash_commits_to_chain_information() {
+		let metadata = metadata();
+
+		let hash = metadata.metadata_hash(info(1)).unwrap();
+
+		assert_eq!(hash, metadata.metadata_digest(info(1)).unwrap().hash());
+		assert_ne!(hash, metadata.metadata_hash(info(2)).unwrap());
+	}
+
+	/// Known answer of the reference implementation, the `merkleized-metadata` crate 0.1.0,
+	/// for its Polkadot fixture. The fixture is metadata V15, it is converted to V14 by using
+	/// the `UncheckedExtrinsic` type of the runtime as extrinsic type.
+	#[test]
+	fn metadata_hash_of_polkadot_matches_reference_implementation() {
+		let bytes = include_bytes!("../../test_data/polkadot_metadata_v14.scale");
+		let runtime_metadata = RuntimeMetadataPrefixed::decode(&mut &bytes[..]).unwrap();
+		let metadata = Metadata::try_from(runtime_metadata).unwrap();
+		let info = MetadataHashInfo {
+			spec_version: 1,
+			spec_name: "nice".into(),
+			base58_prefix: 1,
+			decimals: 1,
+			token_symbol: "lol".into(),
+		};
+
+		let hash = metadata.metadata_hash(info).unwrap();
+
+		assert_eq!(
+			hex::encode(hash),
+			"72b3e70cb722edeb45a9380720ecad79b09b4113ab2dee5f5d974f170fb77a7e"
+		);
+	}
+
+	#[test]
+	fn extrinsic_type_without_type_params_is_rejected() {
+		let extrinsic =
+			ExtrinsicMetadata { ty: meta_type::<()>(), version: 4, signed_extensions: vec![] };
+		let v14 = RuntimeMetadataV14::new(vec![], extrinsic, meta_type::<()>());
+		let metadata = Metadata::try_from(RuntimeMetadataPrefixed::from(v14)).unwrap();
+
+		assert!(matches!(
+			metadata.metadata_hash(info(1)),
+			Err(MetadataError::MerkleizationError(_))
+		));
+	}
+}
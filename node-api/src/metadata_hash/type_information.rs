@@ -0,0 +1,349 @@
+// This file was taken from merkleized-metadata (Bastian Köcher)
+// https://github.com/bkchr/merkleized-metadata
+// And was adapted by Supercomputing Systems AG.
+//
+// This file is licensed as Apache-2.0
+// see LICENSE for license details.
+
+//! Conversion of the types of the runtime metadata to the types of the merkleized metadata.
+
+use super::types;
+use crate::metadata::MetadataError;
+use alloc::{
+	collections::{BTreeMap, BTreeSet},
+	format,
+	string::String,
+	vec,
+	vec::Vec,
+};
+use frame_metadata::{v14::ExtrinsicMetadata, RuntimeMetadataLastVersion};
+use scale_info::{form::PortableForm, Field, PortableRegistry, Type, TypeDef, TypeDefPrimitive};
+
+/// The types of the runtime metadata that are needed to decode extrinsics,
+/// in the form of the merkleized metadata.
+pub(crate) struct TypeInformation {
+	pub extrinsic_metadata: types::ExtrinsicMetadata,
+	/// The leaves of the merkle tree, ordered by type id and enum variant index.
+	pub types: Vec<types::Type>,
+}
+
+impl TypeInformation {
+	pub fn new(metadata: &RuntimeMetadataLastVersion) -> Result<Self, MetadataError> {
+		let registry = &metadata.types;
+		let extrinsic = &metadata.extrinsic;
+
+		// Metadata V14 has no dedicated fields for these types,
+		// but they are type parameters of the extrinsic type.
+		let address_ty = extrinsic_type_param(registry, extrinsic, "Address")?;
+		let call_ty = extrinsic_type_param(registry, extrinsic, "Call")?;
+		let signature_ty = extrinsic_type_param(registry, extrinsic, "Signature")?;
+
+		let mut accessible_types = BTreeSet::new();
+		for ty in [address_ty, call_ty, signature_ty] {
+			collect_accessible_types(registry, ty, &mut accessible_types)?;
+		}
+		for signed_extension in &extrinsic.signed_extensions {
+			collect_accessible_types(registry, signed_extension.ty.id(), &mut accessible_types)?;
+			collect_accessible_types(
+				registry,
+				signed_extension.additional_signed.id(),
+				&mut accessible_types,
+			)?;
+		}
+
+		// Types that are not referenced by id, like primitives, don't get an id either.
+		let mut frame_id_to_id = BTreeMap::new();
+		for frame_id in accessible_types {
+			if is_referenced_by_id(resolve(registry, frame_id)?) {
+				let id = frame_id_to_id.len() as u32;
+				frame_id_to_id.insert(frame_id, id);
+			}
+		}
+
+		let context = TypeContext { registry, frame_id_to_id: &frame_id_to_id };
+
+		let extrinsic_metadata = types::ExtrinsicMetadata {
+			version: extrinsic.version,
+			address_ty: context.type_ref(address_ty)?,
+			call_ty: context.type_ref(call_ty)?,
+			signature_ty: context.type_ref(signature_ty)?,
+			signed_extensions: extrinsic
+				.signed_extensions
+				.iter()
+				.map(|signed_extension| {
+					Ok(types::SignedExtensionMetadata {
+						identifier: signed_extension.identifier.clone(),
+						included_in_extrinsic: context.type_ref(signed_extension.ty.id())?,
+						included_in_signed_data: context
+							.type_ref(signed_extension.additional_signed.id())?,
+					})
+				})
+				.collect::<Result<_, MetadataError>>()?,
+		};
+
+		let mut types = Vec::new();
+		for (frame_id, id) in &frame_id_to_id {
+			types.extend(context.types(resolve(registry, *frame_id)?, *id)?);
+		}
+
+		Ok(Self { extrinsic_metadata, types })
+	}
+}
+
+fn resolve(registry: &PortableRegistry, id: u32) -> Result<&Type<PortableForm>, MetadataError> {
+	registry.resolve(id).ok_or(MetadataError::TypeNotFound(id))
+}
+
+fn extrinsic_type_param(
+	registry: &PortableRegistry,
+	extrinsic: &ExtrinsicMetadata<PortableForm>,
+	name: &str,
+) -> Result<u32, MetadataError> {
+	resolve(registry, extrinsic.ty.id())?
+		.type_params()
+		.iter()
+		.find(|param| param.name() == name)
+		.and_then(|param| param.ty())
+		.map(|ty| ty.id())
+		.ok_or_else(|| {
+			MetadataError::MerkleizationError(format!(
+				"Extrinsic type has no `{}` type parameter",
+				name
+			))
+		})
+}
+
+fn collect_accessible_types(
+	registry: &PortableRegistry,
+	id: u32,
+	accessible_types: &mut BTreeSet<u32>,
+) -> Result<(), MetadataError> {
+	if !accessible_types.insert(id) {
+		return Ok(())
+	}
+
+	match resolve(registry, id)?.type_def() {
+		TypeDef::Composite(composite) => composite.fields().iter().try_for_each(|field| {
+			collect_accessible_types(registry, field.ty().id(), accessible_types)
+		}),
+		TypeDef::Variant(variant) => variant.variants().iter().try_for_each(|variant| {
+			variant.fields().iter().try_for_each(|field| {
+				collect_accessible_types(registry, field.ty().id(), accessible_types)
+			})
+		}),
+		TypeDef::Sequence(sequence) =>
+			collect_accessible_types(registry, sequence.type_param().id(), accessible_types),
+		TypeDef::Array(array) =>
+			collect_accessible_types(registry, array.type_param().id(), accessible_types),
+		TypeDef::Tuple(tuple) => tuple
+			.fields()
+			.iter()
+			.try_for_each(|ty| collect_accessible_types(registry, ty.id(), accessible_types)),
+		// Primitives and compacts are referenced as `TypeRef`, bit sequences only
+		// need the primitive of their store type.
+		TypeDef::Primitive(_) | TypeDef::Compact(_) | TypeDef::BitSequence(_) => Ok(()),
+	}
+}
+
+/// Whether the type is a leaf of the merkle tree and referenced by its id.
+/// Primitives, compacts and types without any data are inlined as `TypeRef`.
+fn is_referenced_by_id(ty: &Type<PortableForm>) -> bool {
+	match ty.type_def() {
+		TypeDef::Primitive(_) | TypeDef::Compact(_) => false,
+		TypeDef::Composite(composite) => !composite.fields().is_empty(),
+		TypeDef::Variant(variant) => !variant.variants().is_empty(),
+		TypeDef::Tuple(tuple) => !tuple.fields().is_empty(),
+		TypeDef::Sequence(_) | TypeDef::Array(_) | TypeDef::BitSequence(_) => true,
+	}
+}
+
+struct TypeContext<'a> {
+	registry: &'a PortableRegistry,
+	frame_id_to_id: &'a BTreeMap<u32, u32>,
+}
+
+impl<'a> TypeContext<'a> {
+	fn type_ref(&self, frame_id: u32) -> Result<types::TypeRef, MetadataError> {
+		let ty = resolve(self.registry, frame_id)?;
+		Ok(match ty.type_def() {
+			TypeDef::Primitive(primitive) => primitive_type_ref(primitive),
+			TypeDef::Compact(_) => {
+				let mut primitives = Vec::new();
+				self.collect_primitives(frame_id, &mut BTreeSet::new(), &mut primitives)?;
+				match primitives.as_slice() {
+					[] => types::TypeRef::Void,
+					[TypeDefPrimitive::U8] => types::TypeRef::CompactU8,
+					[TypeDefPrimitive::U16] => types::TypeRef::CompactU16,
+					[TypeDefPrimitive::U32] => types::TypeRef::CompactU32,
+					[TypeDefPrimitive::U64] => types::TypeRef::CompactU64,
+					[TypeDefPrimitive::U128] => types::TypeRef::CompactU128,
+					primitives =>
+						return Err(MetadataError::MerkleizationError(format!(
+							"Unsupported `Compact` of {:?}",
+							primitives
+						))),
+				}
+			},
+			_ if !is_referenced_by_id(ty) => types::TypeRef::Void,
+			_ => types::TypeRef::ById(
+				(*self
+					.frame_id_to_id
+					.get(&frame_id)
+					.ok_or(MetadataError::TypeNotFound(frame_id))?)
+				.into(),
+			),
+		})
+	}
+
+	/// Convert a type of the runtime metadata to the leaves of the merkle tree with the given `id`.
+	/// Enums are split into one leaf per variant.
+	fn types(&self, ty: &Type<PortableForm>, id: u32) -> Result<Vec<types::Type>, MetadataError> {
+		let path: Vec<String> = ty.path().segments().to_vec();
+		let type_def = match ty.type_def() {
+			TypeDef::Variant(variant) => {
+				let mut variants = variant.variants().iter().collect::<Vec<_>>();
+				variants.sort_by_key(|variant| variant.index());
+				return variants
+					.into_iter()
+					.map(|variant| {
+						Ok(types::Type {
+							path: path.clone(),
+							type_def: types::TypeDef::Enumeration(types::EnumerationVariant {
+								name: variant.name().clone(),
+								fields: self.fields(variant.fields())?,
+								index: (variant.index() as u32).into(),
+							}),
+							type_id: id.into(),
+						})
+					})
+					.collect()
+			},
+			TypeDef::Composite(composite) =>
+				types::TypeDef::Composite(self.fields(composite.fields())?),
+			TypeDef::Sequence(sequence) =>
+				types::TypeDef::Sequence(self.type_ref(sequence.type_param().id())?),
+			TypeDef::Array(array) => types::TypeDef::Array(types::TypeDefArray {
+				len: array.len(),
+				type_param: self.type_ref(array.type_param().id())?,
+			}),
+			TypeDef::Tuple(tuple) => types::TypeDef::Tuple(
+				tuple
+					.fields()
+					.iter()
+					.map(|ty| self.type_ref(ty.id()))
+					.collect::<Result<_, _>>()?,
+			),
+			TypeDef::BitSequence(bit_sequence) => {
+				let mut primitives = Vec::new();
+				self.collect_primitives(
+					bit_sequence.bit_store_type().id(),
+					&mut BTreeSet::new(),
+					&mut primitives,
+				)?;
+				let num_bytes = match primitives.as_slice() {
+					[TypeDefPrimitive::U8] => 1,
+					[TypeDefPrimitive::U16] => 2,
+					[TypeDefPrimitive::U32] => 4,
+					[TypeDefPrimitive::U64] => 8,
+					primitives =>
+						return Err(MetadataError::MerkleizationError(format!(
+							"Unsupported `BitSequence` store type {:?}",
+							primitives
+						))),
+				};
+				let bit_order_type = resolve(self.registry, bit_sequence.bit_order_type().id())?;
+				let least_significant_bit_first = match bit_order_type
+					.path()
+					.segments()
+					.iter()
+					.find(|segment| *segment == "Lsb0" || *segment == "Msb0")
+				{
+					Some(order) => order == "Lsb0",
+					None =>
+						return Err(MetadataError::MerkleizationError(
+							"`BitSequence` order type is neither `Lsb0` nor `Msb0`".into(),
+						)),
+				};
+				types::TypeDef::BitSequence(types::TypeDefBitSequence {
+					num_bytes,
+					least_significant_bit_first,
+				})
+			},
+			TypeDef::Primitive(_) | TypeDef::Compact(_) => return Ok(vec![]),
+		};
+		Ok(vec![types::Type { path, type_def, type_id: id.into() }])
+	}
+
+	fn fields(&self, fields: &[Field<PortableForm>]) -> Result<Vec<types::Field>, MetadataError> {
+		fields
+			.iter()
+			.map(|field| {
+				Ok(types::Field {
+					name: field.name().cloned(),
+					ty: self.type_ref(field.ty().id())?,
+					type_name: field.type_name().cloned(),
+				})
+			})
+			.collect()
+	}
+
+	/// Collect all primitives the type with `frame_id` consists of,
+	/// e.g. the `u128` of a `Compact<Balance>`.
+	fn collect_primitives(
+		&self,
+		frame_id: u32,
+		visited: &mut BTreeSet<u32>,
+		primitives: &mut Vec<TypeDefPrimitive>,
+	) -> Result<(), MetadataError> {
+		if !visited.insert(frame_id) {
+			return Ok(())
+		}
+
+		match resolve(self.registry, frame_id)?.type_def() {
+			TypeDef::Primitive(primitive) => primitives.push(primitive.clone()),
+			TypeDef::Composite(composite) =>
+				for field in composite.fields() {
+					self.collect_primitives(field.ty().id(), visited, primitives)?;
+				},
+			TypeDef::Variant(variant) =>
+				for field in variant.variants().iter().flat_map(|variant| variant.fields()) {
+					self.collect_primitives(field.ty().id(), visited, primitives)?;
+				},
+			TypeDef::Sequence(sequence) =>
+				self.collect_primitives(sequence.type_param().id(), visited, primitives)?,
+			TypeDef::Array(array) =>
+				self.collect_primitives(array.type_param().id(), visited, primitives)?,
+			TypeDef::Tuple(tuple) =>
+				for ty in tuple.fields() {
+					self.collect_primitives(ty.id(), visited, primitives)?;
+				},
+			TypeDef::Compact(compact) =>
+				self.collect_primitives(compact.type_param().id(), visited, primitives)?,
+			TypeDef::BitSequence(bit_sequence) => {
+				self.collect_primitives(bit_sequence.bit_order_type().id(), visited, primitives)?;
+				self.collect_primitives(bit_sequence.bit_store_type().id(), visited, primitives)?;
+			},
+		}
+		Ok(())
+	}
+}
+
+fn primitive_type_ref(primitive: &TypeDefPrimitive) -> types::TypeRef {
+	match primitive {
+		TypeDefPrimitive::Bool => types::TypeRef::Bool,
+		TypeDefPrimitive::Char => types::TypeRef::Char,
+		TypeDefPrimitive::Str => types::TypeRef::Str,
+		TypeDefPrimitive::U8 => types::TypeRef::U8,
+		TypeDefPrimitive::U16 => types::TypeRef::U16,
+		TypeDefPrimitive::U32 => types::TypeRef::U32,
+		TypeDefPrimitive::U64 => types::TypeRef::U64,
+		TypeDefPrimitive::U128 => types::TypeRef::U128,
+		TypeDefPrimitive::U256 => types::TypeRef::U256,
+		TypeDefPrimitive::I8 => types::TypeRef::I8,
+		TypeDefPrimitive::I16 => types::TypeRef::I16,
+		TypeDefPrimitive::I32 => types::TypeRef::I32,
+		TypeDefPrimitive::I64 => types::TypeRef::I64,
+		TypeDefPrimitive::I128 => types::TypeRef::I128,
+		TypeDefPrimitive::I256 => types::TypeRef::I256,
+	}
+}
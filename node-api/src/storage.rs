@@ -13,15 +13,19 @@
 
 //! For querying runtime storage.
 
-use crate::metadata::MetadataError;
+use crate::{
+	error::{Error, StorageAddressError},
+	metadata::MetadataError,
+	Value,
+};
 use codec::Encode;
 use frame_metadata::{StorageEntryMetadata, StorageEntryType, StorageHasher};
-use scale_info::form::PortableForm;
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef};
 use sp_core::storage::StorageKey;
 use sp_std::marker::PhantomData;
 
 #[cfg(not(feature = "std"))]
-use alloc::{borrow::ToOwned, vec::Vec};
+use alloc::{borrow::ToOwned, vec, vec::Vec};
 
 #[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
 pub struct StorageValue {
@@ -83,6 +87,13 @@ pub trait GetStorageTypes {
 	fn get_map<K: Encode>(&self, pallet_prefix: &str) -> Result<StorageMap<K>, MetadataError>;
 	fn get_map_prefix(&self, pallet_prefix: &str) -> Result<StorageKey, MetadataError>;
 	fn get_value(&self, pallet_prefix: &str) -> Result<StorageValue, MetadataError>;
+	fn get_key_from_values(
+		&self,
+		pallet_prefix: &str,
+		keys: Vec<Value>,
+		types: &PortableRegistry,
+	) -> Result<StorageKey, Error>;
+	fn get_value_ty_id(&self) -> u32;
 }
 
 impl GetStorageTypes for StorageEntryMetadata<PortableForm> {
@@ -157,23 +168,216 @@ impl GetStorageTypes for StorageEntryMetadata<PortableForm> {
 			_ => Err(MetadataError::StorageTypeError),
 		}
 	}
+
+	fn get_key_from_values(
+		&self,
+		pallet_prefix: &str,
+		keys: Vec<Value>,
+		types: &PortableRegistry,
+	) -> Result<StorageKey, Error> {
+		let mut bytes = sp_core::twox_128(pallet_prefix.as_bytes()).to_vec();
+		bytes.extend(&sp_core::twox_128(self.name.as_bytes())[..]);
+
+		match &self.ty {
+			StorageEntryType::Plain(_) =>
+				if !keys.is_empty() {
+					return Err(StorageAddressError::WrongNumberOfKeys {
+						actual: 0,
+						expected: keys.len(),
+					}
+					.into())
+				},
+			StorageEntryType::Map { hashers, key, .. } => {
+				// A map with a single hasher hashes its whole key type at once. Maps with more
+				// hashers (double maps, n maps) have a tuple key with one field per hasher.
+				let key_ty_ids = if hashers.len() == 1 {
+					vec![key.id()]
+				} else {
+					let key_ty = types
+						.resolve(key.id())
+						.ok_or(StorageAddressError::TypeNotFound(key.id()))?;
+					match key_ty.type_def() {
+						TypeDef::Tuple(tuple) => tuple.fields().iter().map(|f| f.id()).collect(),
+						_ => return Err(StorageAddressError::MapTypeMustBeTuple.into()),
+					}
+				};
+				if key_ty_ids.len() != hashers.len() {
+					return Err(StorageAddressError::WrongNumberOfHashers {
+						hashers: hashers.len(),
+						fields: key_ty_ids.len(),
+					}
+					.into())
+				}
+				if keys.len() > hashers.len() {
+					return Err(StorageAddressError::WrongNumberOfKeys {
+						actual: hashers.len(),
+						expected: keys.len(),
+					}
+					.into())
+				}
+				for ((value, ty_id), hasher) in keys.into_iter().zip(key_ty_ids).zip(hashers) {
+					let mut encoded_key = Vec::new();
+					crate::encode_value_as_type(value, ty_id, types, &mut encoded_key)?;
+					bytes.extend(hash_bytes(&encoded_key, hasher));
+				}
+			},
+		}
+		Ok(StorageKey(bytes))
+	}
+
+	fn get_value_ty_id(&self) -> u32 {
+		match &self.ty {
+			StorageEntryType::Plain(ty) => ty.id(),
+			StorageEntryType::Map { value, .. } => value.id(),
+		}
+	}
 }
 
 /// generates the key's hash depending on the StorageHasher selected
-fn key_hash<K: Encode>(key: &K, hasher: &StorageHasher) -> Vec<u8> {
-	let encoded_key = key.encode();
+pub(crate) fn key_hash<K: Encode>(key: &K, hasher: &StorageHasher) -> Vec<u8> {
+	hash_bytes(&key.encode(), hasher)
+}
+
+/// hashes the already encoded key with the given StorageHasher
+pub(crate) fn hash_bytes(encoded_key: &[u8], hasher: &StorageHasher) -> Vec<u8> {
 	match hasher {
 		StorageHasher::Identity => encoded_key.to_vec(),
-		StorageHasher::Blake2_128 => sp_core::blake2_128(&encoded_key).to_vec(),
+		StorageHasher::Blake2_128 => sp_core::blake2_128(encoded_key).to_vec(),
 		StorageHasher::Blake2_128Concat => {
 			// copied from substrate Blake2_128Concat::hash since StorageHasher is not public
-			let x: &[u8] = encoded_key.as_slice();
+			let x: &[u8] = encoded_key;
 			sp_core::blake2_128(x).iter().chain(x.iter()).cloned().collect::<Vec<_>>()
 		},
-		StorageHasher::Blake2_256 => sp_core::blake2_256(&encoded_key).to_vec(),
-		StorageHasher::Twox128 => sp_core::twox_128(&encoded_key).to_vec(),
-		StorageHasher::Twox256 => sp_core::twox_256(&encoded_key).to_vec(),
+		StorageHasher::Blake2_256 => sp_core::blake2_256(encoded_key).to_vec(),
+		StorageHasher::Twox128 => sp_core::twox_128(encoded_key).to_vec(),
+		StorageHasher::Twox256 => sp_core::twox_256(encoded_key).to_vec(),
 		StorageHasher::Twox64Concat =>
-			sp_core::twox_64(&encoded_key).iter().chain(&encoded_key).cloned().collect(),
+			sp_core::twox_64(encoded_key).iter().chain(encoded_key).cloned().collect(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Metadata;
+	use frame_metadata::{
+		v14::{
+			ExtrinsicMetadata, PalletMetadata, PalletStorageMetadata, RuntimeMetadataV14,
+			StorageEntryModifier,
+		},
+		RuntimeMetadataPrefixed,
+	};
+	use scale_info::meta_type;
+
+	/// Build fake metadata consisting of a single pallet `Test` with a map and a double map.
+	fn metadata() -> Metadata {
+		let entries = vec![
+			StorageEntryMetadata {
+				name: "Map",
+				modifier: StorageEntryModifier::Optional,
+				ty: StorageEntryType::Map {
+					hashers: vec![StorageHasher::Blake2_128Concat],
+					key: meta_type::<[u8; 32]>(),
+					value: meta_type::<u64>(),
+				},
+				default: vec![],
+				docs: vec![],
+			},
+			StorageEntryMetadata {
+				name: "DoubleMap",
+				modifier: StorageEntryModifier::Optional,
+				ty: StorageEntryType::Map {
+					hashers: vec![StorageHasher::Twox64Concat, StorageHasher::Blake2_128Concat],
+					key: meta_type::<(u32, [u8; 32])>(),
+					value: meta_type::<u64>(),
+				},
+				default: vec![],
+				docs: vec![],
+			},
+		];
+		let pallets = vec![PalletMetadata {
+			name: "Test",
+			storage: Some(PalletStorageMetadata { prefix: "Test", entries }),
+			calls: None,
+			event: None,
+			constants: vec![],
+			error: None,
+			index: 0,
+		}];
+		let extrinsic =
+			ExtrinsicMetadata { ty: meta_type::<()>(), version: 0, signed_extensions: vec![] };
+		let v14 = RuntimeMetadataV14::new(pallets, extrinsic, meta_type::<()>());
+		let runtime_metadata: RuntimeMetadataPrefixed = v14.into();
+		Metadata::try_from(runtime_metadata).unwrap()
+	}
+
+	#[test]
+	fn key_from_values_matches_static_map_key() {
+		let metadata = metadata();
+		let account = [7u8; 32];
+
+		let static_key = metadata.storage_map_key("Test", "Map", account).unwrap();
+		let dynamic_key = metadata
+			.storage_key_from_values("Test", "Map", vec![Value::from_bytes(account)])
+			.unwrap();
+
+		assert_eq!(static_key, dynamic_key);
+	}
+
+	#[test]
+	fn key_from_values_matches_static_double_map_key() {
+		let metadata = metadata();
+		let account = [7u8; 32];
+
+		let static_key =
+			metadata.storage_double_map_key("Test", "DoubleMap", 1u32, account).unwrap();
+		let dynamic_key = metadata
+			.storage_key_from_values(
+				"Test",
+				"DoubleMap",
+				vec![Value::uint(1u32), Value::from_bytes(account)],
+			)
+			.unwrap();
+		let prefix = metadata
+			.storage_key_from_values("Test", "DoubleMap", vec![Value::uint(1u32)])
+			.unwrap();
+
+		assert_eq!(static_key, dynamic_key);
+		assert_eq!(prefix.0, dynamic_key.0[..prefix.0.len()]);
+	}
+
+	#[test]
+	fn key_from_values_fails_with_too_many_keys() {
+		let metadata = metadata();
+
+		let result = metadata.storage_key_from_values(
+			"Test",
+			"Map",
+			vec![Value::from_bytes([7u8; 32]), Value::uint(1u32)],
+		);
+
+		assert!(matches!(
+			result,
+			Err(Error::StorageAddress(StorageAddressError::WrongNumberOfKeys {
+				actual: 1,
+				expected: 2
+			}))
+		));
+	}
+
+	#[test]
+	fn key_from_values_fails_for_unknown_entry() {
+		let metadata = metadata();
+
+		let result = metadata.storage_key_from_values("Test", "Unknown", vec![]);
+
+		assert!(matches!(
+			result,
+			Err(Error::Metadata(MetadataError::StorageEntryNotFound(name))) if name == "Unknown"
+		));
+		assert_eq!(
+			metadata.pallet("Test").unwrap().storage("Unknown").unwrap_err(),
+			MetadataError::StorageNotFound("Unknown")
+		);
 	}
 }
@@ -0,0 +1,368 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+		http://www.apache.org/licenses/LICENSE-2.0
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+*/
+
+//! Typed handles to storage entries, validated once against the runtime metadata.
+
+use crate::{
+	metadata::{Metadata, MetadataError},
+	storage::key_hash,
+};
+use alloc::collections::BTreeSet;
+use codec::Encode;
+use core::fmt;
+use frame_metadata::{StorageEntryType, StorageHasher};
+use scale_info::{form::PortableForm, Field, PortableRegistry, Registry, TypeDef, TypeInfo};
+use sp_core::storage::StorageKey;
+use sp_std::marker::PhantomData;
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+/// Handle to a storage entry with keys of type `K` and values of type `V`.
+///
+/// `K` is `()` for plain storage values, the key type for maps and a tuple `(K1, K2)` of both
+/// key types for double maps. On creation it is checked that the entry exists in the metadata,
+/// that it is of the requested kind and that `V` matches the value type of the metadata. The
+/// handle can then be used to build storage keys without any further metadata lookups.
+pub struct StorageAddress<K, V> {
+	pallet: String,
+	entry: String,
+	hashers: Vec<StorageHasher>,
+	encode_keys: fn(&K, &[StorageHasher]) -> Vec<u8>,
+	_marker: PhantomData<fn() -> V>,
+}
+
+impl<V: TypeInfo + 'static> StorageAddress<(), V> {
+	/// Create a handle to a plain storage value.
+	pub fn value(metadata: &Metadata, pallet: &str, entry: &str) -> Result<Self, MetadataError> {
+		let hashers = validate_entry::<V>(metadata, pallet, entry, 0)?;
+		Ok(Self::new(pallet, entry, hashers, encode_no_keys))
+	}
+}
+
+impl<K: Encode, V: TypeInfo + 'static> StorageAddress<K, V> {
+	/// Create a handle to a storage map.
+	pub fn map(metadata: &Metadata, pallet: &str, entry: &str) -> Result<Self, MetadataError> {
+		let hashers = validate_entry::<V>(metadata, pallet, entry, 1)?;
+		Ok(Self::new(pallet, entry, hashers, encode_map_key::<K>))
+	}
+}
+
+impl<K1: Encode, K2: Encode, V: TypeInfo + 'static> StorageAddress<(K1, K2), V> {
+	/// Create a handle to a storage double map.
+	pub fn double_map(
+		metadata: &Metadata,
+		pallet: &str,
+		entry: &str,
+	) -> Result<Self, MetadataError> {
+		let hashers = validate_entry::<V>(metadata, pallet, entry, 2)?;
+		Ok(Self::new(pallet, entry, hashers, encode_double_map_keys::<K1, K2>))
+	}
+}
+
+impl<K, V> StorageAddress<K, V> {
+	fn new(
+		pallet: &str,
+		entry: &str,
+		hashers: Vec<StorageHasher>,
+		encode_keys: fn(&K, &[StorageHasher]) -> Vec<u8>,
+	) -> Self {
+		Self {
+			pallet: pallet.into(),
+			entry: entry.into(),
+			hashers,
+			encode_keys,
+			_marker: PhantomData,
+		}
+	}
+
+	pub fn pallet(&self) -> &str {
+		&self.pallet
+	}
+
+	pub fn entry(&self) -> &str {
+		&self.entry
+	}
+
+	/// The key prefix shared by all values of this storage entry.
+	pub fn prefix(&self) -> StorageKey {
+		let mut bytes = sp_core::twox_128(self.pallet.as_bytes()).to_vec();
+		bytes.extend(&sp_core::twox_128(self.entry.as_bytes())[..]);
+		StorageKey(bytes)
+	}
+
+	/// The storage key of the value stored under `keys`.
+	pub fn key(&self, keys: &K) -> StorageKey {
+		let mut bytes = self.prefix().0;
+		bytes.extend((self.encode_keys)(keys, &self.hashers));
+		StorageKey(bytes)
+	}
+}
+
+impl<K, V> Clone for StorageAddress<K, V> {
+	fn clone(&self) -> Self {
+		Self {
+			pallet: self.pallet.clone(),
+			entry: self.entry.clone(),
+			hashers: self.hashers.clone(),
+			encode_keys: self.encode_keys,
+			_marker: PhantomData,
+		}
+	}
+}
+
+// hashers do not implement debug in no_std
+impl<K, V> fmt::Debug for StorageAddress<K, V> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("StorageAddress")
+			.field("pallet", &self.pallet)
+			.field("entry", &self.entry)
+			.finish()
+	}
+}
+
+fn encode_no_keys(_keys: &(), _hashers: &[StorageHasher]) -> Vec<u8> {
+	Vec::new()
+}
+
+fn encode_map_key<K: Encode>(key: &K, hashers: &[StorageHasher]) -> Vec<u8> {
+	key_hash(key, &hashers[0])
+}
+
+fn encode_double_map_keys<K1: Encode, K2: Encode>(
+	keys: &(K1, K2),
+	hashers: &[StorageHasher],
+) -> Vec<u8> {
+	let mut bytes = key_hash(&keys.0, &hashers[0]);
+	bytes.extend(key_hash(&keys.1, &hashers[1]));
+	bytes
+}
+
+/// Checks that the entry exists with the expected number of hashers (0 for a plain value) and
+/// that `V` matches its value type. Returns the hashers of the entry.
+fn validate_entry<V: TypeInfo + 'static>(
+	metadata: &Metadata,
+	pallet: &str,
+	entry: &str,
+	number_of_hashers: usize,
+) -> Result<Vec<StorageHasher>, MetadataError> {
+	let entry_metadata = metadata.pallet(pallet)?.storage_entry(entry)?;
+	let (hashers, value_ty_id) = match &entry_metadata.ty {
+		StorageEntryType::Plain(value) if number_of_hashers == 0 => (Vec::new(), value.id()),
+		StorageEntryType::Map { hashers, value, .. } if hashers.len() == number_of_hashers =>
+			(hashers.clone(), value.id()),
+		_ => return Err(MetadataError::StorageTypeError),
+	};
+
+	let mut registry = Registry::new();
+	let static_ty_id = registry.register_type(&scale_info::meta_type::<V>()).id();
+	let static_types: PortableRegistry = registry.into();
+	if !is_type_compatible(
+		&static_types,
+		static_ty_id,
+		metadata.types(),
+		value_ty_id,
+		&mut BTreeSet::new(),
+	) {
+		return Err(MetadataError::IncompatibleStorageMetadata(pallet.into(), entry.into()))
+	}
+	Ok(hashers)
+}
+
+/// Compares the shape of two types, which may be registered in different registries. Type names
+/// and paths are ignored, so a locally defined type matches the runtime type it mirrors.
+fn is_type_compatible(
+	types_a: &PortableRegistry,
+	id_a: u32,
+	types_b: &PortableRegistry,
+	id_b: u32,
+	visited: &mut BTreeSet<(u32, u32)>,
+) -> bool {
+	// Recursive types are compatible if they are compatible up to the recursion.
+	if !visited.insert((id_a, id_b)) {
+		return true
+	}
+	let (ty_a, ty_b) = match (types_a.resolve(id_a), types_b.resolve(id_b)) {
+		(Some(ty_a), Some(ty_b)) => (ty_a, ty_b),
+		_ => return false,
+	};
+	let mut compatible =
+		|id_a: u32, id_b: u32| is_type_compatible(types_a, id_a, types_b, id_b, visited);
+
+	match (ty_a.type_def(), ty_b.type_def()) {
+		(TypeDef::Composite(a), TypeDef::Composite(b)) =>
+			are_fields_compatible(a.fields(), b.fields(), &mut compatible),
+		(TypeDef::Variant(a), TypeDef::Variant(b)) =>
+			a.variants().len() == b.variants().len()
+				&& a.variants().iter().zip(b.variants()).all(|(a, b)| {
+					a.name() == b.name()
+						&& a.index() == b.index()
+						&& are_fields_compatible(a.fields(), b.fields(), &mut compatible)
+				}),
+		(TypeDef::Sequence(a), TypeDef::Sequence(b)) =>
+			compatible(a.type_param().id(), b.type_param().id()),
+		(TypeDef::Array(a), TypeDef::Array(b)) =>
+			a.len() == b.len() && compatible(a.type_param().id(), b.type_param().id()),
+		(TypeDef::Tuple(a), TypeDef::Tuple(b)) =>
+			a.fields().len() == b.fields().len()
+				&& a.fields().iter().zip(b.fields()).all(|(a, b)| compatible(a.id(), b.id())),
+		(TypeDef::Primitive(a), TypeDef::Primitive(b)) => a == b,
+		(TypeDef::Compact(a), TypeDef::Compact(b)) =>
+			compatible(a.type_param().id(), b.type_param().id()),
+		(TypeDef::BitSequence(a), TypeDef::BitSequence(b)) =>
+			compatible(a.bit_store_type().id(), b.bit_store_type().id())
+				&& compatible(a.bit_order_type().id(), b.bit_order_type().id()),
+		_ => false,
+	}
+}
+
+fn are_fields_compatible(
+	fields_a: &[Field<PortableForm>],
+	fields_b: &[Field<PortableForm>],
+	compatible: &mut impl FnMut(u32, u32) -> bool,
+) -> bool {
+	fields_a.len() == fields_b.len()
+		&& fields_a
+			.iter()
+			.zip(fields_b)
+			.all(|(a, b)| a.name() == b.name() && compatible(a.ty().id(), b.ty().id()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_metadata::{
+		v14::{
+			ExtrinsicMetadata, PalletMetadata, PalletStorageMetadata, RuntimeMetadataV14,
+			StorageEntryMetadata, StorageEntryModifier,
+		},
+		RuntimeMetadataPrefixed,
+	};
+	use scale_info::meta_type;
+
+	#[allow(unused)]
+	#[derive(TypeInfo)]
+	struct AccountData {
+		free: u128,
+		reserved: u128,
+	}
+
+	#[allow(unused)]
+	#[derive(TypeInfo)]
+	struct OtherAccountData {
+		free: u128,
+		frozen: u128,
+	}
+
+	/// Build fake metadata consisting of a single pallet `Test` with a value, a map and a
+	/// double map.
+	fn metadata() -> Metadata {
+		let entry = |name, ty| StorageEntryMetadata {
+			name,
+			modifier: StorageEntryModifier::Optional,
+			ty,
+			default: vec![],
+			docs: vec![],
+		};
+		let entries = vec![
+			entry("Value", StorageEntryType::Plain(meta_type::<u32>())),
+			entry(
+				"Map",
+				StorageEntryType::Map {
+					hashers: vec![StorageHasher::Blake2_128Concat],
+					key: meta_type::<[u8; 32]>(),
+					value: meta_type::<AccountData>(),
+				},
+			),
+			entry(
+				"DoubleMap",
+				StorageEntryType::Map {
+					hashers: vec![StorageHasher::Twox64Concat, StorageHasher::Blake2_128Concat],
+					key: meta_type::<(u32, [u8; 32])>(),
+					value: meta_type::<u64>(),
+				},
+			),
+		];
+		let pallets = vec![PalletMetadata {
+			name: "Test",
+			storage: Some(PalletStorageMetadata { prefix: "Test", entries }),
+			calls: None,
+			event: None,
+			constants: vec![],
+			error: None,
+			index: 0,
+		}];
+		let extrinsic =
+			ExtrinsicMetadata { ty: meta_type::<()>(), version: 0, signed_extensions: vec![] };
+		let v14 = RuntimeMetadataV14::new(pallets, extrinsic, meta_type::<()>());
+		let runtime_metadata: RuntimeMetadataPrefixed = v14.into();
+		Metadata::try_from(runtime_metadata).unwrap()
+	}
+
+	#[test]
+	fn addresses_build_the_same_keys_as_metadata() {
+		let metadata = metadata();
+		let account = [7u8; 32];
+
+		let value = StorageAddress::<(), u32>::value(&metadata, "Test", "Value").unwrap();
+		let map = StorageAddress::<[u8; 32], AccountData>::map(&metadata, "Test", "Map").unwrap();
+		let double_map =
+			StorageAddress::<(u32, [u8; 32]), u64>::double_map(&metadata, "Test", "DoubleMap")
+				.unwrap();
+
+		assert_eq!(value.key(&()), metadata.storage_value_key("Test", "Value").unwrap());
+		assert_eq!(map.key(&account), metadata.storage_map_key("Test", "Map", account).unwrap());
+		assert_eq!(
+			double_map.key(&(1, account)),
+			metadata.storage_double_map_key("Test", "DoubleMap", 1u32, account).unwrap()
+		);
+		assert_eq!(map.prefix(), metadata.storage_map_key_prefix("Test", "Map").unwrap());
+	}
+
+	#[test]
+	fn address_creation_fails_for_wrong_kind() {
+		let metadata = metadata();
+
+		assert_eq!(
+			StorageAddress::<u32, u32>::map(&metadata, "Test", "Value").unwrap_err(),
+			MetadataError::StorageTypeError
+		);
+		assert_eq!(
+			StorageAddress::<(), u64>::value(&metadata, "Test", "DoubleMap").unwrap_err(),
+			MetadataError::StorageTypeError
+		);
+		assert_eq!(
+			StorageAddress::<u32, u64>::map(&metadata, "Test", "DoubleMap").unwrap_err(),
+			MetadataError::StorageTypeError
+		);
+		assert_eq!(
+			StorageAddress::<(), u32>::value(&metadata, "Test", "Unknown").unwrap_err(),
+			MetadataError::StorageEntryNotFound("Unknown".into())
+		);
+	}
+
+	#[test]
+	fn address_creation_fails_for_wrong_value_type() {
+		let metadata = metadata();
+
+		assert_eq!(
+			StorageAddress::<(), u64>::value(&metadata, "Test", "Value").unwrap_err(),
+			MetadataError::IncompatibleStorageMetadata("Test".into(), "Value".into())
+		);
+		assert_eq!(
+			StorageAddress::<[u8; 32], OtherAccountData>::map(&metadata, "Test", "Map")
+				.unwrap_err(),
+			MetadataError::IncompatibleStorageMetadata("Test".into(), "Map".into())
+		);
+	}
+}
@@ -0,0 +1,215 @@
+/*
+   Copyright 2019 Supercomputing Systems AG
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+	   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+*/
+
+//! Accounts and signatures of Ethereum compatible runtimes: Account ids are 20 bytes long and
+//! extrinsics are signed with secp256k1 ECDSA over the keccak-256 hash of the payload.
+
+use crate::SignExtrinsic;
+use alloc::string::String;
+use codec::{Decode, Encode, MaxEncodedLen};
+use core::{fmt, str::FromStr};
+use scale_info::TypeInfo;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use sp_core::{ecdsa, hashing::keccak_256, Pair};
+
+/// The account id of Ethereum compatible runtimes, i.e. an Ethereum address.
+#[derive(
+	Encode,
+	Decode,
+	TypeInfo,
+	MaxEncodedLen,
+	Clone,
+	Copy,
+	Default,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Hash,
+)]
+pub struct AccountId20(pub [u8; 20]);
+
+impl AccountId20 {
+	/// The address of an uncompressed secp256k1 public key: The last 20 bytes of the keccak-256
+	/// hash of the key without its prefix byte.
+	fn from_uncompressed_public(public: &[u8; 65]) -> Self {
+		let hash = keccak_256(&public[1..]);
+		let mut account_id = [0u8; 20];
+		account_id.copy_from_slice(&hash[12..]);
+		Self(account_id)
+	}
+}
+
+impl From<[u8; 20]> for AccountId20 {
+	fn from(bytes: [u8; 20]) -> Self {
+		Self(bytes)
+	}
+}
+
+impl From<ecdsa::Public> for AccountId20 {
+	fn from(public: ecdsa::Public) -> Self {
+		let public = libsecp256k1::PublicKey::parse_compressed(&public.0)
+			.expect("An ecdsa public key is a valid compressed secp256k1 public key; qed");
+		Self::from_uncompressed_public(&public.serialize())
+	}
+}
+
+impl AsRef<[u8]> for AccountId20 {
+	fn as_ref(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl fmt::Display for AccountId20 {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "0x{}", hex::encode(self.0))
+	}
+}
+
+impl fmt::Debug for AccountId20 {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Display::fmt(self, f)
+	}
+}
+
+impl FromStr for AccountId20 {
+	type Err = hex::FromHexError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut account_id = [0u8; 20];
+		hex::decode_to_slice(s.trim_start_matches("0x"), &mut account_id)?;
+		Ok(Self(account_id))
+	}
+}
+
+impl Serialize for AccountId20 {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for AccountId20 {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let address = String::deserialize(deserializer)?;
+		address.parse().map_err(D::Error::custom)
+	}
+}
+
+/// Recoverable ECDSA signature of the keccak-256 hash of a payload.
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+pub struct EthereumSignature(pub ecdsa::Signature);
+
+impl EthereumSignature {
+	/// Recover the account that signed `payload`.
+	pub fn recover(&self, payload: &[u8]) -> Option<AccountId20> {
+		let signature: &[u8] = self.0.as_ref();
+		let message = libsecp256k1::Message::parse(&keccak_256(payload));
+		let recovery_id = libsecp256k1::RecoveryId::parse(signature[64]).ok()?;
+		let signature = libsecp256k1::Signature::parse_standard_slice(&signature[..64]).ok()?;
+		let public = libsecp256k1::recover(&message, &signature, &recovery_id).ok()?;
+		Some(AccountId20::from_uncompressed_public(&public.serialize()))
+	}
+}
+
+impl From<ecdsa::Signature> for EthereumSignature {
+	fn from(signature: ecdsa::Signature) -> Self {
+		Self(signature)
+	}
+}
+
+/// Signs extrinsics of Ethereum compatible runtimes with an ECDSA key pair.
+#[derive(Clone)]
+pub struct EthereumSigner(ecdsa::Pair);
+
+impl EthereumSigner {
+	pub fn new(pair: ecdsa::Pair) -> Self {
+		Self(pair)
+	}
+
+	pub fn pair(&self) -> &ecdsa::Pair {
+		&self.0
+	}
+}
+
+impl From<ecdsa::Pair> for EthereumSigner {
+	fn from(pair: ecdsa::Pair) -> Self {
+		Self::new(pair)
+	}
+}
+
+impl SignExtrinsic<AccountId20> for EthereumSigner {
+	type Signature = EthereumSignature;
+	type ExtrinsicAddress = AccountId20;
+
+	fn sign(&self, payload: &[u8]) -> EthereumSignature {
+		self.0.sign_prehashed(&keccak_256(payload)).into()
+	}
+
+	fn account_id(&self) -> AccountId20 {
+		self.0.public().into()
+	}
+
+	fn extrinsic_address(&self) -> AccountId20 {
+		self.account_id()
+	}
+
+	fn dummy_signature(&self) -> EthereumSignature {
+		ecdsa::Signature::from_raw([0; 65]).into()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn signer() -> EthereumSigner {
+		let mut secret_key = [0u8; 32];
+		secret_key[31] = 1;
+		ecdsa::Pair::from_seed(&secret_key).into()
+	}
+
+	#[test]
+	fn account_id_is_ethereum_address_of_key() {
+		let expected: AccountId20 = "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf".parse().unwrap();
+
+		assert_eq!(signer().account_id(), expected);
+		assert_eq!(signer().extrinsic_address().encode(), expected.0.to_vec());
+	}
+
+	#[test]
+	fn signature_recovers_to_signer() {
+		let signer = signer();
+
+		let signature = signer.sign(b"payload");
+
+		assert_eq!(signature.encode().len(), 65);
+		assert_eq!(signer.dummy_signature().encode().len(), 65);
+		assert_eq!(signature.recover(b"payload"), Some(signer.account_id()));
+		assert_ne!(signature.recover(b"other payload"), Some(signer.account_id()));
+	}
+
+	#[test]
+	fn account_id_is_serialized_as_hex() {
+		let account_id = AccountId20([0xab; 20]);
+
+		let json = serde_json::to_string(&account_id).unwrap();
+
+		assert_eq!(json, format!("\"0x{}\"", "ab".repeat(20)));
+		assert_eq!(serde_json::from_str::<AccountId20>(&json).unwrap(), account_id);
+		assert!("0xab".parse::<AccountId20>().is_err());
+	}
+}
@@ -15,6 +15,7 @@
 
 */
 
+use crate::UnknownSignedExtension;
 use codec::{Decode, Encode};
 use core::marker::PhantomData;
 use sp_runtime::{
@@ -44,6 +45,18 @@ impl<Tip, Index> SubstrateDefaultSignedExtra<Tip, Index> {
 	}
 }
 
+/// SignedExtra that contains the nonce of the signer, e.g. to find the extrinsics of an account
+/// in the transaction pool.
+pub trait SignedExtraNonce<Index> {
+	fn nonce(&self) -> Index;
+}
+
+impl<Tip, Index: Copy> SignedExtraNonce<Index> for SubstrateDefaultSignedExtra<Tip, Index> {
+	fn nonce(&self) -> Index {
+		self.nonce
+	}
+}
+
 /// Default AdditionalSigned fields of the respective SignedExtra fields.
 /// The Order is (CheckNonZeroSender, CheckSpecVersion, CheckTxVersion, CheckGenesis, Check::Era, CheckNonce, CheckWeight, transactionPayment::ChargeTransactionPayment).
 pub type SubstrateDefaultAdditionalSigned<Hash> = ((), u32, u32, Hash, Hash, (), (), ());
@@ -58,7 +71,7 @@ pub trait ExtrinsicParams<Index, Hash> {
 	type OtherParams: Default + Clone;
 
 	/// SignedExtra format of the node.
-	type SignedExtra: Copy + Encode;
+	type SignedExtra: Clone + Encode;
 
 	/// Additional Signed format of the node
 	type AdditionalSigned: Encode;
@@ -80,6 +93,41 @@ pub trait ExtrinsicParams<Index, Hash> {
 	/// taken into account when signing it, meaning the client and node must agree
 	/// on their values.
 	fn additional_signed(&self) -> Self::AdditionalSigned;
+
+	/// Create the [`Self::OtherParams`] for a runtime with the signed extensions of the given
+	/// `identifiers`, in the order of its metadata. Returns `None` if the params encode a fixed
+	/// set of signed extensions, for which the default [`Self::OtherParams`] are used.
+	fn other_params_for_signed_extensions<I: AsRef<str>>(
+		_identifiers: &[I],
+	) -> Result<Option<Self::OtherParams>, UnknownSignedExtension> {
+		Ok(None)
+	}
+
+	/// Complete the `other_params` set by a user with the signed extensions of the runtime,
+	/// taken from the `runtime_params` created by `other_params_for_signed_extensions`.
+	/// Params encoding a fixed set of signed extensions return `other_params` as they are.
+	fn other_params_with_signed_extensions(
+		other_params: Self::OtherParams,
+		_runtime_params: &Self::OtherParams,
+	) -> Self::OtherParams {
+		other_params
+	}
+}
+
+/// Builders of [`ExtrinsicParams`] that allow to set the mortality of an extrinsic.
+/// Used by the api to apply its mortality policy to every extrinsic it composes.
+pub trait WithEra<Hash> {
+	/// Set the [`Era`] and the hash of the block it starts at.
+	fn with_era(self, era: Era, checkpoint: Hash) -> Self;
+}
+
+/// Builders of [`ExtrinsicParams`] that allow to set the tip of an extrinsic.
+/// Used by the api to bump the tip when resubmitting an extrinsic.
+pub trait WithTip {
+	type Tip;
+
+	/// Set the tip given to the block author.
+	fn with_tip(self, tip: Self::Tip) -> Self;
 }
 
 /// A struct representing the signed extra and additional parameters required
@@ -155,6 +203,20 @@ impl<Tip: Default, Hash> Default for BaseExtrinsicParamsBuilder<Tip, Hash> {
 	}
 }
 
+impl<Tip: Default, Hash> WithEra<Hash> for BaseExtrinsicParamsBuilder<Tip, Hash> {
+	fn with_era(self, era: Era, checkpoint: Hash) -> Self {
+		self.era(era, checkpoint)
+	}
+}
+
+impl<Tip: Default, Hash> WithTip for BaseExtrinsicParamsBuilder<Tip, Hash> {
+	type Tip = Tip;
+
+	fn with_tip(self, tip: Tip) -> Self {
+		self.tip(tip)
+	}
+}
+
 impl<Tip, Index, Hash> ExtrinsicParams<Index, Hash> for BaseExtrinsicParams<Tip, Index, Hash>
 where
 	u128: From<Tip>,
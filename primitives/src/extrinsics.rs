@@ -33,21 +33,30 @@ pub type CallIndex = [u8; 2];
 
 /// Mirrors the currently used Extrinsic format (V4) from substrate. Has less traits and methods though.
 /// The SingedExtra used does not need to implement SingedExtension here.
+/// The address and signature default to the ones of `AccountId32` based runtimes.
 #[derive(Clone, Eq, PartialEq)]
-pub struct UncheckedExtrinsicV4<Call, SignedExtra> {
-	pub signature: Option<(GenericAddress, MultiSignature, SignedExtra)>,
+pub struct UncheckedExtrinsicV4<
+	Call,
+	SignedExtra,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> {
+	pub signature: Option<(Address, Signature, SignedExtra)>,
 	pub function: Call,
 }
 
-impl<Call, SignedExtra> UncheckedExtrinsicV4<Call, SignedExtra>
+impl<Call, SignedExtra, Address, Signature>
+	UncheckedExtrinsicV4<Call, SignedExtra, Address, Signature>
 where
+	Address: Encode,
 	Call: Encode,
+	Signature: Encode,
 	SignedExtra: Encode,
 {
 	pub fn new_signed(
 		function: Call,
-		signed: GenericAddress,
-		signature: MultiSignature,
+		signed: Address,
+		signature: Signature,
 		extra: SignedExtra,
 	) -> Self {
 		UncheckedExtrinsicV4 { signature: Some((signed, signature, extra)), function }
@@ -60,8 +69,10 @@ where
 	}
 }
 
-impl<Call, SignedExtra> fmt::Debug for UncheckedExtrinsicV4<Call, SignedExtra>
+impl<Call, SignedExtra, Address, Signature> fmt::Debug
+	for UncheckedExtrinsicV4<Call, SignedExtra, Address, Signature>
 where
+	Address: fmt::Debug,
 	Call: fmt::Debug,
 	SignedExtra: fmt::Debug,
 {
@@ -77,9 +88,12 @@ where
 
 const V4: u8 = 4;
 
-impl<Call, SignedExtra> Encode for UncheckedExtrinsicV4<Call, SignedExtra>
+impl<Call, SignedExtra, Address, Signature> Encode
+	for UncheckedExtrinsicV4<Call, SignedExtra, Address, Signature>
 where
+	Address: Encode,
 	Call: Encode,
+	Signature: Encode,
 	SignedExtra: Encode,
 {
 	fn encode(&self) -> Vec<u8> {
@@ -98,9 +112,12 @@ where
 	}
 }
 
-impl<Call, SignedExtra> Decode for UncheckedExtrinsicV4<Call, SignedExtra>
+impl<Call, SignedExtra, Address, Signature> Decode
+	for UncheckedExtrinsicV4<Call, SignedExtra, Address, Signature>
 where
+	Address: Decode + Encode,
 	Call: Decode + Encode,
+	Signature: Decode + Encode,
 	SignedExtra: Decode + Encode,
 {
 	fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
@@ -125,6 +142,162 @@ where
 	}
 }
 
+/// Version of the extrinsic format, encoded in the lower 6 bits of the first byte of an extrinsic.
+pub type ExtrinsicVersion = u8;
+/// Version of the transaction extensions of a general extrinsic.
+pub type ExtensionVersion = u8;
+
+pub const EXTRINSIC_VERSION_V4: ExtrinsicVersion = V4;
+pub const EXTRINSIC_VERSION_V5: ExtrinsicVersion = 5;
+
+const VERSION_MASK: u8 = 0b0011_1111;
+const TYPE_MASK: u8 = 0b1100_0000;
+const BARE_EXTRINSIC: u8 = 0b0000_0000;
+const SIGNED_EXTRINSIC: u8 = 0b1000_0000;
+const GENERAL_EXTRINSIC: u8 = 0b0100_0000;
+
+/// The part of an extrinsic in front of its call, which determines the type of the extrinsic.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Preamble<Address, Signature, Extension> {
+	/// An extrinsic without signature and extensions, e.g. an inherent. Part of v4 and v5.
+	Bare(ExtrinsicVersion),
+	/// An extrinsic signed by `Address`, with the signed extensions. Only part of v4.
+	Signed(Address, Signature, Extension),
+	/// An extrinsic with versioned transaction extensions, which authorize the extrinsic
+	/// themselves. Only part of v5.
+	General(ExtensionVersion, Extension),
+}
+
+/// Extrinsic of the format v4 or v5. In contrast to [`UncheckedExtrinsicV4`], the version is
+/// detected when decoding. Signed extrinsics are always of the format v4, since v5 replaces
+/// them with general extrinsics.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct UncheckedExtrinsic<Address, Call, Signature, Extension> {
+	pub preamble: Preamble<Address, Signature, Extension>,
+	pub function: Call,
+}
+
+impl<Address, Call, Signature, Extension> UncheckedExtrinsic<Address, Call, Signature, Extension>
+where
+	Address: Encode,
+	Call: Encode,
+	Signature: Encode,
+	Extension: Encode,
+{
+	pub fn new_bare(version: ExtrinsicVersion, function: Call) -> Self {
+		Self { preamble: Preamble::Bare(version), function }
+	}
+
+	pub fn new_signed(
+		function: Call,
+		signed: Address,
+		signature: Signature,
+		extension: Extension,
+	) -> Self {
+		Self { preamble: Preamble::Signed(signed, signature, extension), function }
+	}
+
+	pub fn new_general(
+		extension_version: ExtensionVersion,
+		function: Call,
+		extension: Extension,
+	) -> Self {
+		Self { preamble: Preamble::General(extension_version, extension), function }
+	}
+
+	/// The version of the extrinsic format.
+	pub fn version(&self) -> ExtrinsicVersion {
+		match self.preamble {
+			Preamble::Bare(version) => version,
+			Preamble::Signed(..) => EXTRINSIC_VERSION_V4,
+			Preamble::General(..) => EXTRINSIC_VERSION_V5,
+		}
+	}
+
+	pub fn is_signed(&self) -> bool {
+		matches!(self.preamble, Preamble::Signed(..))
+	}
+
+	pub fn hex_encode(&self) -> alloc::string::String {
+		let mut hex_str = hex::encode(self.encode());
+		hex_str.insert_str(0, "0x");
+		hex_str
+	}
+}
+
+impl<Address, Call, Signature, Extension> Encode
+	for UncheckedExtrinsic<Address, Call, Signature, Extension>
+where
+	Address: Encode,
+	Call: Encode,
+	Signature: Encode,
+	Extension: Encode,
+{
+	fn encode(&self) -> Vec<u8> {
+		encode_with_vec_prefix::<Self, _>(|v| {
+			match &self.preamble {
+				Preamble::Bare(version) => {
+					v.push((version & VERSION_MASK) | BARE_EXTRINSIC);
+				},
+				Preamble::Signed(address, signature, extension) => {
+					v.push(EXTRINSIC_VERSION_V4 | SIGNED_EXTRINSIC);
+					(address, signature, extension).encode_to(v);
+				},
+				Preamble::General(extension_version, extension) => {
+					v.push(EXTRINSIC_VERSION_V5 | GENERAL_EXTRINSIC);
+					extension_version.encode_to(v);
+					extension.encode_to(v);
+				},
+			}
+			self.function.encode_to(v);
+		})
+	}
+}
+
+impl<Address, Call, Signature, Extension> Decode
+	for UncheckedExtrinsic<Address, Call, Signature, Extension>
+where
+	Address: Decode + Encode,
+	Call: Decode + Encode,
+	Signature: Decode + Encode,
+	Extension: Decode + Encode,
+{
+	fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+		// Length prefix, see `UncheckedExtrinsicV4`.
+		let _length_do_not_remove_me_see_above: Vec<()> = Decode::decode(input)?;
+
+		let first_byte = input.read_byte()?;
+		let version = first_byte & VERSION_MASK;
+		let preamble = match (first_byte & TYPE_MASK, version) {
+			(BARE_EXTRINSIC, EXTRINSIC_VERSION_V4 | EXTRINSIC_VERSION_V5) =>
+				Preamble::Bare(version),
+			(SIGNED_EXTRINSIC, EXTRINSIC_VERSION_V4) => Preamble::Signed(
+				Decode::decode(input)?,
+				Decode::decode(input)?,
+				Decode::decode(input)?,
+			),
+			(GENERAL_EXTRINSIC, EXTRINSIC_VERSION_V5) =>
+				Preamble::General(input.read_byte()?, Decode::decode(input)?),
+			_ => return Err("Invalid transaction version or type".into()),
+		};
+
+		Ok(UncheckedExtrinsic { preamble, function: Decode::decode(input)? })
+	}
+}
+
+impl<Call, SignedExtra, Address, Signature>
+	From<UncheckedExtrinsicV4<Call, SignedExtra, Address, Signature>>
+	for UncheckedExtrinsic<Address, Call, Signature, SignedExtra>
+{
+	fn from(extrinsic: UncheckedExtrinsicV4<Call, SignedExtra, Address, Signature>) -> Self {
+		let preamble = match extrinsic.signature {
+			Some((address, signature, extra)) => Preamble::Signed(address, signature, extra),
+			None => Preamble::Bare(EXTRINSIC_VERSION_V4),
+		};
+		UncheckedExtrinsic { preamble, function: extrinsic.function }
+	}
+}
+
 /// Same function as in primitives::generic. Needed to be copied as it is private there.
 fn encode_with_vec_prefix<T: Encode, F: Fn(&mut Vec<u8>)>(encoder: F) -> Vec<u8> {
 	let size = sp_std::mem::size_of::<T>();
@@ -152,9 +325,10 @@ fn encode_with_vec_prefix<T: Encode, F: Fn(&mut Vec<u8>)>(encoder: F) -> Vec<u8>
 mod tests {
 	use super::*;
 	use crate::{BaseExtrinsicParams, ExtrinsicParams, PlainTipExtrinsicParamsBuilder};
+	use codec::Compact;
 	use node_template_runtime::Runtime;
 	use sp_core::{Pair, H256 as Hash};
-	use sp_runtime::{generic::Era, testing::sr25519, MultiSignature};
+	use sp_runtime::{generic::Era, testing::sr25519};
 
 	#[test]
 	fn encode_decode_roundtrip_works() {
@@ -169,11 +343,81 @@ mod tests {
 		let default_extra = BaseExtrinsicParams::new(0, 0, 0u32, Hash::from([0u8; 32]), tx_params);
 		let xt = UncheckedExtrinsicV4::new_signed(
 			vec![1, 1, 1],
-			account.into(),
+			GenericAddress::from(account),
 			multi_sig,
 			default_extra.signed_extra(),
 		);
 		let xt_enc = xt.encode();
 		assert_eq!(xt, Decode::decode(&mut xt_enc.as_slice()).unwrap())
 	}
+
+	#[test]
+	fn extrinsic_with_account_id_address_roundtrips() {
+		let (pair, _) = sr25519::Pair::generate();
+		let account: AccountId = pair.public().into();
+		let xt = UncheckedExtrinsicV4::new_signed(
+			vec![1u8, 1, 1],
+			account.clone(),
+			pair.sign(b"payload"),
+			42u32,
+		);
+
+		let xt_enc = xt.encode();
+		let decoded: UncheckedExtrinsicV4<Vec<u8>, u32, AccountId, sr25519::Signature> =
+			Decode::decode(&mut xt_enc.as_slice()).unwrap();
+
+		assert_eq!(decoded, xt);
+		let multi_address_xt = UncheckedExtrinsicV4::new_signed(
+			vec![1u8, 1, 1],
+			GenericAddress::from(account),
+			pair.sign(b"payload"),
+			42u32,
+		);
+		// Without the variant index of the `MultiAddress`.
+		assert_eq!(multi_address_xt.encode().len(), xt_enc.len() + 1);
+	}
+
+	#[test]
+	fn v4_extrinsic_decodes_as_versioned_extrinsic() {
+		let (pair, _) = sr25519::Pair::generate();
+		let signed_xt = UncheckedExtrinsicV4::new_signed(
+			vec![1u8, 1, 1],
+			GenericAddress::from(AccountId::from(pair.public())),
+			MultiSignature::from(pair.sign(b"payload")),
+			42u32,
+		);
+		let bare_xt = UncheckedExtrinsicV4::<_, u32> { signature: None, function: vec![1u8, 1, 1] };
+
+		for xt in [signed_xt, bare_xt] {
+			let decoded: UncheckedExtrinsic<_, _, _, _> =
+				Decode::decode(&mut xt.encode().as_slice()).unwrap();
+			assert_eq!(decoded.version(), EXTRINSIC_VERSION_V4);
+			assert_eq!(decoded, UncheckedExtrinsic::from(xt));
+		}
+	}
+
+	#[test]
+	fn v5_extrinsics_roundtrip() {
+		type Xt = UncheckedExtrinsic<GenericAddress, Vec<u8>, MultiSignature, u32>;
+		let bare_xt = Xt::new_bare(EXTRINSIC_VERSION_V5, vec![1, 1, 1]);
+		let general_xt = Xt::new_general(0, vec![1, 1, 1], 42);
+
+		assert_eq!(bare_xt.encode()[1], 0b0000_0101);
+		assert_eq!(general_xt.encode()[1..3], [0b0100_0101, 0]);
+		for xt in [bare_xt, general_xt] {
+			assert_eq!(xt.version(), EXTRINSIC_VERSION_V5);
+			assert!(!xt.is_signed());
+			assert_eq!(Xt::decode(&mut xt.encode().as_slice()).unwrap(), xt);
+		}
+	}
+
+	#[test]
+	fn unknown_version_or_type_is_rejected() {
+		type Xt = UncheckedExtrinsic<GenericAddress, Vec<u8>, MultiSignature, u32>;
+		// Signed v5, general v4 and bare v6 extrinsics with an empty call.
+		for first_byte in [0b1000_0101u8, 0b0100_0100, 0b0000_0110] {
+			let encoded = (Compact(2u32), first_byte, Vec::<u8>::new()).encode();
+			assert!(Xt::decode(&mut encoded.as_slice()).is_err());
+		}
+	}
 }
@@ -19,14 +19,22 @@
 
 extern crate alloc;
 
+pub use ethereum::*;
 pub use extrinsic_params::*;
 pub use extrinsics::*;
+pub use offline_signing::*;
 pub use pallet_traits::*;
 pub use rpc_params::RpcParams;
+pub use signed_extensions::*;
+pub use signer::*;
 pub use types::*;
 
+pub mod ethereum;
 pub mod extrinsic_params;
 pub mod extrinsics;
+pub mod offline_signing;
 pub mod pallet_traits;
 pub mod rpc_params;
+pub mod signed_extensions;
+pub mod signer;
 pub mod types;
@@ -0,0 +1,334 @@
+/*
+   Copyright 2019 Supercomputing Systems AG
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+	   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+*/
+
+//! Signing of extrinsics on an offline (air-gapped) machine:
+//!
+//! 1. The online machine creates a [`SigningRequest`] from the call and the [`ExtrinsicParams`],
+//!    and exports it as json.
+//! 2. The offline machine imports the request, checks that its payload is the one of its call,
+//!    signs it and exports the signature as a json [`SigningResponse`].
+//! 3. The online machine assembles the extrinsic from the response and the original call
+//!    and signed extra with [`SigningRequest::extrinsic`].
+
+use crate::{ExtrinsicParams, SignExtrinsic, SignedPayload, UncheckedExtrinsicV4};
+use alloc::{string::String, vec::Vec};
+use codec::{Decode, DecodeAll, Encode, Output};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// Everything needed to sign an extrinsic offline. All fields are SCALE encoded.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningRequest {
+	/// The address of the signer, to select the key on the offline machine.
+	#[serde(with = "hex_bytes")]
+	pub address: Vec<u8>,
+	#[serde(with = "hex_bytes")]
+	pub call: Vec<u8>,
+	#[serde(with = "hex_bytes")]
+	pub signed_extra: Vec<u8>,
+	#[serde(with = "hex_bytes")]
+	pub additional_signed: Vec<u8>,
+	/// The bytes to sign: The encoded call, signed extra and additional signed data,
+	/// or their `blake2_256` hash if they are longer than 256 bytes. Signing fails if it
+	/// does not match them.
+	#[serde(with = "hex_bytes")]
+	pub payload: Vec<u8>,
+}
+
+/// The signature of a [`SigningRequest`].
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SigningResponse {
+	/// The SCALE encoded signature, e.g. a `MultiSignature`.
+	#[serde(with = "hex_bytes")]
+	pub signature: Vec<u8>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum OfflineSigningError {
+	/// The request or the response could not be decoded.
+	Decode(codec::Error),
+	/// The payload to sign is not the one of the call, signed extra and additional signed data
+	/// of the request, or the call or signed extra given to assemble the extrinsic are not the
+	/// signed ones.
+	PayloadMismatch,
+	/// The address of the signer is not the one of the request.
+	AddressMismatch,
+}
+
+impl fmt::Display for OfflineSigningError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Decode(error) => write!(f, "Invalid signing request or response: {:?}", error),
+			Self::PayloadMismatch => write!(f, "Payload does not match the signing request"),
+			Self::AddressMismatch => write!(f, "Signer does not match the signing request"),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OfflineSigningError {}
+
+impl From<codec::Error> for OfflineSigningError {
+	fn from(error: codec::Error) -> Self {
+		Self::Decode(error)
+	}
+}
+
+impl SigningRequest {
+	/// Create the request to sign `call` with the given `params` by the signer with `address`.
+	pub fn new<Address, Call, Params, Index, Hash>(
+		address: &Address,
+		call: &Call,
+		params: &Params,
+	) -> Self
+	where
+		Address: Encode,
+		Call: Encode,
+		Params: ExtrinsicParams<Index, Hash>,
+	{
+		let mut request = Self {
+			address: address.encode(),
+			call: call.encode(),
+			signed_extra: params.signed_extra().encode(),
+			additional_signed: params.additional_signed().encode(),
+			payload: Vec::new(),
+		};
+		request.payload = request.expected_payload();
+		request
+	}
+
+	/// The address of the signer.
+	pub fn signer_address<Address: Decode>(&self) -> Result<Address, OfflineSigningError> {
+		Ok(Address::decode_all(&mut self.address.as_slice())?)
+	}
+
+	/// Sign the payload on the offline machine. Fails if the payload is not the one of the
+	/// call, signed extra and additional signed data of the request, so that a compromised
+	/// online machine can not get another call signed than the one it shows. Fails as well if
+	/// the address of the `signer` is not the one of the request, as the extrinsic would be
+	/// assembled with the address of the request.
+	pub fn sign<AccountId>(
+		&self,
+		signer: &impl SignExtrinsic<AccountId>,
+	) -> Result<SigningResponse, OfflineSigningError> {
+		if signer.extrinsic_address().encode() != self.address {
+			return Err(OfflineSigningError::AddressMismatch)
+		}
+		if self.payload != self.expected_payload() {
+			return Err(OfflineSigningError::PayloadMismatch)
+		}
+		Ok(SigningResponse::new(&signer.sign(&self.payload)))
+	}
+
+	/// The payload of the call, signed extra and additional signed data of the request.
+	fn expected_payload(&self) -> Vec<u8> {
+		SignedPayload::from_raw(
+			Encoded(&self.call),
+			Encoded(&self.signed_extra),
+			Encoded(&self.additional_signed),
+		)
+		.using_encoded(|payload| payload.to_vec())
+	}
+
+	/// Assemble the signed extrinsic from the `response` of the offline machine and the `call`
+	/// and `signed_extra` the request has been created with.
+	pub fn extrinsic<Address, Call, Signature, SignedExtra>(
+		&self,
+		call: Call,
+		signed_extra: SignedExtra,
+		response: &SigningResponse,
+	) -> Result<UncheckedExtrinsicV4<Call, SignedExtra, Address, Signature>, OfflineSigningError>
+	where
+		Address: Encode + Decode,
+		Call: Encode,
+		Signature: Encode + Decode,
+		SignedExtra: Encode,
+	{
+		if call.encode() != self.call || signed_extra.encode() != self.signed_extra {
+			return Err(OfflineSigningError::PayloadMismatch)
+		}
+		Ok(UncheckedExtrinsicV4::new_signed(
+			call,
+			self.signer_address()?,
+			response.signature()?,
+			signed_extra,
+		))
+	}
+
+	pub fn to_json(&self) -> serde_json::Result<String> {
+		serde_json::to_string(self)
+	}
+
+	pub fn from_json(json: &str) -> serde_json::Result<Self> {
+		serde_json::from_str(json)
+	}
+}
+
+impl SigningResponse {
+	pub fn new(signature: &impl Encode) -> Self {
+		Self { signature: signature.encode() }
+	}
+
+	pub fn signature<Signature: Decode>(&self) -> Result<Signature, OfflineSigningError> {
+		Ok(Signature::decode_all(&mut self.signature.as_slice())?)
+	}
+
+	pub fn to_json(&self) -> serde_json::Result<String> {
+		serde_json::to_string(self)
+	}
+
+	pub fn from_json(json: &str) -> serde_json::Result<Self> {
+		serde_json::from_str(json)
+	}
+}
+
+/// Already SCALE encoded bytes, which are encoded as they are.
+struct Encoded<'a>(&'a [u8]);
+
+impl Encode for Encoded<'_> {
+	fn size_hint(&self) -> usize {
+		self.0.len()
+	}
+
+	fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+		dest.write(self.0)
+	}
+}
+
+/// (De)serialize bytes as `0x` prefixed hex string.
+mod hex_bytes {
+	use alloc::{string::String, vec::Vec};
+	use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+	pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+		let mut hex_str = hex::encode(bytes);
+		hex_str.insert_str(0, "0x");
+		serializer.serialize_str(&hex_str)
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+		let hex_str = String::deserialize(deserializer)?;
+		hex::decode(hex_str.trim_start_matches("0x")).map_err(D::Error::custom)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{BaseExtrinsicParams, BaseExtrinsicParamsBuilder, PlainTip};
+	use sp_core::{ed25519, Pair, H256};
+	use sp_runtime::{traits::Verify, MultiSignature};
+
+	type Params = BaseExtrinsicParams<PlainTip<u128>, u32, H256>;
+
+	fn params() -> Params {
+		Params::new(1, 2, 3, H256::repeat_byte(1), BaseExtrinsicParamsBuilder::new())
+	}
+
+	fn pair() -> ed25519::Pair {
+		ed25519::Pair::from_seed(&[1; 32])
+	}
+
+	#[test]
+	fn extrinsic_equals_extrinsic_signed_online() {
+		let pair = pair();
+		let call = ([4u8, 0], vec![5u8; 10]);
+		let request = SigningRequest::new(&pair.extrinsic_address(), &call, &params());
+
+		let request = SigningRequest::from_json(&request.to_json().unwrap()).unwrap();
+		let response = request.sign(&pair).unwrap();
+		let response = SigningResponse::from_json(&response.to_json().unwrap()).unwrap();
+		let xt: UncheckedExtrinsicV4<_, _> =
+			request.extrinsic(call.clone(), params().signed_extra(), &response).unwrap();
+
+		let payload =
+			SignedPayload::from_raw(&call, params().signed_extra(), params().additional_signed());
+		let signature = payload.using_encoded(|payload| SignExtrinsic::sign(&pair, payload));
+		let expected = UncheckedExtrinsicV4::new_signed(
+			call,
+			pair.extrinsic_address(),
+			signature,
+			params().signed_extra(),
+		);
+		assert_eq!(xt.encode(), expected.encode());
+	}
+
+	#[test]
+	fn long_payloads_are_hashed() {
+		let pair = pair();
+		let call = ([4u8, 0], vec![5u8; 300]);
+
+		let request = SigningRequest::new(&pair.extrinsic_address(), &call, &params());
+
+		assert_eq!(request.payload.len(), 32);
+		let signature = request.sign(&pair).unwrap().signature::<MultiSignature>().unwrap();
+		let payload =
+			SignedPayload::from_raw(&call, params().signed_extra(), params().additional_signed());
+		let account_id = SignExtrinsic::account_id(&pair);
+		assert!(payload.using_encoded(|payload| signature.verify(payload, &account_id)));
+	}
+
+	#[test]
+	fn other_call_is_rejected() {
+		let pair = pair();
+		let request = SigningRequest::new(&pair.extrinsic_address(), &[4u8, 0], &params());
+		let response = request.sign(&pair).unwrap();
+
+		let result: Result<UncheckedExtrinsicV4<_, _>, _> =
+			request.extrinsic([4u8, 1], params().signed_extra(), &response);
+
+		assert_eq!(result.err(), Some(OfflineSigningError::PayloadMismatch));
+	}
+
+	#[test]
+	fn payload_of_other_call_is_not_signed() {
+		let pair = pair();
+		let mut request = SigningRequest::new(&pair.extrinsic_address(), &[4u8, 0], &params());
+		let other_request = SigningRequest::new(&pair.extrinsic_address(), &[4u8, 1], &params());
+		request.payload = other_request.payload;
+
+		assert_eq!(request.sign(&pair).err(), Some(OfflineSigningError::PayloadMismatch));
+	}
+
+	#[test]
+	fn request_of_other_signer_is_not_signed() {
+		let request = SigningRequest::new(&pair().extrinsic_address(), &[4u8, 0], &params());
+		let other_pair = ed25519::Pair::from_seed(&[2; 32]);
+
+		assert_eq!(request.sign(&other_pair).err(), Some(OfflineSigningError::AddressMismatch));
+	}
+
+	#[test]
+	fn request_is_exported_as_hex() {
+		let request = SigningRequest {
+			address: vec![0],
+			call: vec![4, 0],
+			signed_extra: vec![],
+			additional_signed: vec![1],
+			payload: vec![4, 0, 1],
+		};
+
+		let json = request.to_json().unwrap();
+
+		assert_eq!(
+			json,
+			r#"{"address":"0x00","call":"0x0400","signedExtra":"0x","additionalSigned":"0x01","payload":"0x040001"}"#
+		);
+		assert_eq!(SigningRequest::from_json(&json).unwrap(), request);
+	}
+}
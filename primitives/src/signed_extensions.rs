@@ -0,0 +1,574 @@
+/*
+   Copyright 2019 Supercomputing Systems AG
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+	   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+*/
+
+//! [`ExtrinsicParams`] that encode the signed extensions listed in the metadata of a runtime,
+//! instead of a fixed set of them. Each signed extension is encoded by a handler, looked up
+//! by its identifier in a [`SignedExtensionRegistry`].
+
+use crate::{ExtrinsicParams, WithEra, WithTip};
+use alloc::{
+	collections::BTreeMap,
+	string::{String, ToString},
+	sync::Arc,
+};
+use codec::{Compact, Encode, Output};
+use core::fmt;
+use sp_runtime::generic::Era;
+use sp_std::prelude::*;
+
+/// The values the signed extensions of an extrinsic are encoded from.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct SignedExtensionContext<Tip, Index, Hash> {
+	pub era: Era,
+	pub nonce: Index,
+	pub tip: Tip,
+	pub spec_version: u32,
+	pub transaction_version: u32,
+	pub genesis_hash: Hash,
+	pub mortality_checkpoint: Hash,
+	/// Metadata hash of the runtime, see `Metadata::metadata_hash` of `ac-node-api`.
+	/// `None` disables the `CheckMetadataHash` signed extension.
+	pub metadata_hash: Option<[u8; 32]>,
+}
+
+/// Encodes a single signed extension: Its extra data, which is sent along with the extrinsic,
+/// and its additional signed data, which is only part of the signed payload.
+pub trait SignedExtensionHandler<Tip, Index, Hash> {
+	fn encode_extra_to(
+		&self,
+		context: &SignedExtensionContext<Tip, Index, Hash>,
+		dest: &mut Vec<u8>,
+	);
+
+	fn encode_additional_signed_to(
+		&self,
+		context: &SignedExtensionContext<Tip, Index, Hash>,
+		dest: &mut Vec<u8>,
+	);
+}
+
+type EncodeFn<Tip, Index, Hash> = fn(&SignedExtensionContext<Tip, Index, Hash>, &mut Vec<u8>);
+
+/// Handler encoding the extra and additional signed data of a signed extension with
+/// the given functions.
+pub struct FnSignedExtensionHandler<Tip, Index, Hash> {
+	pub extra: EncodeFn<Tip, Index, Hash>,
+	pub additional_signed: EncodeFn<Tip, Index, Hash>,
+}
+
+impl<Tip, Index, Hash> SignedExtensionHandler<Tip, Index, Hash>
+	for FnSignedExtensionHandler<Tip, Index, Hash>
+{
+	fn encode_extra_to(
+		&self,
+		context: &SignedExtensionContext<Tip, Index, Hash>,
+		dest: &mut Vec<u8>,
+	) {
+		(self.extra)(context, dest)
+	}
+
+	fn encode_additional_signed_to(
+		&self,
+		context: &SignedExtensionContext<Tip, Index, Hash>,
+		dest: &mut Vec<u8>,
+	) {
+		(self.additional_signed)(context, dest)
+	}
+}
+
+pub type SharedSignedExtensionHandler<Tip, Index, Hash> =
+	Arc<dyn SignedExtensionHandler<Tip, Index, Hash> + Send + Sync>;
+
+/// A signed extension of the runtime that has no handler registered.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct UnknownSignedExtension(pub String);
+
+impl fmt::Display for UnknownSignedExtension {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "No handler registered for signed extension {}", self.0)
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownSignedExtension {}
+
+/// Handlers of signed extensions, by identifier.
+pub struct SignedExtensionRegistry<Tip, Index, Hash> {
+	handlers: BTreeMap<String, SharedSignedExtensionHandler<Tip, Index, Hash>>,
+}
+
+impl<Tip, Index, Hash> Clone for SignedExtensionRegistry<Tip, Index, Hash> {
+	fn clone(&self) -> Self {
+		Self { handlers: self.handlers.clone() }
+	}
+}
+
+impl<Tip, Index, Hash> SignedExtensionRegistry<Tip, Index, Hash> {
+	/// A registry without any handlers.
+	pub fn empty() -> Self {
+		Self { handlers: BTreeMap::new() }
+	}
+
+	/// Register the `handler` for the signed extension `identifier`,
+	/// replacing a previously registered one.
+	pub fn register(
+		&mut self,
+		identifier: impl Into<String>,
+		handler: impl SignedExtensionHandler<Tip, Index, Hash> + Send + Sync + 'static,
+	) -> &mut Self {
+		self.handlers.insert(identifier.into(), Arc::new(handler));
+		self
+	}
+
+	pub fn handler(
+		&self,
+		identifier: &str,
+	) -> Option<&SharedSignedExtensionHandler<Tip, Index, Hash>> {
+		self.handlers.get(identifier)
+	}
+}
+
+impl<Tip, Index, Hash> Default for SignedExtensionRegistry<Tip, Index, Hash>
+where
+	Tip: Encode + 'static,
+	Index: Copy + 'static,
+	Hash: Encode + 'static,
+	Compact<Index>: Encode,
+{
+	/// A registry with handlers for the signed extensions of the frame pallets. The tip of
+	/// `ChargeTransactionPayment` and `ChargeAssetTxPayment` is encoded as given, so it must
+	/// be a `PlainTip` or an `AssetTip` respectively.
+	fn default() -> Self {
+		let mut registry = Self::empty();
+		let none: EncodeFn<Tip, Index, Hash> = |_, _| {};
+		let mortality: EncodeFn<Tip, Index, Hash> =
+			|context, dest| context.mortality_checkpoint.encode_to(dest);
+		registry
+			.register(
+				"CheckNonZeroSender",
+				FnSignedExtensionHandler { extra: none, additional_signed: none },
+			)
+			.register(
+				"CheckSpecVersion",
+				FnSignedExtensionHandler {
+					extra: none,
+					additional_signed: |context, dest| context.spec_version.encode_to(dest),
+				},
+			)
+			.register(
+				"CheckTxVersion",
+				FnSignedExtensionHandler {
+					extra: none,
+					additional_signed: |context, dest| context.transaction_version.encode_to(dest),
+				},
+			)
+			.register(
+				"CheckGenesis",
+				FnSignedExtensionHandler {
+					extra: none,
+					additional_signed: |context, dest| context.genesis_hash.encode_to(dest),
+				},
+			)
+			.register(
+				"CheckMortality",
+				FnSignedExtensionHandler {
+					extra: |context, dest| context.era.encode_to(dest),
+					additional_signed: mortality,
+				},
+			)
+			.register(
+				"CheckEra",
+				FnSignedExtensionHandler {
+					extra: |context, dest| context.era.encode_to(dest),
+					additional_signed: mortality,
+				},
+			)
+			.register(
+				"CheckNonce",
+				FnSignedExtensionHandler {
+					extra: |context, dest| Compact(context.nonce).encode_to(dest),
+					additional_signed: none,
+				},
+			)
+			.register(
+				"CheckWeight",
+				FnSignedExtensionHandler { extra: none, additional_signed: none },
+			)
+			.register(
+				"ChargeTransactionPayment",
+				FnSignedExtensionHandler {
+					extra: |context, dest| context.tip.encode_to(dest),
+					additional_signed: none,
+				},
+			)
+			.register(
+				"ChargeAssetTxPayment",
+				FnSignedExtensionHandler {
+					extra: |context, dest| context.tip.encode_to(dest),
+					additional_signed: none,
+				},
+			)
+			.register(
+				"CheckMetadataHash",
+				FnSignedExtensionHandler {
+					// The mode: 0 is disabled, 1 is enabled.
+					extra: |context, dest| (context.metadata_hash.is_some() as u8).encode_to(dest),
+					additional_signed: |context, dest| context.metadata_hash.encode_to(dest),
+				},
+			);
+		registry
+	}
+}
+
+/// Already SCALE encoded data of signed extensions, which is encoded as is.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct EncodedSignedExtensions(pub Vec<u8>);
+
+impl Encode for EncodedSignedExtensions {
+	fn size_hint(&self) -> usize {
+		self.0.len()
+	}
+
+	fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+		dest.write(&self.0)
+	}
+}
+
+/// [`ExtrinsicParams`] encoding the signed extensions of a runtime in the order of its metadata.
+pub struct DynamicExtrinsicParams<Tip, Index, Hash> {
+	context: SignedExtensionContext<Tip, Index, Hash>,
+	handlers: Vec<SharedSignedExtensionHandler<Tip, Index, Hash>>,
+}
+
+/// Builder of [`DynamicExtrinsicParams`]. Unlike other builders, it needs to be created for the
+/// signed extensions of the runtime with [`DynamicExtrinsicParamsBuilder::new`]. The api does so
+/// with the default [`SignedExtensionRegistry`] when it is created. The default builder has no
+/// signed extensions at all, the api completes it with the signed extensions of the runtime.
+pub struct DynamicExtrinsicParamsBuilder<Tip, Index, Hash> {
+	era: Era,
+	mortality_checkpoint: Option<Hash>,
+	tip: Tip,
+	metadata_hash: Option<[u8; 32]>,
+	handlers: Vec<SharedSignedExtensionHandler<Tip, Index, Hash>>,
+}
+
+impl<Tip: Default, Index, Hash> DynamicExtrinsicParamsBuilder<Tip, Index, Hash> {
+	/// Create a builder for the signed extensions with the given `identifiers`, in the order
+	/// of the metadata (see `Metadata::signed_extension_identifiers`). Fails if the `registry`
+	/// has no handler for one of them.
+	pub fn new<I: AsRef<str>>(
+		identifiers: &[I],
+		registry: &SignedExtensionRegistry<Tip, Index, Hash>,
+	) -> Result<Self, UnknownSignedExtension> {
+		let handlers = identifiers
+			.iter()
+			.map(|identifier| {
+				let identifier = identifier.as_ref();
+				registry
+					.handler(identifier)
+					.cloned()
+					.ok_or_else(|| UnknownSignedExtension(identifier.to_string()))
+			})
+			.collect::<Result<_, _>>()?;
+		Ok(Self { handlers, ..Default::default() })
+	}
+
+	/// Set the [`Era`] and the hash of the block it starts at, see `BaseExtrinsicParamsBuilder::era`.
+	pub fn era(mut self, era: Era, checkpoint: Hash) -> Self {
+		self.era = era;
+		self.mortality_checkpoint = Some(checkpoint);
+		self
+	}
+
+	/// Set the tip you'd like to give to the block author
+	/// for this transaction.
+	pub fn tip(mut self, tip: impl Into<Tip>) -> Self {
+		self.tip = tip.into();
+		self
+	}
+
+	/// Enable the `CheckMetadataHash` signed extension, committing to the given metadata hash.
+	pub fn metadata_hash(mut self, metadata_hash: [u8; 32]) -> Self {
+		self.metadata_hash = Some(metadata_hash);
+		self
+	}
+}
+
+impl<Tip: Default, Index, Hash> Default for DynamicExtrinsicParamsBuilder<Tip, Index, Hash> {
+	fn default() -> Self {
+		Self {
+			era: Era::Immortal,
+			mortality_checkpoint: None,
+			tip: Tip::default(),
+			metadata_hash: None,
+			handlers: Vec::new(),
+		}
+	}
+}
+
+impl<Tip: Clone, Index, Hash: Clone> Clone for DynamicExtrinsicParamsBuilder<Tip, Index, Hash> {
+	fn clone(&self) -> Self {
+		Self {
+			era: self.era,
+			mortality_checkpoint: self.mortality_checkpoint.clone(),
+			tip: self.tip.clone(),
+			metadata_hash: self.metadata_hash,
+			handlers: self.handlers.clone(),
+		}
+	}
+}
+
+impl<Tip: Default, Index, Hash> WithEra<Hash> for DynamicExtrinsicParamsBuilder<Tip, Index, Hash> {
+	fn with_era(self, era: Era, checkpoint: Hash) -> Self {
+		self.era(era, checkpoint)
+	}
+}
+
+impl<Tip: Default, Index, Hash> WithTip for DynamicExtrinsicParamsBuilder<Tip, Index, Hash> {
+	type Tip = Tip;
+
+	fn with_tip(self, tip: Tip) -> Self {
+		self.tip(tip)
+	}
+}
+
+impl<Tip, Index, Hash> ExtrinsicParams<Index, Hash> for DynamicExtrinsicParams<Tip, Index, Hash>
+where
+	Tip: Clone + Default + Encode + 'static,
+	Index: Copy + 'static,
+	Hash: Clone + Encode + 'static,
+	Compact<Index>: Encode,
+{
+	type OtherParams = DynamicExtrinsicParamsBuilder<Tip, Index, Hash>;
+	type SignedExtra = EncodedSignedExtensions;
+	type AdditionalSigned = EncodedSignedExtensions;
+
+	fn new(
+		spec_version: u32,
+		transaction_version: u32,
+		nonce: Index,
+		genesis_hash: Hash,
+		other_params: Self::OtherParams,
+	) -> Self {
+		let context = SignedExtensionContext {
+			era: other_params.era,
+			nonce,
+			tip: other_params.tip,
+			spec_version,
+			transaction_version,
+			mortality_checkpoint: other_params
+				.mortality_checkpoint
+				.unwrap_or_else(|| genesis_hash.clone()),
+			genesis_hash,
+			metadata_hash: other_params.metadata_hash,
+		};
+		Self { context, handlers: other_params.handlers }
+	}
+
+	fn signed_extra(&self) -> Self::SignedExtra {
+		let mut extra = Vec::new();
+		for handler in &self.handlers {
+			handler.encode_extra_to(&self.context, &mut extra);
+		}
+		EncodedSignedExtensions(extra)
+	}
+
+	fn additional_signed(&self) -> Self::AdditionalSigned {
+		let mut additional_signed = Vec::new();
+		for handler in &self.handlers {
+			handler.encode_additional_signed_to(&self.context, &mut additional_signed);
+		}
+		EncodedSignedExtensions(additional_signed)
+	}
+
+	fn other_params_for_signed_extensions<I: AsRef<str>>(
+		identifiers: &[I],
+	) -> Result<Option<Self::OtherParams>, UnknownSignedExtension> {
+		DynamicExtrinsicParamsBuilder::new(identifiers, &SignedExtensionRegistry::default())
+			.map(Some)
+	}
+
+	/// Builders created with handlers, e.g. of a custom registry, keep them.
+	fn other_params_with_signed_extensions(
+		mut other_params: Self::OtherParams,
+		runtime_params: &Self::OtherParams,
+	) -> Self::OtherParams {
+		if other_params.handlers.is_empty() {
+			other_params.handlers = runtime_params.handlers.clone();
+		}
+		other_params
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{BaseExtrinsicParams, BaseExtrinsicParamsBuilder, PlainTip};
+	use sp_core::H256;
+
+	type Tip = PlainTip<u128>;
+
+	const DEFAULT_EXTENSIONS: [&str; 8] = [
+		"CheckNonZeroSender",
+		"CheckSpecVersion",
+		"CheckTxVersion",
+		"CheckGenesis",
+		"CheckMortality",
+		"CheckNonce",
+		"CheckWeight",
+		"ChargeTransactionPayment",
+	];
+
+	#[test]
+	fn default_extensions_encode_like_base_extrinsic_params() {
+		let registry = SignedExtensionRegistry::<Tip, u32, H256>::default();
+		let era = Era::mortal(32, 100);
+		let checkpoint = H256::repeat_byte(2);
+		let genesis_hash = H256::repeat_byte(1);
+
+		let dynamic_params = DynamicExtrinsicParams::new(
+			9,
+			2,
+			5,
+			genesis_hash,
+			DynamicExtrinsicParamsBuilder::new(&DEFAULT_EXTENSIONS, &registry)
+				.unwrap()
+				.era(era, checkpoint)
+				.tip(Tip::new(10)),
+		);
+		let base_params = BaseExtrinsicParams::new(
+			9,
+			2,
+			5u32,
+			genesis_hash,
+			BaseExtrinsicParamsBuilder::<Tip, H256>::new()
+				.era(era, checkpoint)
+				.tip(Tip::new(10)),
+		);
+
+		assert_eq!(dynamic_params.signed_extra().encode(), base_params.signed_extra().encode());
+		assert_eq!(
+			dynamic_params.additional_signed().encode(),
+			base_params.additional_signed().encode()
+		);
+	}
+
+	#[test]
+	fn unknown_extensions_are_rejected() {
+		let registry = SignedExtensionRegistry::<Tip, u32, H256>::default();
+
+		let result = DynamicExtrinsicParamsBuilder::new(&["CheckNonce", "CheckFoo"], &registry);
+
+		assert_eq!(result.err(), Some(UnknownSignedExtension("CheckFoo".to_string())));
+	}
+
+	#[test]
+	fn params_builder_is_created_for_the_signed_extensions_of_the_runtime() {
+		type Params = DynamicExtrinsicParams<Tip, u32, H256>;
+		type Base = BaseExtrinsicParams<Tip, u32, H256>;
+		let genesis_hash = H256::repeat_byte(1);
+
+		let builder = Params::other_params_for_signed_extensions(&DEFAULT_EXTENSIONS)
+			.unwrap()
+			.unwrap();
+		let dynamic_params = Params::new(9, 2, 5, genesis_hash, builder);
+		let base_params = Base::new(9, 2, 5, genesis_hash, Default::default());
+
+		assert_eq!(dynamic_params.signed_extra().encode(), base_params.signed_extra().encode());
+		assert_eq!(Base::other_params_for_signed_extensions(&DEFAULT_EXTENSIONS), Ok(None));
+		assert!(Params::other_params_for_signed_extensions(&["CheckFoo"]).is_err());
+	}
+
+	#[test]
+	fn default_builder_is_completed_with_the_signed_extensions_of_the_runtime() {
+		type Params = DynamicExtrinsicParams<Tip, u32, H256>;
+		let registry = SignedExtensionRegistry::<Tip, u32, H256>::default();
+		let runtime_params = DynamicExtrinsicParamsBuilder::new(&DEFAULT_EXTENSIONS, &registry)
+			.unwrap()
+			.tip(Tip::new(1));
+
+		let builder = Params::other_params_with_signed_extensions(
+			DynamicExtrinsicParamsBuilder::default().tip(Tip::new(10)),
+			&runtime_params,
+		);
+		let custom_builder = Params::other_params_with_signed_extensions(
+			DynamicExtrinsicParamsBuilder::new(&["CheckNonce"], &registry).unwrap(),
+			&runtime_params,
+		);
+
+		// Immortal era, nonce and tip.
+		assert_eq!(
+			Params::new(1, 1, 3, H256::zero(), builder).signed_extra().0,
+			vec![0, 3 << 2, 10 << 2]
+		);
+		assert_eq!(
+			Params::new(1, 1, 3, H256::zero(), custom_builder).signed_extra().0,
+			vec![3 << 2]
+		);
+	}
+
+	#[test]
+	fn custom_handlers_are_encoded_in_metadata_order() {
+		struct CheckFoo;
+		impl SignedExtensionHandler<Tip, u32, H256> for CheckFoo {
+			fn encode_extra_to(
+				&self,
+				_context: &SignedExtensionContext<Tip, u32, H256>,
+				dest: &mut Vec<u8>,
+			) {
+				7u8.encode_to(dest)
+			}
+
+			fn encode_additional_signed_to(
+				&self,
+				_context: &SignedExtensionContext<Tip, u32, H256>,
+				dest: &mut Vec<u8>,
+			) {
+				8u8.encode_to(dest)
+			}
+		}
+		let mut registry = SignedExtensionRegistry::<Tip, u32, H256>::default();
+		registry.register("CheckFoo", CheckFoo);
+
+		let params = DynamicExtrinsicParams::new(
+			1,
+			1,
+			3,
+			H256::zero(),
+			DynamicExtrinsicParamsBuilder::new(&["CheckFoo", "CheckNonce"], &registry).unwrap(),
+		);
+
+		assert_eq!(params.signed_extra().0, vec![7, 3 << 2]);
+		assert_eq!(params.additional_signed().0, vec![8]);
+	}
+
+	#[test]
+	fn check_metadata_hash_encodes_mode_and_hash() {
+		let registry = SignedExtensionRegistry::<Tip, u32, H256>::default();
+		let builder =
+			DynamicExtrinsicParamsBuilder::new(&["CheckMetadataHash"], &registry).unwrap();
+
+		let disabled = DynamicExtrinsicParams::new(1, 1, 0, H256::zero(), builder.clone());
+		let enabled =
+			DynamicExtrinsicParams::new(1, 1, 0, H256::zero(), builder.metadata_hash([7; 32]));
+
+		assert_eq!(disabled.signed_extra().0, vec![0]);
+		assert_eq!(disabled.additional_signed().0, vec![0]);
+		assert_eq!(enabled.signed_extra().0, vec![1]);
+		assert_eq!(enabled.additional_signed().0, Some([7u8; 32]).encode());
+	}
+}
@@ -0,0 +1,135 @@
+/*
+   Copyright 2019 Supercomputing Systems AG
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+	   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+*/
+
+//! Signer of extrinsics, which does not need to keep its private key in process memory.
+
+use crate::{AccountId, GenericAddress};
+use codec::Encode;
+use sp_core::{ecdsa, ed25519, sr25519, Pair};
+use sp_runtime::{traits::IdentifyAccount, MultiSignature, MultiSigner};
+
+/// Signs extrinsics on behalf of an account.
+///
+/// It is implemented for all key [`Pair`]s of `AccountId32` based runtimes, and by the
+/// [`EthereumSigner`](crate::EthereumSigner) for `AccountId20` based runtimes. Implement it to sign
+/// with a remote signer, a hardware security module or a keystore instead.
+pub trait SignExtrinsic<AccountId> {
+	/// The signature of the extrinsic, as expected by the runtime.
+	type Signature: Encode;
+	/// The address of the signer in the extrinsic, i.e. the lookup source of the runtime.
+	type ExtrinsicAddress: Encode;
+
+	/// Sign the payload of an extrinsic, i.e. the encoded `SignedPayload`.
+	fn sign(&self, payload: &[u8]) -> Self::Signature;
+
+	/// The account id of the signer in the runtime.
+	fn account_id(&self) -> AccountId;
+
+	/// The address of the signer in the extrinsic.
+	fn extrinsic_address(&self) -> Self::ExtrinsicAddress;
+
+	/// An invalid signature with the encoded length of a real one, e.g. to estimate the fee of
+	/// an extrinsic before it is signed, without asking the signer for a signature.
+	fn dummy_signature(&self) -> Self::Signature;
+}
+
+/// [`SignExtrinsic::sign`] under a name that does not clash with `Pair::sign`, so that it can be
+/// called with method call syntax. Like any method call, it dereferences the signer, which lets
+/// macros take a signer as well as a reference to it. A blanket implementation of
+/// [`SignExtrinsic`] for references would conflict with the one for all [`Pair`]s.
+pub trait SignExtrinsicExt<AccountId>: SignExtrinsic<AccountId> {
+	fn sign_extrinsic(&self, payload: &[u8]) -> Self::Signature {
+		SignExtrinsic::sign(self, payload)
+	}
+}
+
+impl<AccountId, T: SignExtrinsic<AccountId>> SignExtrinsicExt<AccountId> for T {}
+
+impl<P> SignExtrinsic<AccountId> for P
+where
+	P: Pair,
+	MultiSignature: From<P::Signature>,
+	MultiSigner: From<P::Public>,
+{
+	type Signature = MultiSignature;
+	type ExtrinsicAddress = GenericAddress;
+
+	fn sign(&self, payload: &[u8]) -> MultiSignature {
+		Pair::sign(self, payload).into()
+	}
+
+	fn account_id(&self) -> AccountId {
+		MultiSigner::from(self.public()).into_account()
+	}
+
+	fn extrinsic_address(&self) -> GenericAddress {
+		self.account_id().into()
+	}
+
+	fn dummy_signature(&self) -> MultiSignature {
+		match MultiSigner::from(self.public()) {
+			MultiSigner::Ed25519(_) => ed25519::Signature::from_raw([0; 64]).into(),
+			MultiSigner::Sr25519(_) => sr25519::Signature::from_raw([0; 64]).into(),
+			MultiSigner::Ecdsa(_) => ecdsa::Signature::from_raw([0; 65]).into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::traits::Verify;
+
+	#[test]
+	fn pair_signs_as_multi_signature() {
+		let (pair, _) = sr25519::Pair::generate();
+		let account_id = SignExtrinsic::account_id(&pair);
+
+		let signature = SignExtrinsic::sign(&pair, b"payload");
+
+		assert!(signature.verify(&b"payload"[..], &account_id));
+		assert_eq!(pair.extrinsic_address(), GenericAddress::Id(account_id));
+	}
+
+	#[test]
+	fn references_to_signers_sign_with_method_call_syntax() {
+		let (pair, _) = sr25519::Pair::generate();
+		let account_id = SignExtrinsic::account_id(&pair);
+		let signer = &&pair;
+
+		let signature = signer.sign_extrinsic(b"payload");
+
+		assert!(signature.verify(&b"payload"[..], &account_id));
+		assert_eq!(signer.extrinsic_address(), GenericAddress::Id(account_id));
+	}
+
+	#[test]
+	fn dummy_signature_has_the_length_of_a_real_one() {
+		fn assert_same_length<P: Pair>(pair: P)
+		where
+			MultiSignature: From<P::Signature>,
+			MultiSigner: From<P::Public>,
+		{
+			let signature = SignExtrinsic::sign(&pair, b"payload");
+			assert_eq!(pair.dummy_signature().encode().len(), signature.encode().len());
+		}
+
+		assert_same_length(ed25519::Pair::generate().0);
+		assert_same_length(sr25519::Pair::generate().0);
+		assert_same_length(ecdsa::Pair::generate().0);
+	}
+}
@@ -137,6 +137,20 @@ pub struct RuntimeDispatchInfo<Balance, Weight = sp_weights::OldWeight> {
 	pub partial_fee: Balance,
 }
 
+/// Estimated fee and weight of a call, queried with a dummy signed extrinsic before signing.
+#[derive(Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct FeeEstimate<Balance, Weight = sp_weights::OldWeight> {
+	/// The inclusion fee, without the tip.
+	pub partial_fee: Balance,
+	/// Weight of the dispatch.
+	pub weight: Weight,
+	/// Class of the dispatch.
+	pub class: DispatchClass,
+	/// Composition of the fee. `None` if the node did not return any.
+	pub fee_details: Option<FeeDetails<Balance>>,
+}
+
 #[cfg(feature = "std")]
 mod serde_balance {
 	use serde::{Deserialize, Deserializer, Serializer};
@@ -28,15 +28,26 @@ pub use sp_runtime::{
 };
 pub use sp_std::prelude::*;
 
-use crate::{rpc::Request, GetAccountInformation};
-use ac_compose_macros::rpc_params;
+use crate::{
+	api::{Mortality, NonceManager},
+	rpc::Request,
+	GetAccountInformation,
+};
+use ac_compose_macros::{compose_extrinsic_offline, rpc_params};
 use ac_node_api::metadata::Metadata;
-use ac_primitives::{ExtrinsicParams, FrameSystemConfig};
-use codec::Decode;
+use ac_primitives::{
+	ExtrinsicParams, ExtrinsicVersion, FrameSystemConfig, SignExtrinsic, UncheckedExtrinsic,
+	UnknownSignedExtension, WithEra,
+};
+use codec::{Decode, Encode};
 use core::convert::TryFrom;
-use log::{debug, info};
+use log::{debug, error, info};
+use serde::Deserialize;
 use sp_core::Bytes;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::generic::Era;
 use sp_version::RuntimeVersion;
+use std::{sync::Arc, time::Duration};
 
 /// Api to talk with substrate-nodes
 ///
@@ -105,6 +116,11 @@ where
 	runtime_version: RuntimeVersion,
 	client: Client,
 	extrinsic_params_builder: Option<Params::OtherParams>,
+	/// The builder created for the signed extensions of the runtime, if the extrinsic params
+	/// encode them. It completes the builder set by the user.
+	runtime_params_builder: Option<Params::OtherParams>,
+	mortality: Mortality,
+	nonce_manager: Arc<NonceManager<Runtime::Index>>,
 }
 
 impl<Signer, Client, Params, Runtime> Api<Signer, Client, Params, Runtime>
@@ -112,12 +128,37 @@ where
 	Runtime: FrameSystemConfig,
 	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
 {
-	/// Create a new api instance without any node interaction.
+	/// Create a new api instance without any node interaction. If the extrinsic params encode
+	/// the signed extensions listed in the `metadata`, e.g. `DynamicExtrinsicParams`, their
+	/// builder is created for them. If there is no handler for one of them, an error is logged
+	/// and a builder with handlers for all of them needs to be set with
+	/// `set_extrinsic_params_builder`.
 	pub fn new_offline(
 		genesis_hash: Runtime::Hash,
 		metadata: Metadata,
 		runtime_version: RuntimeVersion,
 		client: Client,
+	) -> Self {
+		let runtime_params_builder =
+			Self::extrinsic_params_builder_for(&metadata).unwrap_or_else(|e| {
+				error!("{}, set the extrinsic params builder of the api", e);
+				None
+			});
+		Self::new_with_builder(
+			genesis_hash,
+			metadata,
+			runtime_version,
+			client,
+			runtime_params_builder,
+		)
+	}
+
+	fn new_with_builder(
+		genesis_hash: Runtime::Hash,
+		metadata: Metadata,
+		runtime_version: RuntimeVersion,
+		client: Client,
+		runtime_params_builder: Option<Params::OtherParams>,
 	) -> Self {
 		Self {
 			signer: None,
@@ -126,15 +167,28 @@ where
 			runtime_version,
 			client,
 			extrinsic_params_builder: None,
+			runtime_params_builder,
+			mortality: Mortality::default(),
+			nonce_manager: Default::default(),
 		}
 	}
 
+	/// The extrinsic params builder for the signed extensions listed in the `metadata`,
+	/// if the extrinsic params depend on them.
+	fn extrinsic_params_builder_for(
+		metadata: &Metadata,
+	) -> Result<Option<Params::OtherParams>, UnknownSignedExtension> {
+		Params::other_params_for_signed_extensions(&metadata.signed_extension_identifiers())
+	}
+
 	/// Set the api signer account.
 	pub fn set_signer(&mut self, signer: Signer) {
 		self.signer = Some(signer);
+		// The nonces of the previous signer are of no use for the new one.
+		self.nonce_manager = Default::default();
 	}
 
-	/// Get the private key pair of the api signer.
+	/// Get the signer of the api.
 	pub fn signer(&self) -> Option<&Signer> {
 		self.signer.as_ref()
 	}
@@ -159,19 +213,83 @@ where
 		self.runtime_version.spec_version
 	}
 
+	/// Get the version of the extrinsic format, as advertised by the runtime in its metadata.
+	pub fn extrinsic_version(&self) -> ExtrinsicVersion {
+		self.metadata.extrinsic_version()
+	}
+
 	/// Get the rpc client.
 	pub fn client(&self) -> &Client {
 		&self.client
 	}
 
-	/// Set the extrinscs param builder.
+	/// Get the nonce manager of the api signer, shared by all clones of this api.
+	pub fn nonce_manager(&self) -> &NonceManager<Runtime::Index> {
+		&self.nonce_manager
+	}
+
+	/// The longest time a mortal extrinsic with the given era may take to be included in a block,
+	/// based on the expected block time of the runtime. `None` for immortal extrinsics or if the
+	/// block time is not found in the metadata.
+	pub fn mortality_timeout(&self, era: &Era) -> Option<Duration> {
+		match era {
+			Era::Immortal => None,
+			Era::Mortal(period, _) =>
+				Some(self.expected_block_time()? * u32::try_from(*period).ok()?),
+		}
+	}
+
+	/// The expected block time of the runtime, `Babe.ExpectedBlockTime` or twice the
+	/// `Timestamp.MinimumPeriod` for runtimes without babe.
+	pub fn expected_block_time(&self) -> Option<Duration> {
+		let constant = |pallet: &str, constant: &str| -> Option<u64> {
+			let value = &self.metadata.pallet(pallet).ok()?.constants.get(constant)?.value;
+			u64::decode(&mut value.as_slice()).ok()
+		};
+		constant("Babe", "ExpectedBlockTime")
+			.or_else(|| constant("Timestamp", "MinimumPeriod").map(|period| 2 * period))
+			.map(Duration::from_millis)
+	}
+
+	/// Set the extrinscs param builder. A builder without signed extensions, e.g. the default
+	/// `DynamicExtrinsicParamsBuilder`, gets the ones of the runtime.
 	pub fn set_extrinsic_params_builder(&mut self, extrinsic_params: Params::OtherParams) {
 		self.extrinsic_params_builder = Some(extrinsic_params);
 	}
 
+	/// Set the mortality policy of the extrinsics composed by the api. A mortal policy
+	/// overrides the era set in the extrinsic params builder.
+	pub fn set_mortality(&mut self, mortality: Mortality) {
+		self.mortality = mortality;
+	}
+
+	/// Get the mortality policy of the extrinsics composed by the api.
+	pub fn mortality(&self) -> Mortality {
+		self.mortality
+	}
+
+	/// Get the extrinsic params builder set, or the default one if none is set. Either is
+	/// completed with the signed extensions of the runtime, if the extrinsic params encode them.
+	pub fn extrinsic_params_builder(&self) -> Params::OtherParams {
+		match (&self.extrinsic_params_builder, &self.runtime_params_builder) {
+			(Some(builder), Some(runtime_builder)) =>
+				Params::other_params_with_signed_extensions(builder.clone(), runtime_builder),
+			(Some(builder), None) => builder.clone(),
+			(None, runtime_builder) => runtime_builder.clone().unwrap_or_default(),
+		}
+	}
+
 	/// Get the extrinsic params, built with the set or if none, the default Params Builder.
+	/// The mortality policy is not applied, see `extrinsic_params_with_mortality`.
 	pub fn extrinsic_params(&self, nonce: Runtime::Index) -> Params {
-		let extrinsic_params_builder = self.extrinsic_params_builder.clone().unwrap_or_default();
+		self.build_extrinsic_params(nonce, self.extrinsic_params_builder())
+	}
+
+	pub(crate) fn build_extrinsic_params(
+		&self,
+		nonce: Runtime::Index,
+		extrinsic_params_builder: Params::OtherParams,
+	) -> Params {
 		<Params as ExtrinsicParams<Runtime::Index, Runtime::Hash>>::new(
 			self.runtime_version.spec_version,
 			self.runtime_version.transaction_version,
@@ -182,6 +300,48 @@ where
 	}
 }
 
+impl<Signer, Client, Params, Runtime> Api<Signer, Client, Params, Runtime>
+where
+	Client: Request,
+	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+	Runtime: FrameSystemConfig,
+{
+	/// Get the extrinsic params like `extrinsic_params`, but with the era and checkpoint of
+	/// the mortality policy. For a mortal policy, the latest finalized block is fetched
+	/// from the node.
+	pub fn extrinsic_params_with_mortality(&self, nonce: Runtime::Index) -> ApiResult<Params>
+	where
+		Params::OtherParams: WithEra<Runtime::Hash>,
+	{
+		let mut extrinsic_params_builder = self.extrinsic_params_builder();
+		if let Mortality::Mortal { period } = self.mortality {
+			let (era, checkpoint) = self.mortal_era(period)?;
+			extrinsic_params_builder = extrinsic_params_builder.with_era(era, checkpoint);
+		}
+		Ok(self.build_extrinsic_params(nonce, extrinsic_params_builder))
+	}
+
+	/// Get a mortal era of `period` blocks starting at the latest finalized block,
+	/// together with the hash of that block as checkpoint.
+	pub fn mortal_era(&self, period: u64) -> ApiResult<(Era, Runtime::Hash)> {
+		let checkpoint: Runtime::Hash =
+			self.client.request("chain_getFinalizedHead", rpc_params![])?;
+		let header: HeaderNumber = self
+			.client
+			.request::<Option<_>>("chain_getHeader", rpc_params![Some(checkpoint)])?
+			.ok_or(ApiClientError::BlockHeaderNotFound)?;
+		let number = u64::try_from(header.number).map_err(|_| ApiClientError::TryFromIntError)?;
+		debug!("Mortal era of {} blocks starts at block {} ({:?})", period, number, checkpoint);
+		Ok((Era::mortal(period, number), checkpoint))
+	}
+}
+
+/// The number of a block header, independent of the header type of the runtime.
+#[derive(Deserialize)]
+struct HeaderNumber {
+	number: NumberOrHex,
+}
+
 impl<Signer, Client, Params, Runtime> Api<Signer, Client, Params, Runtime>
 where
 	Client: Request,
@@ -189,7 +349,8 @@ where
 	Runtime: FrameSystemConfig,
 	Runtime::Hash: FromHexString,
 {
-	/// Create a new Api client with call to the node to retrieve metadata.
+	/// Create a new Api client with call to the node to retrieve metadata. Fails if the
+	/// extrinsic params encode the signed extensions of the runtime, but one of them is unknown.
 	pub fn new(client: Client) -> ApiResult<Self> {
 		let genesis_hash = Self::get_genesis_hash(&client)?;
 		info!("Got genesis hash: {:?}", genesis_hash);
@@ -200,10 +361,19 @@ where
 		let runtime_version = Self::get_runtime_version(&client)?;
 		info!("Runtime Version: {:?}", runtime_version);
 
-		Ok(Self::new_offline(genesis_hash, metadata, runtime_version, client))
+		let runtime_params_builder = Self::extrinsic_params_builder_for(&metadata)?;
+		Ok(Self::new_with_builder(
+			genesis_hash,
+			metadata,
+			runtime_version,
+			client,
+			runtime_params_builder,
+		))
 	}
 
-	/// Updates the runtime and metadata of the api via node query.
+	/// Updates the runtime and metadata of the api via node query. If the extrinsic params
+	/// encode the signed extensions of the runtime, the builder set by the user is completed
+	/// with the signed extensions of the new runtime from now on. Fails if one of them is unknown.
 	// Ideally, this function is called if a substrate update runtime event is encountered.
 	pub fn update_runtime(&mut self) -> ApiResult<()> {
 		let metadata = Self::get_metadata(&self.client)?;
@@ -212,6 +382,7 @@ where
 		let runtime_version = Self::get_runtime_version(&self.client)?;
 		info!("Runtime Version: {:?}", runtime_version);
 
+		self.runtime_params_builder = Self::extrinsic_params_builder_for(&metadata)?;
 		self.metadata = metadata;
 		self.runtime_version = runtime_version;
 		Ok(())
@@ -220,17 +391,14 @@ where
 
 impl<Signer, Client, Params, Runtime> Api<Signer, Client, Params, Runtime>
 where
-	Signer: Pair,
-	MultiSignature: From<Signer::Signature>,
+	Signer: SignExtrinsic<Runtime::AccountId>,
 	Client: Request,
 	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
 	Runtime: FrameSystemConfig,
-	Runtime::AccountId: From<Signer::Public>,
 {
 	/// Get the public part of the api signer account.
 	pub fn signer_account(&self) -> Option<Runtime::AccountId> {
-		let pair = self.signer.as_ref()?;
-		Some(pair.public().into())
+		Some(self.signer.as_ref()?.account_id())
 	}
 
 	/// Get nonce of self signer account.
@@ -239,6 +407,49 @@ where
 		self.get_account_info(&account)
 			.map(|acc_opt| acc_opt.map_or_else(|| 0u32.into(), |acc| acc.nonce))
 	}
+
+	/// Get the next nonce of self signer account from the nonce manager. Unlike `get_nonce`,
+	/// this accounts for extrinsics that have been composed, but are not yet included in a block.
+	/// If a composed extrinsic is not submitted, give its nonce back with
+	/// `nonce_manager().rollback(nonce)`. Extrinsics signed with the nonce outside of the api
+	/// can be registered with `nonce_manager().track_extrinsic`, so that the submit methods
+	/// of the api keep the nonce manager up to date.
+	pub fn next_nonce(&self) -> ApiResult<Runtime::Index> {
+		let account = self.signer_account().ok_or(ApiClientError::NoSigner)?;
+		self.nonce_manager.next_nonce(|| self.get_account_next_index(account))
+	}
+
+	/// Compose an extrinsic of `call` in the format of the runtime: Signed by the api signer if
+	/// there is one, bare with the extrinsic version of the metadata otherwise. Signed extrinsics
+	/// are of the format v4, as v5 has no signed extrinsics and v5 runtimes accept v4 as well.
+	/// Like `next_nonce`, this reserves the nonce of a signed extrinsic, see `NonceManager`.
+	pub fn compose_unchecked_extrinsic<Call: Encode + Clone>(
+		&self,
+		call: Call,
+	) -> ApiResult<
+		UncheckedExtrinsic<Signer::ExtrinsicAddress, Call, Signer::Signature, Params::SignedExtra>,
+	>
+	where
+		Params::OtherParams: WithEra<Runtime::Hash>,
+	{
+		match self.signer() {
+			Some(signer) => {
+				let nonce = self.next_nonce()?;
+				let params = match self.extrinsic_params_with_mortality(nonce) {
+					Ok(params) => params,
+					Err(e) => {
+						self.nonce_manager.rollback(nonce);
+						return Err(e)
+					},
+				};
+				let extrinsic: UncheckedExtrinsic<_, _, _, _> =
+					compose_extrinsic_offline!(*signer, call, params).into();
+				self.nonce_manager.track_extrinsic(nonce, &extrinsic.encode());
+				Ok(extrinsic)
+			},
+			None => Ok(UncheckedExtrinsic::new_bare(self.extrinsic_version(), call)),
+		}
+	}
 }
 
 /// Private node query methods. They should be used internally only, because the user should retrieve the data from the struct cache.
@@ -271,3 +482,77 @@ where
 		Metadata::try_from(metadata).map_err(|e| e.into())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rpc::{SnapshotClient, SnapshotHeader, StateSnapshot};
+	use ac_primitives::{DynamicExtrinsicParams, DynamicExtrinsicParamsBuilder, PlainTip};
+	use frame_metadata::RuntimeMetadata;
+	use kitchensink_runtime::Runtime;
+	use sp_core::H256;
+	use std::collections::BTreeMap;
+
+	type Params = DynamicExtrinsicParams<PlainTip<u128>, u32, H256>;
+
+	/// The polkadot metadata, with the signed extensions that `retain` returns true for.
+	fn metadata_with_signed_extensions(retain: impl Fn(&str) -> bool) -> RuntimeMetadataPrefixed {
+		let bytes = include_bytes!("../../node-api/test_data/polkadot_metadata_v14.scale");
+		let mut metadata = RuntimeMetadataPrefixed::decode(&mut bytes.as_slice()).unwrap();
+		if let RuntimeMetadata::V14(ref mut metadata) = metadata.1 {
+			metadata.extrinsic.signed_extensions.retain(|e| retain(&e.identifier));
+		}
+		metadata
+	}
+
+	fn client_serving(metadata: &RuntimeMetadataPrefixed) -> SnapshotClient<H256> {
+		let header = SnapshotHeader {
+			block_hash: H256::repeat_byte(1),
+			state_root: H256::repeat_byte(2),
+			genesis_hash: H256::repeat_byte(3),
+			runtime_version: RuntimeVersion::default(),
+			metadata: metadata.encode(),
+		};
+		SnapshotClient::new(StateSnapshot { header, storage: BTreeMap::new() })
+	}
+
+	#[test]
+	fn update_runtime_recreates_extrinsic_params_builder() {
+		let known = |identifier: &str| identifier != "PrevalidateAttests";
+		let old_metadata = metadata_with_signed_extensions(known);
+		let new_metadata = metadata_with_signed_extensions(|identifier| {
+			known(identifier) && identifier != "CheckNonce"
+		});
+		let mut api = Api::<(), _, Params, Runtime>::new_offline(
+			H256::repeat_byte(3),
+			Metadata::try_from(old_metadata).unwrap(),
+			RuntimeVersion::default(),
+			client_serving(&new_metadata),
+		);
+		// Immortal era, nonce and tip.
+		assert_eq!(api.extrinsic_params(5).signed_extra().0, vec![0, 5 << 2, 0]);
+
+		api.update_runtime().unwrap();
+
+		assert_eq!(api.extrinsic_params(5).signed_extra().0, vec![0, 0]);
+	}
+
+	#[test]
+	fn builder_set_by_user_keeps_the_signed_extensions_of_the_runtime() {
+		let metadata =
+			metadata_with_signed_extensions(|identifier| identifier != "PrevalidateAttests");
+		let mut api = Api::<(), _, Params, Runtime>::new_offline(
+			H256::repeat_byte(3),
+			Metadata::try_from(metadata.clone()).unwrap(),
+			RuntimeVersion::default(),
+			client_serving(&metadata),
+		);
+
+		api.set_extrinsic_params_builder(
+			DynamicExtrinsicParamsBuilder::default().tip(PlainTip::new(10)),
+		);
+
+		// Immortal era, nonce and tip.
+		assert_eq!(api.extrinsic_params(5).signed_extra().0, vec![0, 5 << 2, 10 << 2]);
+	}
+}
@@ -20,6 +20,8 @@ use ac_node_api::{
 	metadata::{InvalidMetadataError, MetadataError},
 	DispatchError,
 };
+use ac_primitives::UnknownSignedExtension;
+use std::time::Duration;
 
 pub type ApiResult<T> = Result<T, Error>;
 
@@ -31,6 +33,10 @@ pub enum Error {
 	RuntimeVersion,
 	#[error("Fetching Metadata failed. Are you connected to the correct endpoint?")]
 	MetadataFetch,
+	#[error("Could not find the header of the requested block")]
+	BlockHeaderNotFound,
+	#[error("Could not find the hash of the requested block")]
+	BlockHashNotFound,
 	#[error("Operation needs a signer to be set in the api")]
 	NoSigner,
 	#[error("RpcClient error: {0:?}")]
@@ -41,6 +47,8 @@ pub enum Error {
 	Metadata(MetadataError),
 	#[error("InvalidMetadata: {0:?}")]
 	InvalidMetadata(InvalidMetadataError),
+	#[error("{0}")]
+	UnknownSignedExtension(#[from] UnknownSignedExtension),
 	#[error("Events Error: {0:?}")]
 	NodeApi(ac_node_api::error::Error),
 	#[error("Error decoding storage value: {0}")]
@@ -59,6 +67,16 @@ pub enum Error {
 	Extrinsic(String),
 	#[error("Stream ended unexpectedly")]
 	NoStream,
+	#[error("Extrinsic has been replaced in the pool by extrinsic {0} with the same nonce")]
+	TransactionUsurped(String),
+	#[error("Extrinsic has been dropped from the pool because of its limit")]
+	TransactionDropped,
+	#[error("Extrinsic is no longer valid in the current state")]
+	TransactionInvalid,
+	#[error("The node stopped watching the extrinsic in block {0} before it was finalized")]
+	FinalityTimeout(String),
+	#[error("Extrinsic did not reach the desired status within {0:?}")]
+	TransactionTimeout(Duration),
 	#[error(transparent)]
 	Other(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
 }
@@ -22,6 +22,8 @@ pub use crate::{
 pub use ac_primitives::FeeDetails;
 pub use api_client::Api;
 pub use frame_metadata::RuntimeMetadataPrefixed;
+pub use nonce_manager::NonceManager;
+pub use resubmission::*;
 pub use rpc_api::*;
 pub use serde_json::Value;
 pub use sp_core::{crypto::Pair, storage::StorageKey};
@@ -37,6 +39,8 @@ use serde::{Deserialize, Serialize};
 
 pub mod api_client;
 pub mod error;
+pub mod nonce_manager;
+pub mod resubmission;
 pub mod rpc_api;
 
 /// Simplified TransactionStatus to allow the user to choose until when to watch
@@ -46,10 +50,26 @@ pub mod rpc_api;
 pub enum XtStatus {
 	Ready = 1,
 	Broadcast = 2,
-	InBlock = 4,
+	InBlock = 3,
 	Finalized = 6,
 }
 
+/// Mortality of the extrinsics composed by the api, see `Api::set_mortality`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Mortality {
+	/// Use the era of the extrinsic params builder, which is immortal by default.
+	Immortal,
+	/// Valid for `period` blocks, starting at the latest finalized block at the time of
+	/// composition. The period is rounded to a power of two between 4 and 65536.
+	Mortal { period: u64 },
+}
+
+impl Default for Mortality {
+	fn default() -> Self {
+		Self::Immortal
+	}
+}
+
 /// Possible transaction status events.
 // Copied from `sc-transaction-pool`
 // (https://github.com/paritytech/substrate/blob/dddfed3d9260cf03244f15ba3db4edf9af7467e9/client/transaction-pool/api/src/lib.rs)
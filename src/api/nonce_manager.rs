@@ -0,0 +1,302 @@
+/*
+   Copyright 2019 Supercomputing Systems AG
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+	   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+*/
+
+//! Local nonce bookkeeping, to submit several extrinsics of the same signer without
+//! waiting for the previous ones to be included in a block.
+
+use crate::TransactionStatus;
+use log::*;
+use sp_core::hashing::blake2_256;
+use sp_runtime::traits::AtLeast32Bit;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fmt::Debug,
+	sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// Thread-safe nonce counter of a single signer.
+///
+/// It is seeded with the next index known to the node (`system_accountNextIndex`, which
+/// includes the transactions in the pool) and hands out increasing nonces from there on.
+/// Nonces that are handed out but not yet included in a block are tracked as in-flight.
+/// If an extrinsic does not make it into a block, its nonce must be given back with
+/// [`NonceManager::rollback`] or [`NonceManager::handle_status`], otherwise the
+/// following extrinsics get stuck in the future queue of the pool.
+///
+/// Extrinsics composed by the api are tracked with [`NonceManager::track_extrinsic`], so the
+/// submit methods of the api can do this on their own: They roll back the nonce if the
+/// submission fails and pass every watched status to [`NonceManager::handle_extrinsic_status`].
+/// The nonce is reserved when the extrinsic is composed, so a composed extrinsic that is not
+/// submitted needs to be given back with [`NonceManager::rollback_extrinsic`].
+///
+/// Extrinsics submitted without watching never report back. Their nonces are forgotten the
+/// next time the counter is seeded by the node, e.g. after a [`NonceManager::resync`].
+#[derive(Debug)]
+pub struct NonceManager<Index> {
+	state: Mutex<NonceState<Index>>,
+}
+
+impl<Index> Default for NonceManager<Index> {
+	fn default() -> Self {
+		Self { state: Mutex::new(NonceState::default()) }
+	}
+}
+
+#[derive(Debug)]
+struct NonceState<Index> {
+	/// Next nonce to hand out. `None` if it needs to be fetched from the node.
+	next: Option<Index>,
+	in_flight: BTreeSet<Index>,
+	/// Nonces of tracked extrinsics, by the hash of the encoded extrinsic.
+	extrinsics: BTreeMap<[u8; 32], Index>,
+}
+
+impl<Index> Default for NonceState<Index> {
+	fn default() -> Self {
+		Self { next: None, in_flight: BTreeSet::new(), extrinsics: BTreeMap::new() }
+	}
+}
+
+impl<Index: AtLeast32Bit + Copy + Debug> NonceManager<Index> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Hand out the next nonce. `fetch_next_index` is called to seed the counter
+	/// with the index known to the node if there is no local state yet. Nonces below that
+	/// index have been used already, so they are no longer in-flight or tracked.
+	pub fn next_nonce<E>(
+		&self,
+		fetch_next_index: impl FnOnce() -> Result<Index, E>,
+	) -> Result<Index, E> {
+		let mut state = self.state();
+		let nonce = match state.next {
+			Some(next) => next,
+			None => {
+				let next = fetch_next_index()?;
+				debug!("Seeded nonce manager with next index {:?}", next);
+				state.in_flight.retain(|in_flight| *in_flight >= next);
+				state.extrinsics.retain(|_, tracked_nonce| *tracked_nonce >= next);
+				next
+			},
+		};
+		state.next = Some(nonce + 1u32.into());
+		state.in_flight.insert(nonce);
+		Ok(nonce)
+	}
+
+	/// The extrinsic with `nonce` has been included in a block.
+	pub fn confirm(&self, nonce: Index) {
+		let mut state = self.state();
+		state.in_flight.remove(&nonce);
+		state.extrinsics.retain(|_, tracked_nonce| *tracked_nonce != nonce);
+	}
+
+	/// The extrinsic with `nonce` will not be included in a block and its nonce is still unused,
+	/// because it was never submitted or has been dropped from the pool. If it was the last
+	/// nonce handed out, it is handed out again next. Otherwise there is a gap now and
+	/// the counter is resynced with the node.
+	pub fn rollback(&self, nonce: Index) {
+		let mut state = self.state();
+		state.in_flight.remove(&nonce);
+		state.extrinsics.retain(|_, tracked_nonce| *tracked_nonce != nonce);
+		if state.next == Some(nonce + 1u32.into()) {
+			state.next = Some(nonce);
+		} else {
+			debug!("Rollback of nonce {:?} leaves a gap, resyncing with the node", nonce);
+			state.next = None;
+		}
+	}
+
+	/// Forget the local counter, the next nonce is fetched from the node again. In-flight
+	/// nonces below the fetched one are forgotten then.
+	pub fn resync(&self) {
+		self.state().next = None;
+	}
+
+	/// Update the bookkeeping with a status of the extrinsic that has been signed with `nonce`:
+	/// Included extrinsics are confirmed and `Dropped` ones rolled back. A `Usurped` extrinsic
+	/// has been replaced by another one with the same nonce, which may not have been handed out
+	/// by this manager. An `Invalid` extrinsic may have become invalid because its nonce has been
+	/// used by another extrinsic. In both cases, the counter is resynced.
+	pub fn handle_status<Hash, BlockHash>(
+		&self,
+		nonce: Index,
+		status: &TransactionStatus<Hash, BlockHash>,
+	) {
+		match status {
+			TransactionStatus::InBlock(_) | TransactionStatus::Finalized(_) => self.confirm(nonce),
+			TransactionStatus::Dropped => self.rollback(nonce),
+			TransactionStatus::Invalid | TransactionStatus::Usurped(_) => {
+				self.confirm(nonce);
+				self.resync();
+			},
+			_ => {},
+		}
+	}
+
+	/// Remember that the encoded `extrinsic` has been signed with `nonce`, which has been handed
+	/// out by this manager. The extrinsic is forgotten once its nonce is confirmed or rolled back.
+	pub fn track_extrinsic(&self, nonce: Index, extrinsic: &[u8]) {
+		self.state().extrinsics.insert(blake2_256(extrinsic), nonce);
+	}
+
+	/// Like [`NonceManager::handle_status`], for the nonce of a tracked extrinsic.
+	/// Statuses of extrinsics that are not tracked are ignored.
+	pub fn handle_extrinsic_status<Hash, BlockHash>(
+		&self,
+		extrinsic: &[u8],
+		status: &TransactionStatus<Hash, BlockHash>,
+	) {
+		if let Some(nonce) = self.tracked_nonce(extrinsic) {
+			self.handle_status(nonce, status);
+		}
+	}
+
+	/// The submission of the encoded `extrinsic` failed. Rolls back its nonce if it is tracked.
+	pub fn rollback_extrinsic(&self, extrinsic: &[u8]) {
+		if let Some(nonce) = self.tracked_nonce(extrinsic) {
+			debug!("Submission of extrinsic with nonce {:?} failed", nonce);
+			self.rollback(nonce);
+		}
+	}
+
+	/// Nonces that have been handed out, but are not yet known to be included in a block.
+	pub fn in_flight(&self) -> BTreeSet<Index> {
+		self.state().in_flight.clone()
+	}
+
+	fn tracked_nonce(&self, extrinsic: &[u8]) -> Option<Index> {
+		self.state().extrinsics.get(&blake2_256(extrinsic)).copied()
+	}
+
+	fn state(&self) -> MutexGuard<'_, NonceState<Index>> {
+		// The state is consistent after every operation, so a poisoned lock can be reused.
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::H256;
+
+	fn seed(index: u32) -> impl FnOnce() -> Result<u32, ()> {
+		move || Ok(index)
+	}
+
+	#[test]
+	fn hands_out_increasing_nonces_after_seeding() {
+		let manager = NonceManager::new();
+
+		assert_eq!(manager.next_nonce(seed(5)), Ok(5));
+		assert_eq!(manager.next_nonce(seed(0)), Ok(6));
+		assert_eq!(manager.next_nonce(seed(0)), Ok(7));
+		assert_eq!(manager.in_flight(), BTreeSet::from([5, 6, 7]));
+
+		manager.confirm(5);
+		assert_eq!(manager.in_flight(), BTreeSet::from([6, 7]));
+	}
+
+	#[test]
+	fn rollback_of_last_nonce_hands_it_out_again() {
+		let manager = NonceManager::new();
+		manager.next_nonce(seed(5)).unwrap();
+		manager.next_nonce(seed(0)).unwrap();
+
+		manager.rollback(6);
+
+		assert_eq!(manager.next_nonce(seed(0)), Ok(6));
+	}
+
+	#[test]
+	fn rollback_with_gap_resyncs() {
+		let manager = NonceManager::new();
+		manager.next_nonce(seed(5)).unwrap();
+		manager.next_nonce(seed(0)).unwrap();
+
+		manager.handle_status(5, &TransactionStatus::<H256, H256>::Dropped);
+
+		assert_eq!(manager.in_flight(), BTreeSet::from([6]));
+		assert_eq!(manager.next_nonce(seed(5)), Ok(5));
+	}
+
+	#[test]
+	fn usurped_and_invalid_resync() {
+		for status in
+			[TransactionStatus::<H256, H256>::Usurped(H256::zero()), TransactionStatus::Invalid]
+		{
+			let manager = NonceManager::new();
+			manager.next_nonce(seed(5)).unwrap();
+
+			// Unlike a rollback, this does not hand out the nonce 5 again.
+			manager.handle_status(5, &status);
+
+			assert!(manager.in_flight().is_empty());
+			assert_eq!(manager.next_nonce(seed(9)), Ok(9));
+		}
+	}
+
+	#[test]
+	fn statuses_of_tracked_extrinsics_are_handled() {
+		let manager = NonceManager::new();
+		manager.next_nonce(seed(5)).unwrap();
+		manager.next_nonce(seed(0)).unwrap();
+		manager.track_extrinsic(5, &[5]);
+		manager.track_extrinsic(6, &[6]);
+
+		manager
+			.handle_extrinsic_status(&[5], &TransactionStatus::<H256, H256>::InBlock(H256::zero()));
+		manager.handle_extrinsic_status(&[7], &TransactionStatus::<H256, H256>::Dropped);
+		assert_eq!(manager.in_flight(), BTreeSet::from([6]));
+
+		manager.rollback_extrinsic(&[6]);
+		assert!(manager.in_flight().is_empty());
+		assert_eq!(manager.next_nonce(seed(0)), Ok(6));
+
+		// Forgotten after the rollback, so the status does not give back the new nonce 6.
+		manager.handle_extrinsic_status(&[6], &TransactionStatus::<H256, H256>::Dropped);
+		assert_eq!(manager.in_flight(), BTreeSet::from([6]));
+	}
+
+	#[test]
+	fn seeding_forgets_nonces_used_in_the_meantime() {
+		let manager = NonceManager::new();
+		for nonce in 5..7 {
+			manager.next_nonce(seed(5)).unwrap();
+			manager.track_extrinsic(nonce, &[nonce as u8]);
+		}
+
+		// Submitted without watching, so the extrinsics never report back.
+		manager.resync();
+		assert_eq!(manager.next_nonce(seed(7)), Ok(7));
+		assert_eq!(manager.in_flight(), BTreeSet::from([7]));
+
+		// Forgotten, so this does not lead to a resync.
+		manager.rollback_extrinsic(&[6]);
+		assert_eq!(manager.next_nonce(seed(0)), Ok(8));
+	}
+
+	#[test]
+	fn failed_seeding_does_not_change_state() {
+		let manager = NonceManager::<u32>::new();
+
+		assert_eq!(manager.next_nonce(|| Err("node not reachable")), Err("node not reachable"));
+		assert!(manager.in_flight().is_empty());
+		assert_eq!(manager.next_nonce(seed(2)), Ok(2));
+	}
+}
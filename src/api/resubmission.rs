@@ -0,0 +1,353 @@
+/*
+   Copyright 2019 Supercomputing Systems AG
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+	   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+*/
+
+//! Resubmission of extrinsics that did not make it into a block. The call is re-signed with
+//! a fresh nonce, mortality checkpoint and, optionally, a higher tip for every attempt.
+
+use crate::{
+	api::{
+		error::Error,
+		rpc_api::{SubmitAndWatch, TransactionSubscriptionFor},
+		Api, ApiResult,
+	},
+	rpc::{Error as RpcClientError, Request, Subscribe},
+	XtStatus,
+};
+use ac_compose_macros::compose_extrinsic_offline;
+use ac_primitives::{ExtrinsicParams, FrameSystemConfig, SignExtrinsic, WithEra, WithTip};
+use codec::Encode;
+use core::fmt::Debug;
+use log::*;
+use serde::de::DeserializeOwned;
+use sp_runtime::{generic::Era, traits::Hash as HashT, transaction_validity::InvalidTransaction};
+
+/// Defines how often and with which parameters an extrinsic is resubmitted.
+#[derive(Debug, Clone, Copy)]
+pub struct ResubmissionPolicy<Tip> {
+	max_attempts: u32,
+	era_period: u64,
+	watch_until: XtStatus,
+	tip: fn(u32) -> Tip,
+}
+
+impl<Tip: Default> ResubmissionPolicy<Tip> {
+	/// Submit at most `max_attempts` times. Every attempt is valid for `era_period` blocks
+	/// after the latest finalized block at the time of signing. By default, an attempt is
+	/// successful once the extrinsic is in a block and no tip is given.
+	pub fn new(max_attempts: u32, era_period: u64) -> Self {
+		Self { max_attempts, era_period, watch_until: XtStatus::InBlock, tip: |_| Tip::default() }
+	}
+
+	/// Set the status an attempt needs to reach to be successful.
+	pub fn watch_until(mut self, watch_until: XtStatus) -> Self {
+		self.watch_until = watch_until;
+		self
+	}
+
+	/// Set the tip of an attempt, given its index starting at 0.
+	/// E.g. `|attempt| PlainTip::new(1_000 * attempt as u128)` to bump the tip on every attempt.
+	pub fn tip(mut self, tip: fn(u32) -> Tip) -> Self {
+		self.tip = tip;
+		self
+	}
+}
+
+/// Outcome of a single submission attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttemptOutcome<Hash> {
+	/// The desired status has been reached. Contains the hash of the block the
+	/// extrinsic is included in, if it is included already.
+	Done(Option<Hash>),
+	/// Dropped from the pool because of its limit.
+	Dropped,
+	/// Rejected by the pool, because its nonce is outdated or its era has passed already.
+	Outdated,
+	/// The desired status has not been reached within the mortality period.
+	Expired,
+	/// The desired status has not been reached within the mortality period, but the nonce has
+	/// been used on chain in the meantime. The extrinsic is most likely included, so it is
+	/// not resubmitted to avoid dispatching the call twice.
+	NonceUsed,
+}
+
+impl<Hash> AttemptOutcome<Hash> {
+	/// Whether another attempt should be made after this outcome.
+	pub fn is_retryable(&self) -> bool {
+		!matches!(self, Self::Done(_) | Self::NonceUsed)
+	}
+
+	/// The outcome of an attempt that failed with `error`, or `None` if another attempt would
+	/// fail the same way and the error must be returned to the caller. This includes extrinsics
+	/// that became invalid for a reason not known to the api, e.g. a lack of funds.
+	fn from_error(error: &Error) -> Option<Self> {
+		match error {
+			Error::TransactionDropped => Some(Self::Dropped),
+			Error::TransactionTimeout(_) => Some(Self::Expired),
+			Error::RpcClient(error) if is_outdated_rejection(error) => Some(Self::Outdated),
+			_ => None,
+		}
+	}
+}
+
+/// A single submission attempt and its outcome.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmissionAttempt<Hash, Index, Tip> {
+	pub extrinsic_hash: Hash,
+	pub nonce: Index,
+	pub era: Era,
+	pub mortality_checkpoint: Hash,
+	pub tip: Tip,
+	pub outcome: AttemptOutcome<Hash>,
+}
+
+/// All attempts made to submit an extrinsic, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResubmissionReport<Hash, Index, Tip> {
+	pub attempts: Vec<SubmissionAttempt<Hash, Index, Tip>>,
+}
+
+impl<Hash, Index, Tip> ResubmissionReport<Hash, Index, Tip> {
+	/// Whether the last attempt reached the desired status.
+	pub fn is_success(&self) -> bool {
+		matches!(self.last_outcome(), Some(AttemptOutcome::Done(_)))
+	}
+
+	/// The block the extrinsic of the last attempt is included in, if known.
+	pub fn block_hash(&self) -> Option<&Hash> {
+		match self.last_outcome() {
+			Some(AttemptOutcome::Done(block_hash)) => block_hash.as_ref(),
+			_ => None,
+		}
+	}
+
+	fn last_outcome(&self) -> Option<&AttemptOutcome<Hash>> {
+		self.attempts.last().map(|attempt| &attempt.outcome)
+	}
+}
+
+pub trait SubmitAndWatchWithResubmission<Client, Hash>
+where
+	Client: Subscribe,
+	Hash: DeserializeOwned,
+{
+	type Index;
+	type Tip;
+
+	/// Sign `call` with the api signer, submit it and watch it until the status of the
+	/// `policy` is reached. If the extrinsic is dropped, becomes invalid, is rejected as
+	/// outdated or is not included within its mortality period, the call is signed again
+	/// with a fresh nonce, era and tip and resubmitted, until the attempts of the `policy`
+	/// are used up. This method is blocking.
+	///
+	/// Attempts that fail for any other reason, e.g. a lost connection, return the error.
+	/// Otherwise all attempts are returned, check `ResubmissionReport::is_success` for the
+	/// outcome of the last one.
+	fn submit_and_watch_call_with_resubmission<Call: Encode + Clone>(
+		&self,
+		call: Call,
+		policy: &ResubmissionPolicy<Self::Tip>,
+	) -> ApiResult<ResubmissionReport<Hash, Self::Index, Self::Tip>>;
+}
+
+impl<Signer, Client, Params, Runtime> SubmitAndWatchWithResubmission<Client, Runtime::Hash>
+	for Api<Signer, Client, Params, Runtime>
+where
+	Signer: SignExtrinsic<Runtime::AccountId>,
+	Client: Subscribe + Request,
+	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+	Params::OtherParams: WithEra<Runtime::Hash> + WithTip,
+	<Params::OtherParams as WithTip>::Tip: Copy + Debug,
+	Runtime: FrameSystemConfig,
+	Runtime::Hash: Send + 'static,
+	TransactionSubscriptionFor<Client, Runtime::Hash>: Send + 'static,
+{
+	type Index = Runtime::Index;
+	type Tip = <Params::OtherParams as WithTip>::Tip;
+
+	fn submit_and_watch_call_with_resubmission<Call: Encode + Clone>(
+		&self,
+		call: Call,
+		policy: &ResubmissionPolicy<Self::Tip>,
+	) -> ApiResult<ResubmissionReport<Runtime::Hash, Runtime::Index, Self::Tip>> {
+		let signer = self.signer().ok_or(Error::NoSigner)?;
+		let mut report = ResubmissionReport { attempts: Vec::new() };
+
+		for attempt in 0..policy.max_attempts {
+			let (era, mortality_checkpoint) = self.mortal_era(policy.era_period)?;
+			let tip = (policy.tip)(attempt);
+			let nonce = self.next_nonce()?;
+
+			// Keep everything else of the configured builder, e.g. the signed extensions of
+			// dynamic extrinsic params.
+			let params_builder = self
+				.extrinsic_params_builder()
+				.with_era(era, mortality_checkpoint)
+				.with_tip(tip);
+			let params = self.build_extrinsic_params(nonce, params_builder);
+			let xt = compose_extrinsic_offline!(*signer, call.clone(), params);
+			let extrinsic_hash = Runtime::Hashing::hash_of(&xt);
+			info!(
+				"Submission attempt {} of extrinsic {:?} with nonce {:?} and tip {:?}",
+				attempt + 1,
+				extrinsic_hash,
+				nonce,
+				tip
+			);
+
+			let result = match self.mortality_timeout(&era) {
+				Some(timeout) => self.submit_and_watch_extrinsic_until_with_timeout(
+					&xt.hex_encode(),
+					policy.watch_until,
+					timeout,
+				),
+				None => self.submit_and_watch_extrinsic_until(&xt.hex_encode(), policy.watch_until),
+			};
+			let outcome = match result {
+				Ok(block_hash) => {
+					self.nonce_manager().confirm(nonce);
+					AttemptOutcome::Done(block_hash)
+				},
+				Err(error) => match AttemptOutcome::from_error(&error) {
+					Some(AttemptOutcome::Expired) if self.get_nonce()? > nonce => {
+						self.nonce_manager().confirm(nonce);
+						AttemptOutcome::NonceUsed
+					},
+					Some(outcome @ (AttemptOutcome::Dropped | AttemptOutcome::Expired)) => {
+						self.nonce_manager().rollback(nonce);
+						outcome
+					},
+					Some(outcome) => {
+						// Outdated, e.g. because the nonce has been used by another extrinsic:
+						// The nonce must not be handed out again, so fetch the next one from
+						// the node.
+						self.nonce_manager().confirm(nonce);
+						self.nonce_manager().resync();
+						outcome
+					},
+					None => {
+						// It is unknown whether the nonce has been used, so fetch it again next time.
+						self.nonce_manager().confirm(nonce);
+						self.nonce_manager().resync();
+						return Err(error)
+					},
+				},
+			};
+			debug!("Submission attempt {} ended with {:?}", attempt + 1, outcome);
+
+			let is_retryable = outcome.is_retryable();
+			report.attempts.push(SubmissionAttempt {
+				extrinsic_hash,
+				nonce,
+				era,
+				mortality_checkpoint,
+				tip,
+				outcome,
+			});
+			if !is_retryable {
+				break
+			}
+		}
+		Ok(report)
+	}
+}
+
+/// Whether the pool rejected the extrinsic with `InvalidTransaction::Stale` or
+/// `InvalidTransaction::AncientBirthBlock`. The rpc error only carries the description of
+/// the invalidity, so this is matched on the description the node derives from the variant.
+fn is_outdated_rejection(error: &RpcClientError) -> bool {
+	let message = error.to_string();
+	[InvalidTransaction::Stale, InvalidTransaction::AncientBirthBlock]
+		.into_iter()
+		.any(|invalid| message.contains(<&'static str>::from(invalid)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ac_primitives::PlainTip;
+	use sp_core::H256;
+	use std::time::Duration;
+
+	/// The error of a rejected submission, as rendered by the jsonrpsee client.
+	fn rejection(description: &str) -> Error {
+		Error::RpcClient(RpcClientError::Client(
+			format!(
+				"RPC call failed: ErrorObject {{ code: ServerError(1010), message: \"Invalid \
+				 Transaction\", data: Some(RawValue(\"{}\")) }}",
+				description
+			)
+			.into(),
+		))
+	}
+
+	#[test]
+	fn pool_errors_are_retryable() {
+		for (error, outcome) in [
+			(Error::TransactionDropped, AttemptOutcome::<H256>::Dropped),
+			(Error::TransactionTimeout(Duration::from_secs(6)), AttemptOutcome::Expired),
+			(rejection("Transaction is outdated"), AttemptOutcome::Outdated),
+			(rejection("Transaction has an ancient birth block"), AttemptOutcome::Outdated),
+		] {
+			let from_error = AttemptOutcome::from_error(&error).unwrap();
+			assert!(from_error.is_retryable());
+			assert_eq!(from_error, outcome);
+		}
+	}
+
+	#[test]
+	fn other_errors_are_returned() {
+		for error in [
+			Error::NoStream,
+			Error::TransactionInvalid,
+			Error::TransactionUsurped("0x01".into()),
+			rejection("Inability to pay some fees (e.g. account balance too low)"),
+			rejection("Transaction has a bad signature"),
+		] {
+			assert_eq!(AttemptOutcome::<H256>::from_error(&error), None);
+		}
+	}
+
+	#[test]
+	fn report_reflects_last_attempt() {
+		let block_hash = H256::repeat_byte(1);
+		let attempt = |outcome| SubmissionAttempt {
+			extrinsic_hash: H256::zero(),
+			nonce: 0u32,
+			era: Era::Immortal,
+			mortality_checkpoint: H256::zero(),
+			tip: PlainTip::new(0u128),
+			outcome,
+		};
+
+		let mut report = ResubmissionReport { attempts: vec![attempt(AttemptOutcome::Dropped)] };
+		assert!(!report.is_success());
+		assert_eq!(report.block_hash(), None);
+
+		report.attempts.push(attempt(AttemptOutcome::Done(Some(block_hash))));
+		assert!(report.is_success());
+		assert_eq!(report.block_hash(), Some(&block_hash));
+	}
+
+	#[test]
+	fn policy_bumps_tip_per_attempt() {
+		let policy = ResubmissionPolicy::<PlainTip<u128>>::new(3, 64)
+			.tip(|attempt| PlainTip::new(1_000 * attempt as u128));
+
+		assert_eq!((policy.tip)(0), PlainTip::new(0));
+		assert_eq!((policy.tip)(2), PlainTip::new(2_000));
+	}
+}
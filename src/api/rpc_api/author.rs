@@ -14,14 +14,29 @@
 //! Interface to common frame system pallet information.
 
 use crate::{
-	api::{error::Error, ApiResult},
+	api::{error::Error, ApiResult, GetStorage},
 	rpc::HandleSubscription,
 	Api, Request, Subscribe, TransactionStatus, XtStatus,
 };
 use ac_compose_macros::rpc_params;
-use ac_primitives::{ExtrinsicParams, FrameSystemConfig};
+use ac_node_api::{events::EventDetails, DispatchError, Events, Phase, StaticEvent};
+use ac_primitives::{
+	BalancesConfig, ExtrinsicParams, FrameSystemConfig, SignedExtraNonce, UncheckedExtrinsicV4,
+};
+use codec::{Compact, Decode, Encode, Input};
+use core::fmt::Debug;
 use log::*;
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_core::Bytes;
+use sp_runtime::{
+	generic::SignedBlock,
+	traits::{Block, GetRuntimeBlockType, Hash as HashT},
+};
+use std::{
+	sync::mpsc::{channel, RecvTimeoutError},
+	thread,
+	time::{Duration, Instant},
+};
 
 pub type TransactionSubscriptionFor<Client, Hash> =
 	<Client as Subscribe>::Subscription<TransactionStatus<Hash, Hash>>;
@@ -45,8 +60,14 @@ where
 
 	fn submit_extrinsic(&self, xthex_prefixed: String) -> ApiResult<Self::Hash> {
 		debug!("sending extrinsic: {:?}", xthex_prefixed);
-		let xt_hash =
-			self.client().request("author_submitExtrinsic", rpc_params![xthex_prefixed])?;
+		let extrinsic = hex::decode(xthex_prefixed.trim_start_matches("0x"))?;
+		let xt_hash = self
+			.client()
+			.request("author_submitExtrinsic", rpc_params![xthex_prefixed])
+			.map_err(|e| {
+				self.nonce_manager().rollback_extrinsic(&extrinsic);
+				e
+			})?;
 		Ok(xt_hash)
 	}
 }
@@ -57,7 +78,8 @@ where
 	Hash: DeserializeOwned,
 {
 	/// Submit an extrinsic an return a websocket Subscription to watch the
-	/// extrinsic progress.
+	/// extrinsic progress. If the extrinsic has been composed by the api, pass the received
+	/// statuses to `Api::nonce_manager().handle_extrinsic_status`, to keep the nonces in sync.
 	fn submit_and_watch_extrinsic(
 		&self,
 		xthex_prefixed: &str,
@@ -70,6 +92,22 @@ where
 		xthex_prefixed: &str,
 		watch_until: XtStatus,
 	) -> ApiResult<Option<Hash>>;
+
+	/// Same as `submit_and_watch_extrinsic_until`, but fails with `Error::TransactionTimeout`
+	/// if the desired status is not reached within `timeout`, e.g. the mortality window of
+	/// the extrinsic as returned by `Api::mortality_timeout`.
+	///
+	/// The subscription is polled in a separate thread, which ends with the next status
+	/// update after a timeout.
+	fn submit_and_watch_extrinsic_until_with_timeout(
+		&self,
+		xthex_prefixed: &str,
+		watch_until: XtStatus,
+		timeout: Duration,
+	) -> ApiResult<Option<Hash>>
+	where
+		TransactionSubscriptionFor<Client, Hash>: Send + 'static,
+		Hash: Send + 'static;
 }
 
 impl<Signer, Client, Params, Runtime> SubmitAndWatch<Client, Runtime::Hash>
@@ -83,13 +121,17 @@ where
 		&self,
 		xthex_prefixed: &str,
 	) -> ApiResult<TransactionSubscriptionFor<Client, Runtime::Hash>> {
+		let extrinsic = hex::decode(xthex_prefixed.trim_start_matches("0x"))?;
 		self.client()
 			.subscribe(
 				"author_submitAndWatchExtrinsic",
 				rpc_params![xthex_prefixed],
 				"author_unsubmitAndWatchExtrinsic",
 			)
-			.map_err(|e| e.into())
+			.map_err(|e| {
+				self.nonce_manager().rollback_extrinsic(&extrinsic);
+				e.into()
+			})
 	}
 
 	fn submit_and_watch_extrinsic_until(
@@ -97,36 +139,604 @@ where
 		xthex_prefixed: &str,
 		watch_until: XtStatus,
 	) -> ApiResult<Option<Runtime::Hash>> {
+		let extrinsic = hex::decode(xthex_prefixed.trim_start_matches("0x"))?;
 		let mut subscription: TransactionSubscriptionFor<Client, Runtime::Hash> =
 			self.submit_and_watch_extrinsic(xthex_prefixed)?;
+		let mut watcher = TransactionWatcher::new(watch_until);
 		while let Some(transaction_status) = subscription.next() {
 			let transaction_status = transaction_status?;
-			if transaction_status.is_supported() {
-				if transaction_status.as_u8() >= watch_until as u8 {
+			self.nonce_manager().handle_extrinsic_status(&extrinsic, &transaction_status);
+			match watcher.update(transaction_status) {
+				Ok(WatchProgress::Pending) => continue,
+				Ok(WatchProgress::Done(block_hash)) => {
 					subscription.unsubscribe()?;
-					return Ok(return_block_hash_if_available(transaction_status))
-				}
-			} else {
-				subscription.unsubscribe()?;
-				let error = Error::Extrinsic(format!(
-					"Unsupported transaction status: {:?}, stopping watch process.",
-					transaction_status
-				));
-				return Err(error)
+					return Ok(block_hash)
+				},
+				Err(error) => {
+					subscription.unsubscribe()?;
+					return Err(error)
+				},
 			}
 		}
 		Err(Error::NoStream)
 	}
+
+	fn submit_and_watch_extrinsic_until_with_timeout(
+		&self,
+		xthex_prefixed: &str,
+		watch_until: XtStatus,
+		timeout: Duration,
+	) -> ApiResult<Option<Runtime::Hash>>
+	where
+		TransactionSubscriptionFor<Client, Runtime::Hash>: Send + 'static,
+		Runtime::Hash: Send + 'static,
+	{
+		let deadline = Instant::now() + timeout;
+		let extrinsic = hex::decode(xthex_prefixed.trim_start_matches("0x"))?;
+		let mut subscription: TransactionSubscriptionFor<Client, Runtime::Hash> =
+			self.submit_and_watch_extrinsic(xthex_prefixed)?;
+
+		// The subscription blocks until the next status update, so poll it in a thread
+		// that stops as soon as the receiving side is gone.
+		let (status_sender, status_receiver) = channel();
+		thread::spawn(move || {
+			while let Some(transaction_status) = subscription.next() {
+				if status_sender.send(transaction_status).is_err() {
+					break
+				}
+			}
+			if let Err(e) = subscription.unsubscribe() {
+				debug!("Could not unsubscribe from transaction status: {:?}", e);
+			}
+		});
+
+		let mut watcher = TransactionWatcher::new(watch_until);
+		loop {
+			let remaining = deadline.saturating_duration_since(Instant::now());
+			match status_receiver.recv_timeout(remaining) {
+				Ok(transaction_status) => {
+					let transaction_status = transaction_status?;
+					self.nonce_manager().handle_extrinsic_status(&extrinsic, &transaction_status);
+					if let WatchProgress::Done(block_hash) = watcher.update(transaction_status)? {
+						return Ok(block_hash)
+					}
+				},
+				Err(RecvTimeoutError::Timeout) => return Err(Error::TransactionTimeout(timeout)),
+				Err(RecvTimeoutError::Disconnected) => return Err(Error::NoStream),
+			}
+		}
+	}
+}
+
+/// Outcome of an extrinsic that has been included in a block, together with
+/// all events emitted while applying it.
+#[derive(Debug)]
+pub struct ExtrinsicReport<Hash, Balance> {
+	pub extrinsic_hash: Hash,
+	pub block_hash: Hash,
+	/// Index of the extrinsic within its block.
+	pub extrinsic_index: u32,
+	/// Events with phase `ApplyExtrinsic(extrinsic_index)`.
+	pub events: Vec<EventDetails>,
+	/// `Ok` for `System.ExtrinsicSuccess`, the decoded error for `System.ExtrinsicFailed`.
+	pub dispatch_result: Result<(), DispatchError>,
+	/// Fee paid according to the `TransactionPayment.TransactionFeePaid` event.
+	/// `None` for extrinsics that don't pay fees, e.g. unsigned ones.
+	pub actual_fee: Option<Balance>,
+}
+
+impl<Hash, Balance> ExtrinsicReport<Hash, Balance> {
+	pub fn is_success(&self) -> bool {
+		self.dispatch_result.is_ok()
+	}
+}
+
+/// Submission of extrinsics, reporting the outcome and events of the submitted extrinsic
+/// only, instead of the first matching event of its block.
+pub trait SubmitAndWatchUntilSuccess<Client, Hash>
+where
+	Client: Subscribe,
+	Hash: DeserializeOwned,
+{
+	type Balance;
+
+	/// Submit an extrinsic and watch it until it is included in a block, or finalized if
+	/// `wait_for_finalized` is set. Returns the report of the extrinsic. A failed dispatch
+	/// is not an error of this function but returned in `dispatch_result` of the report.
+	/// This method is blocking.
+	fn submit_and_watch_extrinsic_until_success(
+		&self,
+		xthex_prefixed: &str,
+		wait_for_finalized: bool,
+	) -> ApiResult<ExtrinsicReport<Hash, Self::Balance>>;
+
+	/// Find the extrinsic with the given hash in the given block and collect its events.
+	fn get_extrinsic_report(
+		&self,
+		extrinsic_hash: Hash,
+		block_hash: Hash,
+	) -> ApiResult<ExtrinsicReport<Hash, Self::Balance>>;
 }
 
-fn return_block_hash_if_available<Hash, BlockHash>(
-	transcation_status: TransactionStatus<Hash, BlockHash>,
-) -> Option<BlockHash> {
-	match transcation_status {
-		TransactionStatus::InBlock(block_hash) => Some(block_hash),
-		TransactionStatus::Retracted(block_hash) => Some(block_hash),
-		TransactionStatus::FinalityTimeout(block_hash) => Some(block_hash),
-		TransactionStatus::Finalized(block_hash) => Some(block_hash),
-		_ => None,
+impl<Signer, Client, Params, Runtime> SubmitAndWatchUntilSuccess<Client, Runtime::Hash>
+	for Api<Signer, Client, Params, Runtime>
+where
+	Client: Subscribe + Request,
+	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+	Runtime: BalancesConfig + GetRuntimeBlockType,
+	Runtime::RuntimeBlock: DeserializeOwned,
+{
+	type Balance = Runtime::Balance;
+
+	fn submit_and_watch_extrinsic_until_success(
+		&self,
+		xthex_prefixed: &str,
+		wait_for_finalized: bool,
+	) -> ApiResult<ExtrinsicReport<Runtime::Hash, Self::Balance>> {
+		let extrinsic = hex::decode(xthex_prefixed.trim_start_matches("0x"))?;
+		let extrinsic_hash = Runtime::Hashing::hash(&extrinsic);
+
+		let watch_until = if wait_for_finalized { XtStatus::Finalized } else { XtStatus::InBlock };
+		let block_hash = self
+			.submit_and_watch_extrinsic_until(xthex_prefixed, watch_until)?
+			.ok_or_else(|| {
+				Error::Extrinsic(format!(
+					"No block hash received for extrinsic {:?}",
+					extrinsic_hash
+				))
+			})?;
+		self.get_extrinsic_report(extrinsic_hash, block_hash)
+	}
+
+	fn get_extrinsic_report(
+		&self,
+		extrinsic_hash: Runtime::Hash,
+		block_hash: Runtime::Hash,
+	) -> ApiResult<ExtrinsicReport<Runtime::Hash, Self::Balance>> {
+		let block: SignedBlock<Runtime::RuntimeBlock> = self
+			.client()
+			.request::<Option<_>>("chain_getBlock", rpc_params![Some(block_hash)])?
+			.ok_or_else(|| Error::Other(format!("Block {:?} not found", block_hash).into()))?;
+		let extrinsic_index = block
+			.block
+			.extrinsics()
+			.iter()
+			.position(|extrinsic| Runtime::Hashing::hash_of(extrinsic) == extrinsic_hash)
+			.ok_or_else(|| {
+				Error::Extrinsic(format!(
+					"Extrinsic {:?} not found in block {:?}",
+					extrinsic_hash, block_hash
+				))
+			})? as u32;
+		debug!("Found extrinsic {:?} at index {}", extrinsic_hash, extrinsic_index);
+
+		let events_key = self.metadata().storage_value_key("System", "Events")?;
+		let event_bytes = self
+			.get_opaque_storage_by_key_hash(events_key, Some(block_hash))?
+			.unwrap_or_default();
+		let events = Events::new(self.metadata().clone(), block_hash, event_bytes);
+
+		let mut extrinsic_events = Vec::new();
+		let mut dispatch_result = Ok(());
+		let mut actual_fee = None;
+		for event_details in events.iter() {
+			let event_details = event_details?;
+			if event_details.phase() != Phase::ApplyExtrinsic(extrinsic_index) {
+				continue
+			}
+			if event_details.pallet_name() == "System"
+				&& event_details.variant_name() == "ExtrinsicFailed"
+			{
+				dispatch_result =
+					Err(DispatchError::decode_from(event_details.field_bytes(), self.metadata()));
+			}
+			if let Some(fee_paid) = event_details
+				.as_event::<TransactionFeePaid<Runtime::AccountId, Runtime::Balance>>()?
+			{
+				actual_fee = Some(fee_paid.actual_fee);
+			}
+			extrinsic_events.push(event_details);
+		}
+
+		Ok(ExtrinsicReport {
+			extrinsic_hash,
+			block_hash,
+			extrinsic_index,
+			events: extrinsic_events,
+			dispatch_result,
+			actual_fee,
+		})
+	}
+}
+
+/// Emitted by the transaction payment pallet once the fee of a signed extrinsic is settled.
+#[derive(Decode)]
+struct TransactionFeePaid<AccountId, Balance> {
+	_who: AccountId,
+	actual_fee: Balance,
+	_tip: Balance,
+}
+
+impl<AccountId: Decode, Balance: Decode> StaticEvent for TransactionFeePaid<AccountId, Balance> {
+	const PALLET: &'static str = "TransactionPayment";
+	const EVENT: &'static str = "TransactionFeePaid";
+}
+
+/// Extrinsic to remove from the pool with `author_removeExtrinsic`,
+/// identified by its hash or its SCALE encoded bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExtrinsicOrHash<Hash> {
+	Hash(Hash),
+	Extrinsic(Bytes),
+}
+
+/// Inspection and cleanup of the transaction pool of the node.
+pub trait ManageTransactionPool<Hash> {
+	type SignedExtra;
+	type Index;
+	type AccountId;
+
+	/// All extrinsics in the pool, decoded with the given address, call and signature types,
+	/// e.g. `GenericAddress`, the `RuntimeCall` of the runtime and `MultiSignature`.
+	fn get_pending_extrinsics<Address, Call, Signature>(
+		&self,
+	) -> ApiResult<Vec<UncheckedExtrinsicV4<Call, Self::SignedExtra, Address, Signature>>>
+	where
+		Address: Decode + Encode,
+		Call: Decode + Encode,
+		Signature: Decode + Encode,
+		Self::SignedExtra: Decode + Encode;
+
+	/// Remove the given extrinsics from the pool. Returns the hashes of the removed
+	/// extrinsics, including the ones that depended on them.
+	/// This is an unsafe rpc method, only available on nodes run with `--rpc-methods=unsafe`.
+	fn remove_extrinsics(&self, extrinsics: Vec<ExtrinsicOrHash<Hash>>) -> ApiResult<Vec<Hash>>;
+
+	/// Remove the extrinsic of `account` with `nonce` from the pool, i.e. the extrinsic that
+	/// provides the `(account, nonce)` tag. The pending extrinsics are decoded with the given
+	/// address and signature types, e.g. `GenericAddress` and `MultiSignature`. Returns the
+	/// hashes of the removed extrinsics, which is empty if no such extrinsic is pending.
+	/// This is an unsafe rpc method, only available on nodes run with `--rpc-methods=unsafe`.
+	fn remove_extrinsic_by_tag<Address, Signature>(
+		&self,
+		account: &Self::AccountId,
+		nonce: Self::Index,
+	) -> ApiResult<Vec<Hash>>
+	where
+		Address: Decode + PartialEq + From<Self::AccountId>,
+		Signature: Decode,
+		Self::SignedExtra: Decode + SignedExtraNonce<Self::Index>;
+}
+
+impl<Signer, Client, Params, Runtime> ManageTransactionPool<Runtime::Hash>
+	for Api<Signer, Client, Params, Runtime>
+where
+	Client: Request,
+	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+	Runtime: FrameSystemConfig,
+{
+	type SignedExtra = Params::SignedExtra;
+	type Index = Runtime::Index;
+	type AccountId = Runtime::AccountId;
+
+	fn get_pending_extrinsics<Address, Call, Signature>(
+		&self,
+	) -> ApiResult<Vec<UncheckedExtrinsicV4<Call, Self::SignedExtra, Address, Signature>>>
+	where
+		Address: Decode + Encode,
+		Call: Decode + Encode,
+		Signature: Decode + Encode,
+		Self::SignedExtra: Decode + Encode,
+	{
+		let pending_extrinsics: Vec<Bytes> =
+			self.client().request("author_pendingExtrinsics", rpc_params![])?;
+		pending_extrinsics
+			.into_iter()
+			.map(|extrinsic| Ok(Decode::decode(&mut extrinsic.as_ref())?))
+			.collect()
+	}
+
+	fn remove_extrinsics(
+		&self,
+		extrinsics: Vec<ExtrinsicOrHash<Runtime::Hash>>,
+	) -> ApiResult<Vec<Runtime::Hash>> {
+		let removed = self.client().request("author_removeExtrinsic", rpc_params![extrinsics])?;
+		Ok(removed)
+	}
+
+	fn remove_extrinsic_by_tag<Address, Signature>(
+		&self,
+		account: &Runtime::AccountId,
+		nonce: Runtime::Index,
+	) -> ApiResult<Vec<Runtime::Hash>>
+	where
+		Address: Decode + PartialEq + From<Runtime::AccountId>,
+		Signature: Decode,
+		Self::SignedExtra: Decode + SignedExtraNonce<Runtime::Index>,
+	{
+		let pending_extrinsics: Vec<Bytes> =
+			self.client().request("author_pendingExtrinsics", rpc_params![])?;
+		let address = Address::from(account.clone());
+		let matching: Vec<_> = pending_extrinsics
+			.into_iter()
+			.filter(|extrinsic| {
+				signer_and_nonce::<Address, Signature, Self::SignedExtra, Runtime::Index>(extrinsic)
+					.map_or(false, |(signer, signer_nonce)| {
+						signer == address && signer_nonce == nonce
+					})
+			})
+			.map(ExtrinsicOrHash::Extrinsic)
+			.collect();
+		if matching.is_empty() {
+			debug!("No pending extrinsic of {:?} with nonce {:?}", account, nonce);
+			return Ok(Vec::new())
+		}
+		self.remove_extrinsics(matching)
+	}
+}
+
+/// Decode the signer and nonce of an encoded extrinsic, without decoding the call.
+/// `None` for unsigned extrinsics.
+fn signer_and_nonce<Address, Signature, SignedExtra, Index>(
+	extrinsic: &[u8],
+) -> Option<(Address, Index)>
+where
+	Address: Decode,
+	Signature: Decode,
+	SignedExtra: Decode + SignedExtraNonce<Index>,
+{
+	let input = &mut &extrinsic[..];
+	let _length: Compact<u32> = Decode::decode(input).ok()?;
+	let is_signed = input.read_byte().ok()? & 0b1000_0000 != 0;
+	if !is_signed {
+		return None
+	}
+	let (signer, _signature, extra): (Address, Signature, SignedExtra) =
+		Decode::decode(input).ok()?;
+	Some((signer, extra.nonce()))
+}
+
+/// Management of the keys in the keystore of the node, e.g. to rotate the session keys of
+/// a validator. These are unsafe rpc methods, only available on nodes run with
+/// `--rpc-methods=unsafe`.
+pub trait ManageKeys {
+	/// Whether the keystore contains the private key of `public_key` with `key_type`,
+	/// e.g. "babe" or "gran".
+	fn has_key(&self, public_key: Bytes, key_type: &str) -> ApiResult<bool>;
+
+	/// Whether the keystore contains the private keys of all `session_keys`,
+	/// as returned by `rotate_keys`.
+	fn has_session_keys(&self, session_keys: Bytes) -> ApiResult<bool>;
+
+	/// Insert the key derived from `suri` with `key_type` into the keystore.
+	fn insert_key(&self, key_type: &str, suri: &str, public_key: Bytes) -> ApiResult<()>;
+
+	/// Generate new session keys in the keystore and return their public keys, SCALE encoded
+	/// as they need to be passed to `Session::set_keys`.
+	fn rotate_keys(&self) -> ApiResult<Bytes>;
+}
+
+impl<Signer, Client, Params, Runtime> ManageKeys for Api<Signer, Client, Params, Runtime>
+where
+	Client: Request,
+	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+	Runtime: FrameSystemConfig,
+{
+	fn has_key(&self, public_key: Bytes, key_type: &str) -> ApiResult<bool> {
+		let has_key = self.client().request("author_hasKey", rpc_params![public_key, key_type])?;
+		Ok(has_key)
+	}
+
+	fn has_session_keys(&self, session_keys: Bytes) -> ApiResult<bool> {
+		let has_keys = self.client().request("author_hasSessionKeys", rpc_params![session_keys])?;
+		Ok(has_keys)
+	}
+
+	fn insert_key(&self, key_type: &str, suri: &str, public_key: Bytes) -> ApiResult<()> {
+		self.client()
+			.request("author_insertKey", rpc_params![key_type, suri, public_key])?;
+		Ok(())
+	}
+
+	fn rotate_keys(&self) -> ApiResult<Bytes> {
+		let session_keys = self.client().request("author_rotateKeys", rpc_params![])?;
+		Ok(session_keys)
+	}
+}
+
+/// Progress of a [`TransactionWatcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchProgress<BlockHash> {
+	/// The desired status has not been reached yet.
+	Pending,
+	/// The desired status has been reached. Contains the hash of the block
+	/// the extrinsic is included in, if it is included already.
+	Done(Option<BlockHash>),
+}
+
+/// Follows the lifecycle of a submitted extrinsic through its transaction status updates.
+///
+/// `Future` and `Retracted` are regular states, e.g. while waiting for an earlier nonce
+/// or during a reorg, and the watch continues. `Usurped`, `Dropped`, `Invalid` and
+/// `FinalityTimeout` are final and returned as the corresponding errors.
+#[derive(Debug, Clone)]
+pub struct TransactionWatcher<BlockHash> {
+	watch_until: XtStatus,
+	in_block: Option<BlockHash>,
+}
+
+impl<BlockHash: Clone + Debug> TransactionWatcher<BlockHash> {
+	pub fn new(watch_until: XtStatus) -> Self {
+		Self { watch_until, in_block: None }
+	}
+
+	/// The block the extrinsic is currently included in, if any.
+	pub fn in_block(&self) -> Option<&BlockHash> {
+		self.in_block.as_ref()
+	}
+
+	/// Process the next status update of the extrinsic.
+	pub fn update<Hash: Debug>(
+		&mut self,
+		transaction_status: TransactionStatus<Hash, BlockHash>,
+	) -> ApiResult<WatchProgress<BlockHash>> {
+		trace!("Transaction status update: {:?}", transaction_status);
+		let progress = match transaction_status {
+			TransactionStatus::Future => WatchProgress::Pending,
+			TransactionStatus::Ready => self.done_if_reached(XtStatus::Ready, None),
+			TransactionStatus::Broadcast(_) => self.done_if_reached(XtStatus::Broadcast, None),
+			TransactionStatus::InBlock(block_hash) => {
+				self.in_block = Some(block_hash.clone());
+				self.done_if_reached(XtStatus::InBlock, Some(block_hash))
+			},
+			TransactionStatus::Retracted(block_hash) => {
+				debug!("Block {:?} has been retracted, waiting for a new one", block_hash);
+				self.in_block = None;
+				WatchProgress::Pending
+			},
+			TransactionStatus::Finalized(block_hash) => WatchProgress::Done(Some(block_hash)),
+			// No further updates will be sent, but the block is not known to be final.
+			TransactionStatus::FinalityTimeout(block_hash) =>
+				return Err(Error::FinalityTimeout(format!("{:?}", block_hash))),
+			TransactionStatus::Usurped(hash) =>
+				return Err(Error::TransactionUsurped(format!("{:?}", hash))),
+			TransactionStatus::Dropped => return Err(Error::TransactionDropped),
+			TransactionStatus::Invalid => return Err(Error::TransactionInvalid),
+		};
+		Ok(progress)
+	}
+
+	fn done_if_reached(
+		&self,
+		status: XtStatus,
+		block_hash: Option<BlockHash>,
+	) -> WatchProgress<BlockHash> {
+		if status as u8 >= self.watch_until as u8 {
+			WatchProgress::Done(block_hash)
+		} else {
+			WatchProgress::Pending
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ac_primitives::{
+		AccountId, AccountId20, EthereumSignature, GenericAddress, MultiAddress,
+		SubstrateDefaultSignedExtra,
+	};
+	use sp_core::{ecdsa, H256};
+	use sp_runtime::{generic::Era, MultiSignature};
+
+	type Status = TransactionStatus<H256, H256>;
+	type Extra = SubstrateDefaultSignedExtra<u128, u32>;
+
+	#[test]
+	fn watcher_continues_after_future_and_retracted() {
+		let mut watcher = TransactionWatcher::new(XtStatus::Finalized);
+		let retracted_block = H256::repeat_byte(1);
+		let final_block = H256::repeat_byte(2);
+
+		for status in [
+			Status::Future,
+			Status::Ready,
+			Status::InBlock(retracted_block),
+			Status::Retracted(retracted_block),
+		] {
+			assert_eq!(watcher.update(status).unwrap(), WatchProgress::Pending);
+		}
+		assert_eq!(watcher.in_block(), None);
+
+		assert_eq!(watcher.update(Status::InBlock(final_block)).unwrap(), WatchProgress::Pending);
+		assert_eq!(watcher.in_block(), Some(&final_block));
+		assert_eq!(
+			watcher.update(Status::Finalized(final_block)).unwrap(),
+			WatchProgress::Done(Some(final_block))
+		);
+	}
+
+	#[test]
+	fn watcher_is_done_once_desired_status_is_reached() {
+		let block = H256::repeat_byte(1);
+
+		let mut watcher = TransactionWatcher::new(XtStatus::Ready);
+		assert_eq!(watcher.update(Status::Ready).unwrap(), WatchProgress::Done(None));
+
+		// A single node does not broadcast, so the extrinsic is in a block right away.
+		let mut watcher = TransactionWatcher::new(XtStatus::Broadcast);
+		assert_eq!(watcher.update(Status::Ready).unwrap(), WatchProgress::Pending);
+		assert_eq!(
+			watcher.update(Status::InBlock(block)).unwrap(),
+			WatchProgress::Done(Some(block))
+		);
+
+		let mut watcher = TransactionWatcher::new(XtStatus::InBlock);
+		assert_eq!(
+			watcher.update(Status::InBlock(block)).unwrap(),
+			WatchProgress::Done(Some(block))
+		);
+	}
+
+	#[test]
+	fn watcher_returns_typed_errors() {
+		let mut watcher = TransactionWatcher::<H256>::new(XtStatus::InBlock);
+
+		assert!(matches!(
+			watcher.update(Status::Usurped(H256::zero())),
+			Err(Error::TransactionUsurped(_))
+		));
+		assert!(matches!(watcher.update(Status::Dropped), Err(Error::TransactionDropped)));
+		assert!(matches!(watcher.update(Status::Invalid), Err(Error::TransactionInvalid)));
+		assert!(matches!(
+			watcher.update(Status::FinalityTimeout(H256::zero())),
+			Err(Error::FinalityTimeout(_))
+		));
+	}
+
+	#[test]
+	fn signer_and_nonce_are_decoded_without_call() {
+		let signer = MultiAddress::Id(AccountId::new([1; 32]));
+		let signature = MultiSignature::from(sp_core::sr25519::Signature::from_raw([0; 64]));
+		let extra = SubstrateDefaultSignedExtra::new(Era::Immortal, 5u32, 0u128);
+		let signed_xt =
+			UncheckedExtrinsicV4::new_signed(([4u8, 0u8], 42u64), signer.clone(), signature, extra);
+		let unsigned_xt =
+			UncheckedExtrinsicV4::<_, Extra> { signature: None, function: [0u8, 1u8] };
+
+		assert_eq!(
+			signer_and_nonce::<GenericAddress, MultiSignature, Extra, u32>(&signed_xt.encode()),
+			Some((signer, 5))
+		);
+		assert_eq!(
+			signer_and_nonce::<GenericAddress, MultiSignature, Extra, u32>(&unsigned_xt.encode()),
+			None
+		);
+	}
+
+	#[test]
+	fn signer_and_nonce_are_decoded_with_other_address_and_signature() {
+		let signer = AccountId20([1; 20]);
+		let signature = EthereumSignature(ecdsa::Signature::from_raw([0; 65]));
+		let extra = SubstrateDefaultSignedExtra::new(Era::Immortal, 7u32, 0u128);
+		let xt = UncheckedExtrinsicV4::new_signed([4u8, 0u8], signer, signature, extra);
+
+		assert_eq!(
+			signer_and_nonce::<AccountId20, EthereumSignature, Extra, u32>(&xt.encode()),
+			Some((signer, 7))
+		);
+	}
+
+	#[test]
+	fn extrinsic_or_hash_serializes_like_substrate() {
+		let by_hash = ExtrinsicOrHash::Hash(H256::repeat_byte(1));
+		let by_extrinsic = ExtrinsicOrHash::<H256>::Extrinsic(Bytes(vec![1, 2]));
+
+		assert_eq!(
+			serde_json::to_string(&by_hash).unwrap(),
+			format!("{{\"hash\":\"0x{}\"}}", "01".repeat(32))
+		);
+		assert_eq!(serde_json::to_string(&by_extrinsic).unwrap(), r#"{"extrinsic":"0x0102"}"#);
 	}
 }
@@ -18,11 +18,10 @@ use crate::{
 };
 use ac_compose_macros::rpc_params;
 pub use ac_node_api::{events::EventDetails, StaticEvent};
-use ac_primitives::{ExtrinsicParams, FrameSystemConfig};
+use ac_primitives::{ExtrinsicParams, FrameSystemConfig, SignExtrinsic};
 use log::*;
 use serde::de::DeserializeOwned;
-use sp_core::Pair;
-use sp_runtime::{generic::SignedBlock, traits::GetRuntimeBlockType, MultiSignature};
+use sp_runtime::{generic::SignedBlock, traits::GetRuntimeBlockType};
 
 pub trait GetHeader<Hash> {
 	type Header;
@@ -79,8 +78,7 @@ pub trait GetBlock<Number, Hash> {
 impl<Signer, Client, Params, Runtime> GetBlock<Runtime::BlockNumber, Runtime::Hash>
 	for Api<Signer, Client, Params, Runtime>
 where
-	Signer: Pair,
-	MultiSignature: From<Signer::Signature>,
+	Signer: SignExtrinsic<Runtime::AccountId>,
 	Client: Request,
 	Runtime: FrameSystemConfig + GetRuntimeBlockType,
 	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
@@ -14,20 +14,22 @@
 //! Interface to common frame system pallet information.
 
 use crate::{
-	api::{Api, ApiResult, GetStorage},
+	api::{Api, ApiResult, Error, GetStorage},
 	rpc::Subscribe,
 	utils, Request,
 };
 use ac_compose_macros::rpc_params;
 pub use ac_node_api::{events::EventDetails, StaticEvent};
-use ac_primitives::{AccountInfo, ExtrinsicParams, FrameSystemConfig};
+use ac_node_api::{DispatchError, Metadata, MetadataHashInfo};
+use ac_primitives::{AccountInfo, ExtrinsicParams, FrameSystemConfig, SignExtrinsic};
+use codec::{Decode, Input};
 use log::*;
 use serde::de::DeserializeOwned;
 use sp_core::{
 	storage::{StorageChangeSet, StorageKey},
-	Pair,
+	Bytes,
 };
-use sp_runtime::MultiSignature;
+use sp_runtime::transaction_validity::TransactionValidityError;
 
 pub trait GetAccountInformation<AccountId> {
 	type Index;
@@ -39,13 +41,16 @@ pub trait GetAccountInformation<AccountId> {
 	) -> ApiResult<Option<AccountInfo<Self::Index, Self::AccountData>>>;
 
 	fn get_account_data(&self, address: &AccountId) -> ApiResult<Option<Self::AccountData>>;
+
+	/// Get the next nonce of the account, which accounts for the transactions
+	/// in the pool, unlike the nonce of the account info.
+	fn get_account_next_index(&self, account_id: AccountId) -> ApiResult<Self::Index>;
 }
 
 impl<Signer, Client, Params, Runtime> GetAccountInformation<Runtime::AccountId>
 	for Api<Signer, Client, Params, Runtime>
 where
-	Signer: Pair,
-	MultiSignature: From<Signer::Signature>,
+	Signer: SignExtrinsic<Runtime::AccountId>,
 	Client: Request,
 	Runtime: FrameSystemConfig,
 	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
@@ -73,6 +78,12 @@ where
 	) -> ApiResult<Option<Runtime::AccountData>> {
 		self.get_account_info(address).map(|info| info.map(|i| i.data))
 	}
+
+	fn get_account_next_index(&self, account_id: Runtime::AccountId) -> ApiResult<Self::Index> {
+		let next_index =
+			self.client().request("system_accountNextIndex", rpc_params![account_id])?;
+		Ok(next_index)
+	}
 }
 
 pub trait SubscribeFrameSystem<Client, Hash>
@@ -100,3 +111,115 @@ where
 			.map_err(|e| e.into())
 	}
 }
+
+/// Outcome of a dry run of an extrinsic, decoded from the `ApplyExtrinsicResult`.
+#[derive(Debug)]
+pub enum DryRunResult {
+	/// The extrinsic would be included in a block and its call dispatched successfully.
+	Success,
+	/// The extrinsic would be included in a block and pay its fees, but its call fails.
+	DispatchError(DispatchError),
+	/// The extrinsic would not be included in a block at all, e.g. because of a bad nonce,
+	/// signature or mortality. Such extrinsics are rejected by the pool without paying fees.
+	TransactionValidityError(TransactionValidityError),
+}
+
+impl DryRunResult {
+	/// Decode a SCALE encoded `ApplyExtrinsicResult`. A failed dispatch is resolved against
+	/// the `metadata` into the pallet and name of its error, if possible.
+	pub fn decode_from(bytes: &[u8], metadata: &Metadata) -> Result<Self, codec::Error> {
+		let input = &mut &bytes[..];
+		match input.read_byte()? {
+			0 => match input.read_byte()? {
+				0 => Ok(Self::Success),
+				// The remaining bytes are the `DispatchError` of the runtime.
+				1 if !input.is_empty() =>
+					Ok(Self::DispatchError(DispatchError::decode_from(*input, metadata))),
+				_ => Err("Invalid DispatchOutcome".into()),
+			},
+			1 => Ok(Self::TransactionValidityError(TransactionValidityError::decode(input)?)),
+			_ => Err("Invalid ApplyExtrinsicResult variant".into()),
+		}
+	}
+
+	pub fn is_success(&self) -> bool {
+		matches!(self, Self::Success)
+	}
+}
+
+pub trait DryRun<Hash> {
+	/// Dry run the extrinsic against the state of `at_block`, the best block if `None`, to
+	/// find out whether it would be valid and dispatched successfully, without submitting it.
+	/// This is an unsafe rpc method, only available on nodes run with `--rpc-methods=unsafe`.
+	fn dry_run(&self, xthex_prefixed: &str, at_block: Option<Hash>) -> ApiResult<DryRunResult>;
+}
+
+impl<Signer, Client, Params, Runtime> DryRun<Runtime::Hash> for Api<Signer, Client, Params, Runtime>
+where
+	Client: Request,
+	Runtime: FrameSystemConfig,
+	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+{
+	fn dry_run(
+		&self,
+		xthex_prefixed: &str,
+		at_block: Option<Runtime::Hash>,
+	) -> ApiResult<DryRunResult> {
+		let apply_result: Bytes =
+			self.client().request("system_dryRun", rpc_params![xthex_prefixed, at_block])?;
+		debug!("Dry run result: {:?}", apply_result);
+		Ok(DryRunResult::decode_from(&apply_result, self.metadata())?)
+	}
+}
+
+pub trait GetMetadataHash {
+	/// Get the chain information the metadata hash commits to: The runtime version,
+	/// the `SS58Prefix` constant and the token properties of the node.
+	fn get_metadata_hash_info(&self) -> ApiResult<MetadataHashInfo>;
+
+	/// Get the metadata hash of the current runtime, which is needed to enable the
+	/// `CheckMetadataHash` signed extension with `DynamicExtrinsicParamsBuilder::metadata_hash`.
+	fn get_metadata_hash(&self) -> ApiResult<[u8; 32]>;
+}
+
+impl<Signer, Client, Params, Runtime> GetMetadataHash for Api<Signer, Client, Params, Runtime>
+where
+	Client: Request,
+	Runtime: FrameSystemConfig,
+	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+{
+	fn get_metadata_hash_info(&self) -> ApiResult<MetadataHashInfo> {
+		let properties: serde_json::Map<String, serde_json::Value> =
+			self.client().request("system_properties", rpc_params![])?;
+		// Chains with several tokens list the properties of the native token first.
+		let property = |name: &str| {
+			let value = properties.get(name);
+			match value.and_then(|value| value.as_array()) {
+				Some(values) => values.first(),
+				None => value,
+			}
+			.ok_or_else(|| Error::Other(format!("Missing system property {}", name).into()))
+		};
+		let decimals = property("tokenDecimals")?
+			.as_u64()
+			.and_then(|decimals| u8::try_from(decimals).ok())
+			.ok_or_else(|| Error::Other("Invalid system property tokenDecimals".into()))?;
+		let token_symbol = property("tokenSymbol")?
+			.as_str()
+			.ok_or_else(|| Error::Other("Invalid system property tokenSymbol".into()))?
+			.to_string();
+
+		Ok(MetadataHashInfo {
+			spec_version: self.runtime_version().spec_version,
+			spec_name: self.runtime_version().spec_name.to_string(),
+			base58_prefix: self.get_constant("System", "SS58Prefix")?,
+			decimals,
+			token_symbol,
+		})
+	}
+
+	fn get_metadata_hash(&self) -> ApiResult<[u8; 32]> {
+		let info = self.get_metadata_hash_info()?;
+		Ok(self.metadata().metadata_hash(info)?)
+	}
+}
@@ -13,7 +13,7 @@
 
 pub use self::{
 	author::*, chain::*, frame_system::*, pallet_balances::*, pallet_transaction_payment::*,
-	state::*, subscribe_events::*,
+	snapshot::*, state::*, storage_diff::*, subscribe_events::*,
 };
 
 pub mod author;
@@ -21,5 +21,7 @@ pub mod chain;
 pub mod frame_system;
 pub mod pallet_balances;
 pub mod pallet_transaction_payment;
+pub mod snapshot;
 pub mod state;
+pub mod storage_diff;
 pub mod subscribe_events;
@@ -12,11 +12,19 @@
 */
 use crate::{
 	api::{Api, ApiClientError, ApiResult},
+	extrinsic::{
+		common::Batch,
+		utility::{UTILITY_BATCH, UTILITY_MODULE},
+	},
 	rpc::Request,
 	ExtrinsicParams,
 };
-use ac_compose_macros::rpc_params;
-use ac_primitives::{BalancesConfig, FeeDetails, InclusionFee, RuntimeDispatchInfo};
+use ac_compose_macros::{rpc_params, try_compose_call};
+use ac_primitives::{
+	BalancesConfig, FeeDetails, FeeEstimate, InclusionFee, RuntimeDispatchInfo, SignExtrinsic,
+	UncheckedExtrinsicV4, WithEra,
+};
+use codec::Encode;
 use core::str::FromStr;
 use sp_rpc::number::NumberOrHex;
 
@@ -75,6 +83,76 @@ where
 	}
 }
 
+/// Fee estimation of calls before they are signed.
+pub trait EstimateFee {
+	type Balance;
+
+	/// Estimate the fee and weight of `call` by querying the payment info and fee details of
+	/// an extrinsic with a dummy signature. The extrinsic has the same length as the signed one,
+	/// because it is composed with the address, dummy signature and current nonce of the api
+	/// signer, and the extrinsic params with the mortality policy of the api. Fails with
+	/// `NoSigner` without a signer, as the length of its address and signature is unknown then.
+	fn estimate_fee<Call: Encode + Clone>(
+		&self,
+		call: Call,
+	) -> ApiResult<Option<FeeEstimate<Self::Balance>>>;
+
+	/// Estimate the fee and weight of dispatching all `calls` within a single `Utility::batch`.
+	fn estimate_batch_fee<Call: Encode + Clone>(
+		&self,
+		calls: Vec<Call>,
+	) -> ApiResult<Option<FeeEstimate<Self::Balance>>>;
+}
+
+impl<Signer, Client, Params, Runtime> EstimateFee for Api<Signer, Client, Params, Runtime>
+where
+	Signer: SignExtrinsic<Runtime::AccountId>,
+	Client: Request,
+	Runtime: BalancesConfig,
+	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+	Params::OtherParams: WithEra<Runtime::Hash>,
+	Runtime::Balance: TryFrom<NumberOrHex> + FromStr,
+{
+	type Balance = Runtime::Balance;
+
+	fn estimate_fee<Call: Encode + Clone>(
+		&self,
+		call: Call,
+	) -> ApiResult<Option<FeeEstimate<Self::Balance>>> {
+		let signer = self.signer().ok_or(ApiClientError::NoSigner)?;
+		let params = self.extrinsic_params_with_mortality(self.get_nonce()?)?;
+		// The signature is not checked, but needs to have the size of a real one.
+		let xthex_prefixed = UncheckedExtrinsicV4::new_signed(
+			call,
+			signer.extrinsic_address(),
+			signer.dummy_signature(),
+			params.signed_extra(),
+		)
+		.hex_encode();
+
+		let dispatch_info = match self.get_payment_info(&xthex_prefixed, None)? {
+			Some(dispatch_info) => dispatch_info,
+			None => return Ok(None),
+		};
+		let fee_details = self.get_fee_details(&xthex_prefixed, None)?;
+		Ok(Some(FeeEstimate {
+			partial_fee: dispatch_info.partial_fee,
+			weight: dispatch_info.weight,
+			class: dispatch_info.class,
+			fee_details,
+		}))
+	}
+
+	fn estimate_batch_fee<Call: Encode + Clone>(
+		&self,
+		calls: Vec<Call>,
+	) -> ApiResult<Option<FeeEstimate<Self::Balance>>> {
+		let batch_call =
+			try_compose_call!(self.metadata(), UTILITY_MODULE, UTILITY_BATCH, Batch { calls })?;
+		self.estimate_fee(batch_call)
+	}
+}
+
 fn convert_fee_details<Balance: TryFrom<NumberOrHex>>(
 	details: FeeDetails<NumberOrHex>,
 ) -> ApiResult<FeeDetails<Balance>> {
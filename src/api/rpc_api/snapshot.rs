@@ -0,0 +1,78 @@
+/*
+   Copyright 2019 Supercomputing Systems AG
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+	   http://www.apache.org/licenses/LICENSE-2.0
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+use crate::{
+	api::{Api, ApiResult, Error},
+	rpc::{Request, SnapshotHeader, StateSnapshot},
+};
+use ac_compose_macros::rpc_params;
+use ac_primitives::{ExtrinsicParams, FrameSystemConfig};
+use log::*;
+use serde::de::DeserializeOwned;
+use sp_core::{storage::StorageKey, Bytes};
+use sp_runtime::traits::Header;
+use sp_version::RuntimeVersion;
+use std::collections::BTreeMap;
+
+pub trait ExportSnapshot<Hash> {
+	/// Export all storage entries whose keys begin with one of the `prefixes`, together with
+	/// the block hash, state root, runtime version and metadata of `at_block`.
+	/// Use `utils::pallet_storage_prefix` to export the storage of whole pallets.
+	///
+	/// The snapshot can be saved to a file and served offline with a `SnapshotClient`.
+	fn export_snapshot(
+		&self,
+		prefixes: Vec<StorageKey>,
+		at_block: Hash,
+	) -> ApiResult<StateSnapshot<Hash>>;
+}
+
+impl<Signer, Client, Params, Runtime> ExportSnapshot<Runtime::Hash>
+	for Api<Signer, Client, Params, Runtime>
+where
+	Client: Request,
+	Runtime: FrameSystemConfig,
+	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+	Runtime::Header: DeserializeOwned,
+{
+	fn export_snapshot(
+		&self,
+		prefixes: Vec<StorageKey>,
+		at_block: Runtime::Hash,
+	) -> ApiResult<StateSnapshot<Runtime::Hash>> {
+		let block_header: Runtime::Header = self
+			.client()
+			.request::<Option<Runtime::Header>>("chain_getHeader", rpc_params![Some(at_block)])?
+			.ok_or(Error::BlockHeaderNotFound)?;
+		let runtime_version: RuntimeVersion =
+			self.client().request("state_getRuntimeVersion", rpc_params![Some(at_block)])?;
+		let metadata: Bytes =
+			self.client().request("state_getMetadata", rpc_params![Some(at_block)])?;
+
+		let mut storage = BTreeMap::new();
+		for prefix in prefixes {
+			let entries = self.get_storage_with_prefix(&prefix, at_block)?;
+			debug!("Exporting {} storage entries with prefix {:?}", entries.len(), prefix);
+			storage.extend(entries.into_iter().map(|(key, value)| (key.0, value)));
+		}
+
+		let header = SnapshotHeader {
+			block_hash: at_block,
+			state_root: *block_header.state_root(),
+			genesis_hash: self.genesis_hash(),
+			runtime_version,
+			metadata: metadata.0,
+		};
+		Ok(StateSnapshot { header, storage })
+	}
+}
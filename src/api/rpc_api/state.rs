@@ -10,15 +10,27 @@
    See the License for the specific language governing permissions and
    limitations under the License.
 */
-use crate::{api::ApiResult, rpc::Subscribe, utils, Api, MetadataError, ReadProof, Request};
+use crate::{
+	api::{ApiClientError, ApiResult},
+	rpc::{HandleSubscription, Subscribe},
+	utils, Api, MetadataError, ReadProof, Request,
+};
 use ac_compose_macros::rpc_params;
+use ac_node_api::{
+	decode_value_as_type, error::Error as NodeApiError, StorageAddress, TypeId, Value,
+};
 pub use ac_node_api::{events::EventDetails, StaticEvent};
 use ac_primitives::{ExtrinsicParams, FrameSystemConfig};
 use codec::{Decode, Encode};
+use core::marker::PhantomData;
 use log::*;
 use serde::de::DeserializeOwned;
 use sp_core::storage::{StorageChangeSet, StorageData, StorageKey};
 
+/// The changes of a block: the block hash and the new, decoded values of the changed keys.
+/// A value of `None` means the key has been removed from storage.
+pub type StorageChanges<Hash, V> = (Hash, Vec<(StorageKey, Option<V>)>);
+
 /// Generic interface to substrate storage.
 pub trait GetStorage<Hash> {
 	fn get_storage_value<V: Decode>(
@@ -95,8 +107,51 @@ pub trait GetStorage<Hash> {
 
 	fn get_keys(&self, key: StorageKey, at_block: Option<Hash>) -> ApiResult<Option<Vec<String>>>;
 
+	/// Returns up to `count` storage keys beginning with `prefix`, starting after `start_key`.
+	fn get_storage_keys_paged(
+		&self,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+		at_block: Option<Hash>,
+	) -> ApiResult<Vec<StorageKey>>;
+
 	fn get_constant<C: Decode>(&self, pallet: &'static str, constant: &'static str)
 		-> ApiResult<C>;
+
+	/// Query any storage entry by its pallet and storage name, with the map keys given as
+	/// dynamic [`Value`]s. The keys are encoded and the storage value is decoded with the types
+	/// found in the metadata, so no runtime types need to be known at compile time.
+	fn get_storage_dynamic(
+		&self,
+		storage_prefix: &str,
+		storage_key_name: &str,
+		keys: Vec<Value>,
+		at_block: Option<Hash>,
+	) -> ApiResult<Option<Value<TypeId>>>;
+
+	/// Query the value stored under `keys` in the storage entry of a validated [`StorageAddress`].
+	fn get_storage_by_address<K, V: Decode>(
+		&self,
+		address: &StorageAddress<K, V>,
+		keys: &K,
+		at_block: Option<Hash>,
+	) -> ApiResult<Option<V>>;
+
+	fn get_storage_proof_by_address<K, V>(
+		&self,
+		address: &StorageAddress<K, V>,
+		keys: &K,
+		at_block: Option<Hash>,
+	) -> ApiResult<Option<ReadProof<Hash>>>;
+
+	/// Query all values of the storage entry of a validated [`StorageAddress`], e.g. all
+	/// entries of a map, together with their storage keys.
+	fn get_storage_entries_by_address<K, V: Decode>(
+		&self,
+		address: &StorageAddress<K, V>,
+		at_block: Option<Hash>,
+	) -> ApiResult<Vec<(StorageKey, V)>>;
 }
 
 impl<Signer, Client, Params, Runtime> GetStorage<Runtime::Hash>
@@ -242,6 +297,19 @@ where
 		Ok(keys)
 	}
 
+	fn get_storage_keys_paged(
+		&self,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+		at_block: Option<Runtime::Hash>,
+	) -> ApiResult<Vec<StorageKey>> {
+		let keys = self
+			.client()
+			.request("state_getKeysPaged", rpc_params![prefix, count, start_key, at_block])?;
+		Ok(keys)
+	}
+
 	fn get_constant<C: Decode>(
 		&self,
 		pallet: &'static str,
@@ -256,6 +324,75 @@ where
 
 		Ok(Decode::decode(&mut c.value.as_slice())?)
 	}
+
+	fn get_storage_dynamic(
+		&self,
+		storage_prefix: &str,
+		storage_key_name: &str,
+		keys: Vec<Value>,
+		at_block: Option<Runtime::Hash>,
+	) -> ApiResult<Option<Value<TypeId>>> {
+		let storagekey =
+			self.metadata()
+				.storage_key_from_values(storage_prefix, storage_key_name, keys)?;
+		info!("storage key is: 0x{}", hex::encode(&storagekey));
+		let value_ty_id = self.metadata().storage_value_ty_id(storage_prefix, storage_key_name)?;
+
+		let s = self.get_opaque_storage_by_key_hash(storagekey, at_block)?;
+		match s {
+			Some(storage) => {
+				let value = decode_value_as_type(
+					&mut storage.as_slice(),
+					value_ty_id,
+					self.metadata().types(),
+				)
+				.map_err(NodeApiError::DecodeValue)?;
+				Ok(Some(value))
+			},
+			None => Ok(None),
+		}
+	}
+
+	fn get_storage_by_address<K, V: Decode>(
+		&self,
+		address: &StorageAddress<K, V>,
+		keys: &K,
+		at_block: Option<Runtime::Hash>,
+	) -> ApiResult<Option<V>> {
+		let storagekey = address.key(keys);
+		info!("storage key is: 0x{}", hex::encode(&storagekey));
+		self.get_storage_by_key_hash(storagekey, at_block)
+	}
+
+	fn get_storage_proof_by_address<K, V>(
+		&self,
+		address: &StorageAddress<K, V>,
+		keys: &K,
+		at_block: Option<Runtime::Hash>,
+	) -> ApiResult<Option<ReadProof<Runtime::Hash>>> {
+		let storagekey = address.key(keys);
+		info!("storage key is: 0x{}", hex::encode(&storagekey));
+		self.get_storage_proof_by_keys(vec![storagekey], at_block)
+	}
+
+	fn get_storage_entries_by_address<K, V: Decode>(
+		&self,
+		address: &StorageAddress<K, V>,
+		at_block: Option<Runtime::Hash>,
+	) -> ApiResult<Vec<(StorageKey, V)>> {
+		// All pages of keys must be fetched at the same block, the head may change in between.
+		let at_block = match at_block {
+			Some(block_hash) => block_hash,
+			None => self
+				.client()
+				.request::<Option<Runtime::Hash>>("chain_getBlockHash", rpc_params![])?
+				.ok_or(ApiClientError::BlockHashNotFound)?,
+		};
+		self.get_storage_with_prefix(&address.prefix(), at_block)?
+			.into_iter()
+			.map(|(key, value)| Ok((key, Decode::decode(&mut value.as_slice())?)))
+			.collect()
+	}
 }
 
 pub trait SubscribeState<Client, Hash>
@@ -268,6 +405,22 @@ where
 		pallet: &str,
 		storage_key: &str,
 	) -> ApiResult<Client::Subscription<StorageChangeSet<Hash>>>;
+
+	/// Subscribe to the changes of several storage keys at once, e.g. built with
+	/// `metadata().storage_value_key`, `storage_map_key` or `storage_double_map_key`.
+	/// All watched keys must store a value of type `V`.
+	fn subscribe_storage<V: Decode>(
+		&self,
+		keys: Vec<StorageKey>,
+	) -> ApiResult<StorageChangeSubscription<Client::Subscription<StorageChangeSet<Hash>>, Hash, V>>;
+
+	/// Subscribe to the values stored under each of `keys` in the storage entry of a
+	/// validated [`StorageAddress`].
+	fn subscribe_storage_by_address<K, V: Decode>(
+		&self,
+		address: &StorageAddress<K, V>,
+		keys: &[K],
+	) -> ApiResult<StorageChangeSubscription<Client::Subscription<StorageChangeSet<Hash>>, Hash, V>>;
 }
 
 impl<Signer, Client, Params, Runtime> SubscribeState<Client, Runtime::Hash>
@@ -288,4 +441,95 @@ where
 			.subscribe("state_subscribeStorage", rpc_params![vec![key]], "state_unsubscribeStorage")
 			.map_err(|e| e.into())
 	}
+
+	fn subscribe_storage<V: Decode>(
+		&self,
+		keys: Vec<StorageKey>,
+	) -> ApiResult<
+		StorageChangeSubscription<
+			Client::Subscription<StorageChangeSet<Runtime::Hash>>,
+			Runtime::Hash,
+			V,
+		>,
+	> {
+		debug!("subscribing to storage keys {:?}", keys);
+		let subscription = self.client().subscribe(
+			"state_subscribeStorage",
+			rpc_params![keys.clone()],
+			"state_unsubscribeStorage",
+		)?;
+		Ok(StorageChangeSubscription::new(subscription, keys))
+	}
+
+	fn subscribe_storage_by_address<K, V: Decode>(
+		&self,
+		address: &StorageAddress<K, V>,
+		keys: &[K],
+	) -> ApiResult<
+		StorageChangeSubscription<
+			Client::Subscription<StorageChangeSet<Runtime::Hash>>,
+			Runtime::Hash,
+			V,
+		>,
+	> {
+		self.subscribe_storage(keys.iter().map(|keys| address.key(keys)).collect())
+	}
+}
+
+/// Subscription to the changes of a set of storage keys, which decodes the
+/// changed values into `V`.
+pub struct StorageChangeSubscription<Subscription, Hash, V> {
+	subscription: Subscription,
+	keys: Vec<StorageKey>,
+	_phantom: PhantomData<(Hash, V)>,
+}
+
+impl<Subscription, Hash, V> StorageChangeSubscription<Subscription, Hash, V> {
+	pub fn new(subscription: Subscription, keys: Vec<StorageKey>) -> Self {
+		Self { subscription, keys, _phantom: Default::default() }
+	}
+
+	/// The storage keys watched by this subscription.
+	pub fn keys(&self) -> &[StorageKey] {
+		&self.keys
+	}
+}
+
+impl<Subscription, Hash, V> StorageChangeSubscription<Subscription, Hash, V>
+where
+	Subscription: HandleSubscription<StorageChangeSet<Hash>>,
+	Hash: DeserializeOwned,
+	V: Decode,
+{
+	/// Returns the changes of the next block that modified at least one of the watched keys.
+	/// Returns `None` if the subscription has been terminated.
+	pub fn next(&mut self) -> Option<ApiResult<StorageChanges<Hash, V>>> {
+		let change_set = match self.subscription.next()? {
+			Ok(change_set) => change_set,
+			Err(e) => return Some(Err(e.into())),
+		};
+		Some(decode_change_set(change_set))
+	}
+
+	/// Unsubscribe and consume the subscription.
+	pub fn unsubscribe(self) -> ApiResult<()> {
+		self.subscription.unsubscribe().map_err(|e| e.into())
+	}
+}
+
+fn decode_change_set<Hash, V: Decode>(
+	change_set: StorageChangeSet<Hash>,
+) -> ApiResult<StorageChanges<Hash, V>> {
+	let changes = change_set
+		.changes
+		.into_iter()
+		.map(|(key, data)| {
+			let value = match data {
+				Some(data) => Some(V::decode(&mut data.0.as_slice())?),
+				None => None,
+			};
+			Ok((key, value))
+		})
+		.collect::<ApiResult<_>>()?;
+	Ok((change_set.block, changes))
 }
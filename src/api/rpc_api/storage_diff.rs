@@ -0,0 +1,200 @@
+/*
+   Copyright 2019 Supercomputing Systems AG
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+	   http://www.apache.org/licenses/LICENSE-2.0
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Compare the storage of a pallet or storage prefix between two blocks.
+
+use crate::{
+	api::{Api, ApiResult, GetStorage},
+	rpc::Request,
+};
+use ac_compose_macros::rpc_params;
+use ac_node_api::{decode_value_as_type, GetStorageTypes, Metadata, TypeId, Value};
+use ac_primitives::{ExtrinsicParams, FrameSystemConfig};
+use log::*;
+use serde::Serialize;
+use sp_core::{
+	storage::{StorageChangeSet, StorageKey},
+	Bytes,
+};
+use std::collections::BTreeMap;
+
+/// Number of keys fetched per `state_getKeysPaged` request.
+const KEYS_PAGE_SIZE: u32 = 1000;
+
+/// The storage entries that have been added, removed or changed between two blocks.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageDiff {
+	/// Entries that exist in the second, but not in the first block.
+	pub added: Vec<StorageDiffEntry>,
+	/// Entries that exist in the first, but not in the second block.
+	pub removed: Vec<StorageDiffEntry>,
+	/// Entries that exist in both blocks, but with different values.
+	pub changed: Vec<StorageDiffChange>,
+}
+
+impl StorageDiff {
+	/// Returns true if the storage is the same in both blocks.
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+	}
+}
+
+/// A storage entry that has been added or removed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageDiffEntry {
+	pub key: StorageKey,
+	/// Pallet and storage name of the entry, if it is known to the metadata.
+	pub entry: Option<(String, String)>,
+	pub value: StorageDiffValue,
+}
+
+/// A storage entry whose value has changed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageDiffChange {
+	pub key: StorageKey,
+	/// Pallet and storage name of the entry, if it is known to the metadata.
+	pub entry: Option<(String, String)>,
+	pub old_value: StorageDiffValue,
+	pub new_value: StorageDiffValue,
+}
+
+/// A storage value, decoded through the metadata if possible.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageDiffValue {
+	/// The value has been decoded with the value type found in the metadata.
+	Decoded(Value<TypeId>),
+	/// The key is unknown to the metadata, or the value could not be decoded.
+	Raw(Bytes),
+}
+
+pub trait GetStorageDiff<Hash> {
+	/// Compare all storage entries whose keys begin with `prefix` between `block_a` and
+	/// `block_b`. Use `utils::pallet_storage_prefix` for all entries of a pallet or
+	/// `utils::storage_key` for all entries of a single storage item.
+	fn get_storage_diff(
+		&self,
+		prefix: StorageKey,
+		block_a: Hash,
+		block_b: Hash,
+	) -> ApiResult<StorageDiff>;
+}
+
+impl<Signer, Client, Params, Runtime> GetStorageDiff<Runtime::Hash>
+	for Api<Signer, Client, Params, Runtime>
+where
+	Client: Request,
+	Runtime: FrameSystemConfig,
+	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+{
+	fn get_storage_diff(
+		&self,
+		prefix: StorageKey,
+		block_a: Runtime::Hash,
+		block_b: Runtime::Hash,
+	) -> ApiResult<StorageDiff> {
+		let mut storage_a = self.get_storage_with_prefix(&prefix, block_a)?;
+		let storage_b = self.get_storage_with_prefix(&prefix, block_b)?;
+		debug!("Comparing {} with {} storage entries", storage_a.len(), storage_b.len());
+
+		let metadata = self.metadata();
+		let mut diff = StorageDiff::default();
+		for (key, new_value) in storage_b {
+			match storage_a.remove(&key) {
+				None => {
+					let entry = entry_name(metadata, &key);
+					let value = decode_value(metadata, &key, new_value);
+					diff.added.push(StorageDiffEntry { key, entry, value });
+				},
+				Some(old_value) if old_value != new_value => {
+					let entry = entry_name(metadata, &key);
+					let old_value = decode_value(metadata, &key, old_value);
+					let new_value = decode_value(metadata, &key, new_value);
+					diff.changed.push(StorageDiffChange { key, entry, old_value, new_value });
+				},
+				Some(_) => {},
+			}
+		}
+		for (key, old_value) in storage_a {
+			let entry = entry_name(metadata, &key);
+			let value = decode_value(metadata, &key, old_value);
+			diff.removed.push(StorageDiffEntry { key, entry, value });
+		}
+		Ok(diff)
+	}
+}
+
+impl<Signer, Client, Params, Runtime> Api<Signer, Client, Params, Runtime>
+where
+	Client: Request,
+	Runtime: FrameSystemConfig,
+	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+{
+	/// Fetch all keys with the given prefix page by page, and their values in one request per page.
+	/// The block is not optional, so that all pages are read from the same state.
+	pub(crate) fn get_storage_with_prefix(
+		&self,
+		prefix: &StorageKey,
+		at_block: Runtime::Hash,
+	) -> ApiResult<BTreeMap<StorageKey, Vec<u8>>> {
+		let mut storage = BTreeMap::new();
+		let mut start_key = None;
+		loop {
+			let keys = self.get_storage_keys_paged(
+				Some(prefix.clone()),
+				KEYS_PAGE_SIZE,
+				start_key,
+				Some(at_block),
+			)?;
+			if keys.is_empty() {
+				break
+			}
+			let is_last_page = keys.len() < KEYS_PAGE_SIZE as usize;
+			start_key = keys.last().cloned();
+
+			let change_sets: Vec<StorageChangeSet<Runtime::Hash>> =
+				self.client().request("state_queryStorageAt", rpc_params![keys, at_block])?;
+			for change_set in change_sets {
+				storage.extend(
+					change_set
+						.changes
+						.into_iter()
+						.filter_map(|(key, data)| data.map(|data| (key, data.0))),
+				);
+			}
+			if is_last_page {
+				break
+			}
+		}
+		Ok(storage)
+	}
+}
+
+fn entry_name(metadata: &Metadata, key: &StorageKey) -> Option<(String, String)> {
+	metadata
+		.storage_entry_for_key(&key.0)
+		.map(|(pallet, entry)| (pallet.name().to_string(), entry.name.clone()))
+}
+
+fn decode_value(metadata: &Metadata, key: &StorageKey, value: Vec<u8>) -> StorageDiffValue {
+	let decoded = metadata.storage_entry_for_key(&key.0).and_then(|(_, entry)| {
+		decode_value_as_type(&mut value.as_slice(), entry.get_value_ty_id(), metadata.types()).ok()
+	});
+	match decoded {
+		Some(decoded) => StorageDiffValue::Decoded(decoded),
+		None => StorageDiffValue::Raw(Bytes(value)),
+	}
+}
@@ -75,7 +75,8 @@ where
 
 				// Check for failed xt and return as Dispatch Error in case we find one.
 				// Careful - this reports the first one encountered. This event may belong to another extrinsic
-				// than the one that is being waited for.
+				// than the one that is being waited for. Use `submit_and_watch_extrinsic_until_success`
+				// to get the events of a specific extrinsic only.
 				if extrinsic_has_failed(&event_details) {
 					let dispatch_error =
 						DispatchError::decode_from(event_details.field_bytes(), self.metadata());
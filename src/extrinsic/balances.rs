@@ -17,15 +17,18 @@
 
 //! Extrinsics for `pallet-balances`.
 
-use crate::{api::Api, rpc::Request};
-use ac_compose_macros::compose_extrinsic;
+use crate::{
+	api::{Api, ApiResult},
+	rpc::Request,
+};
+use ac_compose_macros::try_compose_extrinsic;
 use ac_primitives::{
-	BalancesConfig, CallIndex, ExtrinsicParams, GenericAddress, UncheckedExtrinsicV4,
+	BalancesConfig, CallIndex, ExtrinsicParams, GenericAddress, SignExtrinsic,
+	UncheckedExtrinsicV4, WithEra,
 };
 use codec::{Compact, Encode};
 use serde::de::DeserializeOwned;
-use sp_core::crypto::Pair;
-use sp_runtime::{traits::GetRuntimeBlockType, MultiSignature, MultiSigner};
+use sp_runtime::{traits::GetRuntimeBlockType, MultiSignature};
 
 pub const BALANCES_MODULE: &str = "Balances";
 pub const BALANCES_TRANSFER: &str = "transfer";
@@ -35,31 +38,57 @@ pub type BalanceTransferFn<Balance> = (CallIndex, GenericAddress, Compact<Balanc
 pub type BalanceSetBalanceFn<Balance> =
 	(CallIndex, GenericAddress, Compact<Balance>, Compact<Balance>);
 
-pub type BalanceTransferXt<SignedExtra, Balance> =
-	UncheckedExtrinsicV4<BalanceTransferFn<Balance>, SignedExtra>;
-pub type BalanceSetBalanceXt<SignedExtra, Balance> =
-	UncheckedExtrinsicV4<BalanceSetBalanceFn<Balance>, SignedExtra>;
+pub type BalanceTransferXt<
+	SignedExtra,
+	Balance,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> = UncheckedExtrinsicV4<BalanceTransferFn<Balance>, SignedExtra, Address, Signature>;
+pub type BalanceSetBalanceXt<
+	SignedExtra,
+	Balance,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> = UncheckedExtrinsicV4<BalanceSetBalanceFn<Balance>, SignedExtra, Address, Signature>;
 
 #[cfg(feature = "std")]
 impl<Signer, Client, Params, Runtime> Api<Signer, Client, Params, Runtime>
 where
-	Signer: Pair,
-	MultiSignature: From<Signer::Signature>,
-	MultiSigner: From<Signer::Public>,
+	Signer: SignExtrinsic<Runtime::AccountId>,
 	Client: Request,
 	Runtime: GetRuntimeBlockType + BalancesConfig,
 	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+	Params::OtherParams: WithEra<Runtime::Hash>,
 	Compact<Runtime::Balance>: Encode,
 	Runtime::Header: DeserializeOwned,
 	Runtime::RuntimeBlock: DeserializeOwned,
-	Runtime::AccountId: From<Signer::Public>,
 {
 	pub fn balance_transfer(
 		&self,
 		to: GenericAddress,
 		amount: Runtime::Balance,
-	) -> BalanceTransferXt<Params::SignedExtra, Runtime::Balance> {
-		compose_extrinsic!(self, BALANCES_MODULE, BALANCES_TRANSFER, to, Compact(amount))
+	) -> BalanceTransferXt<
+		Params::SignedExtra,
+		Runtime::Balance,
+		Signer::ExtrinsicAddress,
+		Signer::Signature,
+	> {
+		self.try_balance_transfer(to, amount).unwrap()
+	}
+
+	pub fn try_balance_transfer(
+		&self,
+		to: GenericAddress,
+		amount: Runtime::Balance,
+	) -> ApiResult<
+		BalanceTransferXt<
+			Params::SignedExtra,
+			Runtime::Balance,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(self, BALANCES_MODULE, BALANCES_TRANSFER, to, Compact(amount))
 	}
 
 	pub fn balance_set_balance(
@@ -67,8 +96,29 @@ where
 		who: GenericAddress,
 		free_balance: Runtime::Balance,
 		reserved_balance: Runtime::Balance,
-	) -> BalanceSetBalanceXt<Params::SignedExtra, Runtime::Balance> {
-		compose_extrinsic!(
+	) -> BalanceSetBalanceXt<
+		Params::SignedExtra,
+		Runtime::Balance,
+		Signer::ExtrinsicAddress,
+		Signer::Signature,
+	> {
+		self.try_balance_set_balance(who, free_balance, reserved_balance).unwrap()
+	}
+
+	pub fn try_balance_set_balance(
+		&self,
+		who: GenericAddress,
+		free_balance: Runtime::Balance,
+		reserved_balance: Runtime::Balance,
+	) -> ApiResult<
+		BalanceSetBalanceXt<
+			Params::SignedExtra,
+			Runtime::Balance,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(
 			self,
 			BALANCES_MODULE,
 			BALANCES_SET_BALANCE,
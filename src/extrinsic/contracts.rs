@@ -18,16 +18,18 @@
 //! Extrinsics for `pallet-contract`.
 //! Contracts module is community maintained and not CI tested, therefore it may not work as is.
 
-use crate::{api::Api, rpc::Request};
-use ac_compose_macros::compose_extrinsic;
+use crate::{
+	api::{Api, ApiResult},
+	rpc::Request,
+};
+use ac_compose_macros::try_compose_extrinsic;
 use ac_primitives::{
 	BalancesConfig, CallIndex, ContractsConfig, ExtrinsicParams, FrameSystemConfig, GenericAddress,
-	UncheckedExtrinsicV4,
+	SignExtrinsic, UncheckedExtrinsicV4, WithEra,
 };
 use codec::{Compact, Encode};
 use serde::de::DeserializeOwned;
-use sp_core::crypto::Pair;
-use sp_runtime::{traits::GetRuntimeBlockType, MultiSignature, MultiSigner};
+use sp_runtime::{traits::GetRuntimeBlockType, MultiSignature};
 use sp_std::prelude::*;
 
 pub const CONTRACTS_MODULE: &str = "Contracts";
@@ -53,13 +55,27 @@ pub type ContractInstantiateWithCodeFn<Currency> =
 	(CallIndex, Endowment<Currency>, GasLimit, Code, Data, Salt);
 pub type ContractCallFn<Currency> = (CallIndex, Destination, Value<Currency>, GasLimit, Data);
 
-pub type ContractPutCodeXt<SignedExtra> = UncheckedExtrinsicV4<ContractPutCodeFn, SignedExtra>;
-pub type ContractInstantiateXt<SignedExtra, Currency, Hash> =
-	UncheckedExtrinsicV4<ContractInstantiateFn<Currency, Hash>, SignedExtra>;
-pub type ContractInstantiateWithCodeXt<SignedExtra, Currency> =
-	UncheckedExtrinsicV4<ContractInstantiateWithCodeFn<Currency>, SignedExtra>;
-pub type ContractCallXt<SignedExtra, Currency> =
-	UncheckedExtrinsicV4<ContractCallFn<Currency>, SignedExtra>;
+pub type ContractPutCodeXt<SignedExtra, Address = GenericAddress, Signature = MultiSignature> =
+	UncheckedExtrinsicV4<ContractPutCodeFn, SignedExtra, Address, Signature>;
+pub type ContractInstantiateXt<
+	SignedExtra,
+	Currency,
+	Hash,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> = UncheckedExtrinsicV4<ContractInstantiateFn<Currency, Hash>, SignedExtra, Address, Signature>;
+pub type ContractInstantiateWithCodeXt<
+	SignedExtra,
+	Currency,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> = UncheckedExtrinsicV4<ContractInstantiateWithCodeFn<Currency>, SignedExtra, Address, Signature>;
+pub type ContractCallXt<
+	SignedExtra,
+	Currency,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> = UncheckedExtrinsicV4<ContractCallFn<Currency>, SignedExtra, Address, Signature>;
 
 #[cfg(feature = "std")]
 type BalanceOf<T> = <<T as ContractsConfig>::Currency as frame_support::traits::Currency<
@@ -69,14 +85,12 @@ type BalanceOf<T> = <<T as ContractsConfig>::Currency as frame_support::traits::
 #[cfg(feature = "std")]
 impl<Signer, Client, Params, Runtime> Api<Signer, Client, Params, Runtime>
 where
-	Signer: Pair,
-	MultiSignature: From<Signer::Signature>,
-	MultiSigner: From<Signer::Public>,
+	Signer: SignExtrinsic<Runtime::AccountId>,
 	Client: Request,
 	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+	Params::OtherParams: WithEra<Runtime::Hash>,
 	Runtime: GetRuntimeBlockType + ContractsConfig + BalancesConfig,
 	Compact<BalanceOf<Runtime>>: Encode + Clone,
-	Runtime::AccountId: From<Signer::Public>,
 	Runtime::Currency: frame_support::traits::Currency<Runtime::AccountId>,
 	Runtime::Header: DeserializeOwned,
 	Runtime::RuntimeBlock: DeserializeOwned,
@@ -85,8 +99,18 @@ where
 		&self,
 		gas_limit: Gas,
 		code: Data,
-	) -> ContractPutCodeXt<Params::SignedExtra> {
-		compose_extrinsic!(self, CONTRACTS_MODULE, CONTRACTS_PUT_CODE, Compact(gas_limit), code)
+	) -> ContractPutCodeXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature> {
+		self.try_contract_put_code(gas_limit, code).unwrap()
+	}
+
+	pub fn try_contract_put_code(
+		&self,
+		gas_limit: Gas,
+		code: Data,
+	) -> ApiResult<
+		ContractPutCodeXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>,
+	> {
+		try_compose_extrinsic!(self, CONTRACTS_MODULE, CONTRACTS_PUT_CODE, Compact(gas_limit), code)
 	}
 
 	pub fn contract_instantiate(
@@ -95,8 +119,32 @@ where
 		gas_limit: Gas,
 		code_hash: Runtime::Hash,
 		data: Data,
-	) -> ContractInstantiateXt<Params::SignedExtra, BalanceOf<Runtime>, Runtime::Hash> {
-		compose_extrinsic!(
+	) -> ContractInstantiateXt<
+		Params::SignedExtra,
+		BalanceOf<Runtime>,
+		Runtime::Hash,
+		Signer::ExtrinsicAddress,
+		Signer::Signature,
+	> {
+		self.try_contract_instantiate(endowment, gas_limit, code_hash, data).unwrap()
+	}
+
+	pub fn try_contract_instantiate(
+		&self,
+		endowment: BalanceOf<Runtime>,
+		gas_limit: Gas,
+		code_hash: Runtime::Hash,
+		data: Data,
+	) -> ApiResult<
+		ContractInstantiateXt<
+			Params::SignedExtra,
+			BalanceOf<Runtime>,
+			Runtime::Hash,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(
 			self,
 			CONTRACTS_MODULE,
 			CONTRACTS_INSTANTIATE,
@@ -114,8 +162,32 @@ where
 		code: Data,
 		data: Data,
 		salt: Data,
-	) -> ContractInstantiateWithCodeXt<Params::SignedExtra, BalanceOf<Runtime>> {
-		compose_extrinsic!(
+	) -> ContractInstantiateWithCodeXt<
+		Params::SignedExtra,
+		BalanceOf<Runtime>,
+		Signer::ExtrinsicAddress,
+		Signer::Signature,
+	> {
+		self.try_contract_instantiate_with_code(endowment, gas_limit, code, data, salt)
+			.unwrap()
+	}
+
+	pub fn try_contract_instantiate_with_code(
+		&self,
+		endowment: BalanceOf<Runtime>,
+		gas_limit: Gas,
+		code: Data,
+		data: Data,
+		salt: Data,
+	) -> ApiResult<
+		ContractInstantiateWithCodeXt<
+			Params::SignedExtra,
+			BalanceOf<Runtime>,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(
 			self,
 			CONTRACTS_MODULE,
 			CONTRACTS_INSTANTIATE_WITH_CODE,
@@ -133,8 +205,30 @@ where
 		value: BalanceOf<Runtime>,
 		gas_limit: Gas,
 		data: Data,
-	) -> ContractCallXt<Params::SignedExtra, BalanceOf<Runtime>> {
-		compose_extrinsic!(
+	) -> ContractCallXt<
+		Params::SignedExtra,
+		BalanceOf<Runtime>,
+		Signer::ExtrinsicAddress,
+		Signer::Signature,
+	> {
+		self.try_contract_call(dest, value, gas_limit, data).unwrap()
+	}
+
+	pub fn try_contract_call(
+		&self,
+		dest: GenericAddress,
+		value: BalanceOf<Runtime>,
+		gas_limit: Gas,
+		data: Data,
+	) -> ApiResult<
+		ContractCallXt<
+			Params::SignedExtra,
+			BalanceOf<Runtime>,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(
 			self,
 			CONTRACTS_MODULE,
 			CONTRACTS_CALL,
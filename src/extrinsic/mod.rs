@@ -16,6 +16,8 @@
 */
 
 //! Offers some predefined extrinsics for common runtime modules.
+//! Each of them has a `try_` variant, which returns an error instead of panicking if the call
+//! is not in the metadata of the node or if the nonce of the signer can not be fetched.
 
 pub mod balances;
 pub mod common;
@@ -23,3 +25,25 @@ pub mod contracts;
 #[cfg(feature = "staking-xt")]
 pub mod staking;
 pub mod utility;
+
+#[cfg(test)]
+mod tests {
+	use crate::{pallet_call, AccountId, GenericAddress};
+	use codec::{Compact, Encode};
+
+	#[pallet_call(pallet = "Balances", call = "transfer", krate = "crate")]
+	#[derive(Clone)]
+	struct BalanceTransfer<Balance> {
+		dest: GenericAddress,
+		#[codec(compact)]
+		value: Balance,
+	}
+
+	#[test]
+	fn pallet_call_encodes_fields_like_call_arguments() {
+		let dest = GenericAddress::Id(AccountId::new([1; 32]));
+		let call = BalanceTransfer { dest: dest.clone(), value: 42u128 };
+
+		assert_eq!(call.encode(), (dest, Compact(42u128)).encode());
+	}
+}
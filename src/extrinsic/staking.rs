@@ -18,16 +18,15 @@
 //! Extrinsics for `pallet-staking`.
 
 use super::common::*;
-use crate::{rpc::Request, Api};
-use ac_compose_macros::compose_extrinsic;
+use crate::{rpc::Request, Api, ApiResult};
+use ac_compose_macros::try_compose_extrinsic;
 use ac_primitives::{
-	BalancesConfig, CallIndex, ExtrinsicParams, GenericAddress, RewardDestination, StakingConfig,
-	UncheckedExtrinsicV4,
+	BalancesConfig, CallIndex, ExtrinsicParams, GenericAddress, RewardDestination, SignExtrinsic,
+	StakingConfig, UncheckedExtrinsicV4, WithEra,
 };
 use codec::{Compact, Encode};
 use serde::de::DeserializeOwned;
-use sp_core::Pair;
-use sp_runtime::{traits::GetRuntimeBlockType, AccountId32, MultiSignature, MultiSigner};
+use sp_runtime::{traits::GetRuntimeBlockType, AccountId32, MultiSignature};
 
 const STAKING_MODULE: &str = "Staking";
 const STAKING_BOND: &str = "bond";
@@ -61,41 +60,64 @@ pub type StakingForceNoEraFn = (CallIndex, ForceEra);
 pub type StakingSetPayeeFn = (CallIndex, GenericAddress);
 pub type StakingSetValidatorCountFn = (CallIndex, u32);
 
-pub type StakingBondXt<SignedExtra, Balance> =
-	UncheckedExtrinsicV4<StakingBondFn<Balance>, SignedExtra>;
-pub type StakingBondExtraXt<SignedExtra, Balance> =
-	UncheckedExtrinsicV4<StakingBondExtraFn<Balance>, SignedExtra>;
-pub type StakingUnbondXt<SignedExtra, Balance> =
-	UncheckedExtrinsicV4<StakingUnbondFn<Balance>, SignedExtra>;
-pub type StakingRebondXt<SignedExtra, Balance> =
-	UncheckedExtrinsicV4<StakingRebondFn<Balance>, SignedExtra>;
-pub type StakingWithdrawUnbondedXt<SignedExtra> =
-	UncheckedExtrinsicV4<StakingWithdrawUnbondedFn, SignedExtra>;
-pub type StakingNominateXt<SignedExtra> = UncheckedExtrinsicV4<StakingNominateFn, SignedExtra>;
-pub type StakingChillXt<SignedExtra> = UncheckedExtrinsicV4<StakingChillFn, SignedExtra>;
-pub type StakingSetControllerXt<SignedExtra> =
-	UncheckedExtrinsicV4<StakingSetControllerFn, SignedExtra>;
-pub type StakingPayoutStakersXt<SignedExtra> =
-	UncheckedExtrinsicV4<StakingPayoutStakersFn, SignedExtra>;
-pub type StakingForceNewEraXt<SignedExtra> =
-	UncheckedExtrinsicV4<StakingForceNewEraFn, SignedExtra>;
-pub type StakingForceNewEraAlwaysXt<SignedExtra> =
-	UncheckedExtrinsicV4<StakingForceNewEraAlwaysFn, SignedExtra>;
-pub type StakingForceNoEraXt<SignedExtra> = UncheckedExtrinsicV4<StakingForceNoEraFn, SignedExtra>;
-pub type StakingSetPayeeXt<SignedExtra> = UncheckedExtrinsicV4<StakingSetPayeeFn, SignedExtra>;
-pub type StakingSetValidatorCountXt<SignedExtra> =
-	UncheckedExtrinsicV4<StakingSetValidatorCountFn, SignedExtra>;
+pub type StakingBondXt<SignedExtra, Balance, Address = GenericAddress, Signature = MultiSignature> =
+	UncheckedExtrinsicV4<StakingBondFn<Balance>, SignedExtra, Address, Signature>;
+pub type StakingBondExtraXt<
+	SignedExtra,
+	Balance,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> = UncheckedExtrinsicV4<StakingBondExtraFn<Balance>, SignedExtra, Address, Signature>;
+pub type StakingUnbondXt<
+	SignedExtra,
+	Balance,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> = UncheckedExtrinsicV4<StakingUnbondFn<Balance>, SignedExtra, Address, Signature>;
+pub type StakingRebondXt<
+	SignedExtra,
+	Balance,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> = UncheckedExtrinsicV4<StakingRebondFn<Balance>, SignedExtra, Address, Signature>;
+pub type StakingWithdrawUnbondedXt<
+	SignedExtra,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> = UncheckedExtrinsicV4<StakingWithdrawUnbondedFn, SignedExtra, Address, Signature>;
+pub type StakingNominateXt<SignedExtra, Address = GenericAddress, Signature = MultiSignature> =
+	UncheckedExtrinsicV4<StakingNominateFn, SignedExtra, Address, Signature>;
+pub type StakingChillXt<SignedExtra, Address = GenericAddress, Signature = MultiSignature> =
+	UncheckedExtrinsicV4<StakingChillFn, SignedExtra, Address, Signature>;
+pub type StakingSetControllerXt<SignedExtra, Address = GenericAddress, Signature = MultiSignature> =
+	UncheckedExtrinsicV4<StakingSetControllerFn, SignedExtra, Address, Signature>;
+pub type StakingPayoutStakersXt<SignedExtra, Address = GenericAddress, Signature = MultiSignature> =
+	UncheckedExtrinsicV4<StakingPayoutStakersFn, SignedExtra, Address, Signature>;
+pub type StakingForceNewEraXt<SignedExtra, Address = GenericAddress, Signature = MultiSignature> =
+	UncheckedExtrinsicV4<StakingForceNewEraFn, SignedExtra, Address, Signature>;
+pub type StakingForceNewEraAlwaysXt<
+	SignedExtra,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> = UncheckedExtrinsicV4<StakingForceNewEraAlwaysFn, SignedExtra, Address, Signature>;
+pub type StakingForceNoEraXt<SignedExtra, Address = GenericAddress, Signature = MultiSignature> =
+	UncheckedExtrinsicV4<StakingForceNoEraFn, SignedExtra, Address, Signature>;
+pub type StakingSetPayeeXt<SignedExtra, Address = GenericAddress, Signature = MultiSignature> =
+	UncheckedExtrinsicV4<StakingSetPayeeFn, SignedExtra, Address, Signature>;
+pub type StakingSetValidatorCountXt<
+	SignedExtra,
+	Address = GenericAddress,
+	Signature = MultiSignature,
+> = UncheckedExtrinsicV4<StakingSetValidatorCountFn, SignedExtra, Address, Signature>;
 
 // https://polkadot.js.org/docs/substrate/extrinsics#staking
 impl<Signer, Client, Params, Runtime> Api<Signer, Client, Params, Runtime>
 where
-	Signer: Pair,
-	MultiSignature: From<Signer::Signature>,
-	MultiSigner: From<Signer::Public>,
+	Signer: SignExtrinsic<Runtime::AccountId>,
 	Client: Request,
 	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+	Params::OtherParams: WithEra<Runtime::Hash>,
 	Runtime: GetRuntimeBlockType + BalancesConfig + StakingConfig,
-	Runtime::AccountId: From<Signer::Public>,
 	Compact<Runtime::CurrencyBalance>: Encode,
 	Runtime::Header: DeserializeOwned,
 	Runtime::RuntimeBlock: DeserializeOwned,
@@ -106,16 +128,65 @@ where
 		controller: GenericAddress,
 		value: Runtime::CurrencyBalance,
 		payee: RewardDestination<GenericAddress>,
-	) -> StakingBondXt<Params::SignedExtra, Runtime::CurrencyBalance> {
-		compose_extrinsic!(self, STAKING_MODULE, STAKING_BOND, controller, Compact(value), payee)
+	) -> StakingBondXt<
+		Params::SignedExtra,
+		Runtime::CurrencyBalance,
+		Signer::ExtrinsicAddress,
+		Signer::Signature,
+	> {
+		self.try_staking_bond(controller, value, payee).unwrap()
+	}
+
+	/// Like [`Self::staking_bond`], but returns an error instead of panicking.
+	pub fn try_staking_bond(
+		&self,
+		controller: GenericAddress,
+		value: Runtime::CurrencyBalance,
+		payee: RewardDestination<GenericAddress>,
+	) -> ApiResult<
+		StakingBondXt<
+			Params::SignedExtra,
+			Runtime::CurrencyBalance,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(
+			self,
+			STAKING_MODULE,
+			STAKING_BOND,
+			controller,
+			Compact(value),
+			payee
+		)
 	}
 
 	/// Bonds extra funds from the stash's free balance to the balance for staking.
 	pub fn staking_bond_extra(
 		&self,
 		value: Runtime::CurrencyBalance,
-	) -> StakingBondExtraXt<Params::SignedExtra, Runtime::CurrencyBalance> {
-		compose_extrinsic!(self, STAKING_MODULE, STAKING_BOND_EXTRA, Compact(value))
+	) -> StakingBondExtraXt<
+		Params::SignedExtra,
+		Runtime::CurrencyBalance,
+		Signer::ExtrinsicAddress,
+		Signer::Signature,
+	> {
+		self.try_staking_bond_extra(value).unwrap()
+	}
+
+	/// Like [`Self::staking_bond_extra`], but returns an error instead of panicking.
+	pub fn try_staking_bond_extra(
+		&self,
+		value: Runtime::CurrencyBalance,
+	) -> ApiResult<
+		StakingBondExtraXt<
+			Params::SignedExtra,
+			Runtime::CurrencyBalance,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(self, STAKING_MODULE, STAKING_BOND_EXTRA, Compact(value))
 	}
 
 	/// Unbond `value` portion of the stash.
@@ -124,16 +195,56 @@ where
 	pub fn staking_unbond(
 		&self,
 		value: Runtime::CurrencyBalance,
-	) -> StakingUnbondXt<Params::SignedExtra, Runtime::CurrencyBalance> {
-		compose_extrinsic!(self, STAKING_MODULE, STAKING_UNBOND, Compact(value))
+	) -> StakingUnbondXt<
+		Params::SignedExtra,
+		Runtime::CurrencyBalance,
+		Signer::ExtrinsicAddress,
+		Signer::Signature,
+	> {
+		self.try_staking_unbond(value).unwrap()
+	}
+
+	/// Like [`Self::staking_unbond`], but returns an error instead of panicking.
+	pub fn try_staking_unbond(
+		&self,
+		value: Runtime::CurrencyBalance,
+	) -> ApiResult<
+		StakingUnbondXt<
+			Params::SignedExtra,
+			Runtime::CurrencyBalance,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(self, STAKING_MODULE, STAKING_UNBOND, Compact(value))
 	}
 
 	/// Rebond `value` portion of the current amount that is in the process of unbonding.
 	pub fn staking_rebond(
 		&self,
 		value: Runtime::CurrencyBalance,
-	) -> StakingRebondXt<Params::SignedExtra, Runtime::CurrencyBalance> {
-		compose_extrinsic!(self, STAKING_MODULE, STAKING_REBOND, Compact(value))
+	) -> StakingRebondXt<
+		Params::SignedExtra,
+		Runtime::CurrencyBalance,
+		Signer::ExtrinsicAddress,
+		Signer::Signature,
+	> {
+		self.try_staking_rebond(value).unwrap()
+	}
+
+	/// Like [`Self::staking_rebond`], but returns an error instead of panicking.
+	pub fn try_staking_rebond(
+		&self,
+		value: Runtime::CurrencyBalance,
+	) -> ApiResult<
+		StakingRebondXt<
+			Params::SignedExtra,
+			Runtime::CurrencyBalance,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(self, STAKING_MODULE, STAKING_REBOND, Compact(value))
 	}
 
 	/// Free the balance of the stash so the stash account can do whatever it wants.
@@ -142,8 +253,19 @@ where
 	pub fn staking_withdraw_unbonded(
 		&self,
 		num_slashing_spans: u32,
-	) -> StakingWithdrawUnbondedXt<Params::SignedExtra> {
-		compose_extrinsic!(self, STAKING_MODULE, STAKING_WITHDRAW_UNBONDED, num_slashing_spans)
+	) -> StakingWithdrawUnbondedXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>
+	{
+		self.try_staking_withdraw_unbonded(num_slashing_spans).unwrap()
+	}
+
+	/// Like [`Self::staking_withdraw_unbonded`], but returns an error instead of panicking.
+	pub fn try_staking_withdraw_unbonded(
+		&self,
+		num_slashing_spans: u32,
+	) -> ApiResult<
+		StakingWithdrawUnbondedXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>,
+	> {
+		try_compose_extrinsic!(self, STAKING_MODULE, STAKING_WITHDRAW_UNBONDED, num_slashing_spans)
 	}
 
 	/// Nominate `targets` as validators.
@@ -151,13 +273,33 @@ where
 	pub fn staking_nominate(
 		&self,
 		targets: Vec<GenericAddress>,
-	) -> StakingNominateXt<Params::SignedExtra> {
-		compose_extrinsic!(self, STAKING_MODULE, STAKING_NOMINATE, targets)
+	) -> StakingNominateXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature> {
+		self.try_staking_nominate(targets).unwrap()
+	}
+
+	/// Like [`Self::staking_nominate`], but returns an error instead of panicking.
+	pub fn try_staking_nominate(
+		&self,
+		targets: Vec<GenericAddress>,
+	) -> ApiResult<
+		StakingNominateXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>,
+	> {
+		try_compose_extrinsic!(self, STAKING_MODULE, STAKING_NOMINATE, targets)
 	}
 
 	/// Stop nominating por validating. Effects take place in the next era
-	pub fn staking_chill(&self) -> StakingChillXt<Params::SignedExtra> {
-		compose_extrinsic!(self, STAKING_MODULE, STAKING_CHILL)
+	pub fn staking_chill(
+		&self,
+	) -> StakingChillXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature> {
+		self.try_staking_chill().unwrap()
+	}
+
+	/// Like [`Self::staking_chill`], but returns an error instead of panicking.
+	pub fn try_staking_chill(
+		&self,
+	) -> ApiResult<StakingChillXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>>
+	{
+		try_compose_extrinsic!(self, STAKING_MODULE, STAKING_CHILL)
 	}
 
 	/// (Re-)set the controller of the stash
@@ -166,44 +308,136 @@ where
 	pub fn staking_set_controller(
 		&self,
 		controller: GenericAddress,
-	) -> StakingSetControllerXt<Params::SignedExtra> {
-		compose_extrinsic!(self, STAKING_MODULE, STAKING_SET_CONTROLLER, controller)
+	) -> StakingSetControllerXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature> {
+		self.try_staking_set_controller(controller).unwrap()
+	}
+
+	/// Like [`Self::staking_set_controller`], but returns an error instead of panicking.
+	pub fn try_staking_set_controller(
+		&self,
+		controller: GenericAddress,
+	) -> ApiResult<
+		StakingSetControllerXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>,
+	> {
+		try_compose_extrinsic!(self, STAKING_MODULE, STAKING_SET_CONTROLLER, controller)
 	}
 	/// Return the payout call for the given era
 	pub fn payout_stakers(
 		&self,
 		era: u32,
 		account: AccountId32,
-	) -> StakingPayoutStakersXt<Params::SignedExtra> {
+	) -> StakingPayoutStakersXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature> {
+		self.try_payout_stakers(era, account).unwrap()
+	}
+
+	/// Like [`Self::payout_stakers`], but returns an error instead of panicking.
+	pub fn try_payout_stakers(
+		&self,
+		era: u32,
+		account: AccountId32,
+	) -> ApiResult<
+		StakingPayoutStakersXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>,
+	> {
 		let value = PayoutStakers { validator_stash: account, era };
-		compose_extrinsic!(self, STAKING_MODULE, PAYOUT_STAKERS, value)
+		try_compose_extrinsic!(self, STAKING_MODULE, PAYOUT_STAKERS, value)
 	}
 
 	/// For New Era at the end of Next Session.
-	pub fn force_new_era(&self) -> StakingForceNewEraXt<Params::SignedExtra> {
-		compose_extrinsic!(self, STAKING_MODULE, FORCE_NEW_ERA, ForceEra {})
+	pub fn force_new_era(
+		&self,
+	) -> StakingForceNewEraXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature> {
+		self.try_force_new_era().unwrap()
+	}
+
+	/// Like [`Self::force_new_era`], but returns an error instead of panicking.
+	pub fn try_force_new_era(
+		&self,
+	) -> ApiResult<
+		StakingForceNewEraXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>,
+	> {
+		try_compose_extrinsic!(self, STAKING_MODULE, FORCE_NEW_ERA, ForceEra {})
 	}
 
 	/// Force there to be a new era at the end of sessions indefinitely.
-	pub fn force_new_era_always(&self) -> StakingForceNewEraAlwaysXt<Params::SignedExtra> {
-		compose_extrinsic!(self, STAKING_MODULE, FORCE_NEW_ERA_ALWAYS, ForceEra {})
+	pub fn force_new_era_always(
+		&self,
+	) -> StakingForceNewEraAlwaysXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>
+	{
+		self.try_force_new_era_always().unwrap()
+	}
+
+	/// Like [`Self::force_new_era_always`], but returns an error instead of panicking.
+	pub fn try_force_new_era_always(
+		&self,
+	) -> ApiResult<
+		StakingForceNewEraAlwaysXt<
+			Params::SignedExtra,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(self, STAKING_MODULE, FORCE_NEW_ERA_ALWAYS, ForceEra {})
 	}
 
 	/// Force there to be no new eras indefinitely.
-	pub fn force_no_era(&self) -> StakingForceNewEraAlwaysXt<Params::SignedExtra> {
-		compose_extrinsic!(self, STAKING_MODULE, FORCE_NO_ERA, ForceEra {})
+	pub fn force_no_era(
+		&self,
+	) -> StakingForceNewEraAlwaysXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>
+	{
+		self.try_force_no_era().unwrap()
+	}
+
+	/// Like [`Self::force_no_era`], but returns an error instead of panicking.
+	pub fn try_force_no_era(
+		&self,
+	) -> ApiResult<
+		StakingForceNewEraAlwaysXt<
+			Params::SignedExtra,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(self, STAKING_MODULE, FORCE_NO_ERA, ForceEra {})
 	}
 
 	/// Re-set the payment target for a controller.
-	pub fn set_payee(&self, payee: GenericAddress) -> StakingSetControllerXt<Params::SignedExtra> {
-		compose_extrinsic!(self, STAKING_MODULE, STAKING_SET_PAYEE, payee)
+	pub fn set_payee(
+		&self,
+		payee: GenericAddress,
+	) -> StakingSetControllerXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature> {
+		self.try_set_payee(payee).unwrap()
+	}
+
+	/// Like [`Self::set_payee`], but returns an error instead of panicking.
+	pub fn try_set_payee(
+		&self,
+		payee: GenericAddress,
+	) -> ApiResult<
+		StakingSetControllerXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>,
+	> {
+		try_compose_extrinsic!(self, STAKING_MODULE, STAKING_SET_PAYEE, payee)
 	}
 
 	/// Sets the number of validators.
 	pub fn set_validator_count(
 		&self,
 		count: u32,
-	) -> StakingSetValidatorCountXt<Params::SignedExtra> {
-		compose_extrinsic!(self, STAKING_MODULE, SET_VALIDATOR_COUNT, count)
+	) -> StakingSetValidatorCountXt<Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>
+	{
+		self.try_set_validator_count(count).unwrap()
+	}
+
+	/// Like [`Self::set_validator_count`], but returns an error instead of panicking.
+	pub fn try_set_validator_count(
+		&self,
+		count: u32,
+	) -> ApiResult<
+		StakingSetValidatorCountXt<
+			Params::SignedExtra,
+			Signer::ExtrinsicAddress,
+			Signer::Signature,
+		>,
+	> {
+		try_compose_extrinsic!(self, STAKING_MODULE, SET_VALIDATOR_COUNT, count)
 	}
 }
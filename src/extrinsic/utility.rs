@@ -18,44 +18,62 @@
 //! Extrinsics for `pallet-utility`.
 
 use super::common::Batch;
-use crate::{rpc::Request, Api};
-use ac_compose_macros::compose_extrinsic;
-use ac_primitives::{BalancesConfig, CallIndex, ExtrinsicParams, UncheckedExtrinsicV4};
+use crate::{rpc::Request, Api, ApiResult};
+use ac_compose_macros::try_compose_extrinsic;
+use ac_primitives::{
+	BalancesConfig, CallIndex, ExtrinsicParams, GenericAddress, SignExtrinsic,
+	UncheckedExtrinsicV4, WithEra,
+};
 use codec::Encode;
-use sp_core::Pair;
-use sp_runtime::{traits::GetRuntimeBlockType, MultiSignature, MultiSigner};
+use sp_runtime::{traits::GetRuntimeBlockType, MultiSignature};
 
-const UTILITY_MODULE: &str = "Utility";
-const UTILITY_BATCH: &str = "batch";
-const UTILITY_FORCE_BATCH: &str = "force_batch";
+pub const UTILITY_MODULE: &str = "Utility";
+pub const UTILITY_BATCH: &str = "batch";
+pub const UTILITY_FORCE_BATCH: &str = "force_batch";
 
 pub type UtilityBatchFn<Call> = (CallIndex, Batch<Call>);
-pub type UtilityBatchXt<Call, SignedExtra> =
-	UncheckedExtrinsicV4<UtilityBatchFn<Call>, SignedExtra>;
+pub type UtilityBatchXt<Call, SignedExtra, Address = GenericAddress, Signature = MultiSignature> =
+	UncheckedExtrinsicV4<UtilityBatchFn<Call>, SignedExtra, Address, Signature>;
 
 impl<Signer, Client, Params, Runtime> Api<Signer, Client, Params, Runtime>
 where
-	Signer: Pair,
-	MultiSignature: From<Signer::Signature>,
-	MultiSigner: From<Signer::Public>,
+	Signer: SignExtrinsic<Runtime::AccountId>,
 	Client: Request,
 	Params: ExtrinsicParams<Runtime::Index, Runtime::Hash>,
+	Params::OtherParams: WithEra<Runtime::Hash>,
 	Runtime: GetRuntimeBlockType + BalancesConfig,
-	Runtime::AccountId: From<Signer::Public>,
 {
 	pub fn batch<Call: Encode + Clone>(
 		&self,
 		calls: Vec<Call>,
-	) -> UtilityBatchXt<Call, Params::SignedExtra> {
+	) -> UtilityBatchXt<Call, Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature> {
+		self.try_batch(calls).unwrap()
+	}
+
+	pub fn try_batch<Call: Encode + Clone>(
+		&self,
+		calls: Vec<Call>,
+	) -> ApiResult<
+		UtilityBatchXt<Call, Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>,
+	> {
 		let calls = Batch { calls };
-		compose_extrinsic!(self, UTILITY_MODULE, UTILITY_BATCH, calls)
+		try_compose_extrinsic!(self, UTILITY_MODULE, UTILITY_BATCH, calls)
 	}
 
 	pub fn force_batch<Call: Encode + Clone>(
 		&self,
 		calls: Vec<Call>,
-	) -> UtilityBatchXt<Call, Params::SignedExtra> {
+	) -> UtilityBatchXt<Call, Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature> {
+		self.try_force_batch(calls).unwrap()
+	}
+
+	pub fn try_force_batch<Call: Encode + Clone>(
+		&self,
+		calls: Vec<Call>,
+	) -> ApiResult<
+		UtilityBatchXt<Call, Params::SignedExtra, Signer::ExtrinsicAddress, Signer::Signature>,
+	> {
 		let calls = Batch { calls };
-		compose_extrinsic!(self, UTILITY_MODULE, UTILITY_FORCE_BATCH, calls)
+		try_compose_extrinsic!(self, UTILITY_MODULE, UTILITY_FORCE_BATCH, calls)
 	}
 }
@@ -17,9 +17,11 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![feature(assert_matches)]
 
-pub use ac_compose_macros::{compose_call, compose_extrinsic_offline};
+pub use ac_compose_macros::{compose_call, compose_extrinsic_offline, try_compose_call};
 pub use ac_node_api::*;
 pub use ac_primitives::*;
+// re-export for the code generated by `pallet_call`
+pub use codec;
 
 pub mod utils;
 
@@ -30,7 +32,7 @@ pub use crate::api::*;
 #[cfg(feature = "std")]
 pub use crate::rpc::*;
 #[cfg(feature = "std")]
-pub use ac_compose_macros::compose_extrinsic;
+pub use ac_compose_macros::{compose_extrinsic, pallet_call, try_compose_extrinsic};
 
 #[cfg(feature = "std")]
 pub mod api;
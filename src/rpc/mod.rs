@@ -32,8 +32,10 @@ pub use jsonrpsee_client::*;
 pub mod jsonrpsee_client;
 
 pub mod error;
+pub mod snapshot_client;
 
 pub use error::*;
+pub use snapshot_client::*;
 
 use ac_primitives::RpcParams;
 use serde::de::DeserializeOwned;
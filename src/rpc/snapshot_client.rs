@@ -0,0 +1,332 @@
+/*
+   Copyright 2019 Supercomputing Systems AG
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+	   http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+*/
+
+//! A state snapshot of a single block and a client serving storage and metadata requests
+//! from it, such that code relying on `GetStorage` can be run against reproduced state offline.
+
+use crate::rpc::{Error as RpcClientError, Request, Result};
+use ac_primitives::RpcParams;
+use codec::{Decode, Encode, IoReader};
+use core::fmt::Debug;
+use log::*;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use sp_core::{
+	storage::{StorageData, StorageKey},
+	Bytes,
+};
+use sp_version::RuntimeVersion;
+use std::{
+	collections::BTreeMap,
+	fs::File,
+	io::{BufReader, BufWriter, Read, Write},
+	path::Path,
+};
+
+/// Information about the block a snapshot has been taken at.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct SnapshotHeader<Hash> {
+	pub block_hash: Hash,
+	pub state_root: Hash,
+	pub genesis_hash: Hash,
+	pub runtime_version: RuntimeVersion,
+	/// SCALE encoded `RuntimeMetadataPrefixed`, as returned by `state_getMetadata`.
+	pub metadata: Vec<u8>,
+}
+
+/// The storage below some chosen prefixes at a single block.
+///
+/// The snapshot is stored as the SCALE encoded header, followed by the
+/// SCALE encoded key-value pairs, sorted by key.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct StateSnapshot<Hash> {
+	pub header: SnapshotHeader<Hash>,
+	pub storage: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl<Hash: Encode + Decode> StateSnapshot<Hash> {
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+		self.using_encoded(|bytes| writer.write_all(bytes))?;
+		Ok(())
+	}
+
+	pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+		Self::decode(&mut IoReader(reader)).map_err(|e| RpcClientError::Client(Box::new(e)))
+	}
+
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+		let mut writer = BufWriter::new(File::create(path)?);
+		self.write_to(&mut writer)?;
+		writer.flush()?;
+		Ok(())
+	}
+
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+		Self::read_from(&mut BufReader::new(File::open(path)?))
+	}
+}
+
+/// Client answering requests from a [`StateSnapshot`] instead of a node.
+///
+/// Supported are `state_getStorage`, `state_queryStorageAt`, `state_getKeysPaged`,
+/// `state_getMetadata`, `state_getRuntimeVersion` and `chain_getBlockHash` (genesis and latest
+/// block only), which is enough to create an `Api` and query its storage, also by prefix.
+/// Requests at any other block than the one the snapshot has been taken at are rejected.
+#[derive(Debug, Clone)]
+pub struct SnapshotClient<Hash> {
+	snapshot: StateSnapshot<Hash>,
+}
+
+impl<Hash> SnapshotClient<Hash>
+where
+	Hash: Encode + Decode,
+{
+	pub fn new(snapshot: StateSnapshot<Hash>) -> Self {
+		Self { snapshot }
+	}
+
+	pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+		Ok(Self::new(StateSnapshot::load(path)?))
+	}
+
+	pub fn snapshot(&self) -> &StateSnapshot<Hash> {
+		&self.snapshot
+	}
+}
+
+impl<Hash> Request for SnapshotClient<Hash>
+where
+	Hash: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+	fn request<R: DeserializeOwned>(&self, method: &str, params: RpcParams) -> Result<R> {
+		let params = match params.to_json_value()? {
+			Value::Array(params) => params,
+			params => vec![params],
+		};
+		debug!("Snapshot request {} with params {:?}", method, params);
+		let header = &self.snapshot.header;
+
+		let response = match method {
+			"state_getStorage" => {
+				self.ensure_snapshot_block(param(&params, 1)?)?;
+				let key: StorageKey = param(&params, 0)?.ok_or_else(|| missing_param(method))?;
+				let data = self.snapshot.storage.get(&key.0).cloned().map(StorageData);
+				serde_json::to_value(data)?
+			},
+			"state_queryStorageAt" => {
+				self.ensure_snapshot_block(param(&params, 1)?)?;
+				let keys: Vec<StorageKey> =
+					param(&params, 0)?.ok_or_else(|| missing_param(method))?;
+				let changes: Vec<(StorageKey, Option<StorageData>)> = keys
+					.into_iter()
+					.map(|key| {
+						let data = self.snapshot.storage.get(&key.0).cloned().map(StorageData);
+						(key, data)
+					})
+					.collect();
+				// A single `StorageChangeSet`, at the block of the snapshot.
+				json!([{ "block": header.block_hash, "changes": changes }])
+			},
+			"state_getKeysPaged" => {
+				self.ensure_snapshot_block(param(&params, 3)?)?;
+				let prefix: StorageKey = param(&params, 0)?.unwrap_or(StorageKey(vec![]));
+				let count: u32 = param(&params, 1)?.ok_or_else(|| missing_param(method))?;
+				let start_key: Option<StorageKey> = param(&params, 2)?;
+				let keys: Vec<StorageKey> = self
+					.snapshot
+					.storage
+					.range(prefix.0.clone()..)
+					.map(|(key, _)| key)
+					.take_while(|key| key.starts_with(&prefix.0))
+					.filter(|key| start_key.as_ref().map_or(true, |start| **key > start.0))
+					.take(count as usize)
+					.map(|key| StorageKey(key.clone()))
+					.collect();
+				serde_json::to_value(keys)?
+			},
+			"state_getMetadata" => {
+				self.ensure_snapshot_block(param(&params, 0)?)?;
+				serde_json::to_value(Bytes(header.metadata.clone()))?
+			},
+			"state_getRuntimeVersion" => {
+				self.ensure_snapshot_block(param(&params, 0)?)?;
+				serde_json::to_value(&header.runtime_version)?
+			},
+			"chain_getBlockHash" => match param::<u64>(&params, 0)? {
+				None => serde_json::to_value(&header.block_hash)?,
+				Some(0) => serde_json::to_value(&header.genesis_hash)?,
+				Some(_) => Value::Null,
+			},
+			_ =>
+				return Err(RpcClientError::Client(
+					format!("Method {} is not supported by the snapshot client", method).into(),
+				)),
+		};
+		Ok(serde_json::from_value(response)?)
+	}
+}
+
+impl<Hash> SnapshotClient<Hash>
+where
+	Hash: PartialEq + Debug,
+{
+	fn ensure_snapshot_block(&self, at_block: Option<Hash>) -> Result<()> {
+		match at_block {
+			Some(hash) if hash != self.snapshot.header.block_hash => Err(RpcClientError::Client(
+				format!(
+					"Snapshot only contains the state of block {:?}, requested: {:?}",
+					self.snapshot.header.block_hash, hash
+				)
+				.into(),
+			)),
+			_ => Ok(()),
+		}
+	}
+}
+
+/// Returns the deserialized parameter at `index`, or `None` if it is missing or `null`.
+fn param<T: DeserializeOwned>(params: &[Value], index: usize) -> Result<Option<T>> {
+	match params.get(index) {
+		None | Some(Value::Null) => Ok(None),
+		Some(value) => Ok(Some(serde_json::from_value(value.clone())?)),
+	}
+}
+
+fn missing_param(method: &str) -> RpcClientError {
+	RpcClientError::Client(format!("Missing parameter for {}", method).into())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ac_compose_macros::rpc_params;
+	use sp_core::{storage::StorageChangeSet, H256};
+
+	fn test_snapshot() -> StateSnapshot<H256> {
+		let header = SnapshotHeader {
+			block_hash: H256::repeat_byte(1),
+			state_root: H256::repeat_byte(2),
+			genesis_hash: H256::repeat_byte(3),
+			runtime_version: RuntimeVersion::default(),
+			metadata: vec![4, 5, 6],
+		};
+		let storage = BTreeMap::from([
+			(vec![1, 1], vec![11]),
+			(vec![1, 2], vec![12]),
+			(vec![1, 3], vec![13]),
+			(vec![2, 1], vec![21]),
+		]);
+		StateSnapshot { header, storage }
+	}
+
+	#[test]
+	fn snapshot_encoding_roundtrip_works() {
+		let snapshot = test_snapshot();
+		let mut bytes = Vec::new();
+		snapshot.write_to(&mut bytes).unwrap();
+
+		let decoded = StateSnapshot::<H256>::read_from(&mut bytes.as_slice()).unwrap();
+		assert_eq!(decoded, snapshot);
+	}
+
+	#[test]
+	fn snapshot_client_serves_storage() {
+		let client = SnapshotClient::new(test_snapshot());
+		let block_hash = H256::repeat_byte(1);
+
+		let data: Option<StorageData> = client
+			.request("state_getStorage", rpc_params![StorageKey(vec![1, 2]), Some(block_hash)])
+			.unwrap();
+		assert_eq!(data, Some(StorageData(vec![12])));
+
+		let data: Option<StorageData> =
+			client.request("state_getStorage", rpc_params![StorageKey(vec![3])]).unwrap();
+		assert_eq!(data, None);
+
+		let other_block = Some(H256::repeat_byte(9));
+		assert!(client
+			.request::<Option<StorageData>>(
+				"state_getStorage",
+				rpc_params![StorageKey(vec![1, 2]), other_block]
+			)
+			.is_err());
+	}
+
+	#[test]
+	fn snapshot_client_serves_storage_at_block() {
+		let client = SnapshotClient::new(test_snapshot());
+		let block_hash = H256::repeat_byte(1);
+		let keys = vec![StorageKey(vec![1, 2]), StorageKey(vec![3])];
+
+		let change_sets: Vec<StorageChangeSet<H256>> = client
+			.request("state_queryStorageAt", rpc_params![keys.clone(), block_hash])
+			.unwrap();
+		assert_eq!(
+			change_sets,
+			vec![StorageChangeSet {
+				block: block_hash,
+				changes: vec![
+					(StorageKey(vec![1, 2]), Some(StorageData(vec![12]))),
+					(StorageKey(vec![3]), None)
+				],
+			}]
+		);
+
+		let other_block = H256::repeat_byte(9);
+		assert!(client
+			.request::<Vec<StorageChangeSet<H256>>>(
+				"state_queryStorageAt",
+				rpc_params![keys, other_block]
+			)
+			.is_err());
+	}
+
+	#[test]
+	fn snapshot_client_serves_keys_paged() {
+		let client = SnapshotClient::new(test_snapshot());
+		let prefix = Some(StorageKey(vec![1]));
+
+		let keys: Vec<StorageKey> = client
+			.request(
+				"state_getKeysPaged",
+				rpc_params![prefix.clone(), 2, Option::<StorageKey>::None],
+			)
+			.unwrap();
+		assert_eq!(keys, vec![StorageKey(vec![1, 1]), StorageKey(vec![1, 2])]);
+
+		let keys: Vec<StorageKey> = client
+			.request("state_getKeysPaged", rpc_params![prefix, 2, Some(StorageKey(vec![1, 2]))])
+			.unwrap();
+		assert_eq!(keys, vec![StorageKey(vec![1, 3])]);
+	}
+
+	#[test]
+	fn snapshot_client_serves_metadata_and_block_hashes() {
+		let client = SnapshotClient::new(test_snapshot());
+
+		let metadata: Bytes = client.request("state_getMetadata", rpc_params![]).unwrap();
+		assert_eq!(metadata.0, vec![4, 5, 6]);
+
+		let genesis_hash: Option<H256> =
+			client.request("chain_getBlockHash", rpc_params![Some(0)]).unwrap();
+		assert_eq!(genesis_hash, Some(H256::repeat_byte(3)));
+
+		let block_hash: Option<H256> =
+			client.request("chain_getBlockHash", rpc_params![Option::<u32>::None]).unwrap();
+		assert_eq!(block_hash, Some(H256::repeat_byte(1)));
+	}
+}
@@ -29,6 +29,11 @@ pub fn storage_key(module: &str, storage_key_name: &str) -> StorageKey {
 	StorageKey(key)
 }
 
+/// Returns the prefix of all storage keys of the given module.
+pub fn pallet_storage_prefix(module: &str) -> StorageKey {
+	StorageKey(twox_128(module.as_bytes()).to_vec())
+}
+
 pub trait FromHexString {
 	fn from_hex(hex: String) -> Result<Self, hex::FromHexError>
 	where
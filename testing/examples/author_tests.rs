@@ -15,12 +15,21 @@
 
 //! Tests for the author rpc interface functions.
 
-use kitchensink_runtime::Runtime;
+use codec::{Compact, Encode};
+use kitchensink_runtime::{Runtime, RuntimeCall};
+use sp_core::{sr25519, Bytes, Pair, H256};
 use sp_keyring::AccountKeyring;
+use sp_runtime::generic::Era;
 use std::{thread, time::Duration};
 use substrate_api_client::{
-	rpc::JsonrpseeClient, Api, AssetTipExtrinsicParams, HandleSubscription, MultiAddress,
-	SubmitAndWatch, SubmitExtrinsic, TransactionStatus, XtStatus,
+	compose_call, compose_extrinsic_offline,
+	decoder::{Composite, Value},
+	rpc::JsonrpseeClient,
+	Api, AssetTip, AssetTipExtrinsicParams, DynamicExtrinsicParams, ExtrinsicParams,
+	GenericAddress, HandleSubscription, ManageKeys, ManageTransactionPool, Mortality, MultiAddress,
+	MultiSignature, ResubmissionPolicy, SignExtrinsic, SigningRequest, SigningResponse,
+	SubmitAndWatch, SubmitAndWatchUntilSuccess, SubmitAndWatchWithResubmission, SubmitExtrinsic,
+	TransactionStatus, UncheckedExtrinsicV4, XtStatus,
 };
 
 #[tokio::main]
@@ -83,7 +92,7 @@ async fn main() {
 
 	let api3 = api.clone();
 	thread::sleep(Duration::from_secs(6)); // Wait a little to avoid transaction too low priority error.
-	let xt5 = api.balance_transfer(bob, 1000).hex_encode();
+	let xt5 = api.balance_transfer(bob.clone(), 1000).hex_encode();
 	let until_finalized_handle = thread::spawn(move || {
 		let _block_hash = api3
 			.submit_and_watch_extrinsic_until(&xt5, XtStatus::Finalized)
@@ -92,6 +101,136 @@ async fn main() {
 		println!("Success: submit_and_watch_extrinsic_until Finalized");
 	});
 
+	thread::sleep(Duration::from_secs(6)); // Wait a little to avoid transaction too low priority error.
+	let xt6 = api.balance_transfer(bob.clone(), 1000).hex_encode();
+	let report = api.submit_and_watch_extrinsic_until_success(&xt6, false).unwrap();
+	assert!(report.is_success());
+	assert!(report.actual_fee.is_some());
+	assert!(report
+		.events
+		.iter()
+		.any(|event| event.pallet_name() == "Balances" && event.variant_name() == "Transfer"));
+	println!("Success: submit_and_watch_extrinsic_until_success");
+
+	thread::sleep(Duration::from_secs(6)); // Wait a little to avoid transaction too low priority error.
+	let call =
+		compose_call!(api.metadata(), "Balances", "transfer", bob.clone(), Compact(1000u128));
+	let policy = ResubmissionPolicy::new(3, 16).tip(|attempt| AssetTip::new(100 * attempt as u128));
+	let report = api.submit_and_watch_call_with_resubmission(call, &policy).unwrap();
+	assert!(report.is_success());
+	assert!(report.block_hash().is_some());
+	println!(
+		"Success: submit_and_watch_call_with_resubmission after {} attempts",
+		report.attempts.len()
+	);
+
+	thread::sleep(Duration::from_secs(6)); // Wait a little to avoid transaction too low priority error.
+	let mut mortal_api = api.clone();
+	mortal_api.set_mortality(Mortality::Mortal { period: 32 });
+	let xt7 = mortal_api.balance_transfer(bob.clone(), 1000);
+	assert!(matches!(xt7.signature.as_ref().unwrap().2.era, Era::Mortal(32, _)));
+	let _block_hash = mortal_api
+		.submit_and_watch_extrinsic_until(&xt7.hex_encode(), XtStatus::InBlock)
+		.unwrap();
+	println!("Success: submit mortal extrinsic");
+
+	// Signed extensions as listed in the metadata of the runtime, the api creates the params
+	// builder for them. Signed by another account, as the nonces of Alice are managed by the
+	// first api.
+	let dynamic_client = JsonrpseeClient::with_default_url().unwrap();
+	let mut dynamic_api =
+		Api::<_, _, DynamicExtrinsicParams<AssetTip<u128>, u32, H256>, Runtime>::new(
+			dynamic_client,
+		)
+		.unwrap();
+	dynamic_api.set_signer(AccountKeyring::Charlie.pair());
+	let xt8 = dynamic_api.balance_transfer(bob.clone(), 1000);
+	let _block_hash = dynamic_api
+		.submit_and_watch_extrinsic_until(&xt8.hex_encode(), XtStatus::InBlock)
+		.unwrap();
+	println!("Success: submit extrinsic with dynamic signed extensions");
+
+	// Offline signing: Only the signing request and the signature are exchanged with the signer.
+	let call =
+		compose_call!(api.metadata(), "Balances", "transfer", bob.clone(), Compact(1000u128));
+	let nonce = api.next_nonce().unwrap();
+	let params = api.extrinsic_params(nonce);
+	let request =
+		SigningRequest::new(&AccountKeyring::Alice.pair().extrinsic_address(), &call, &params);
+	let request_json = request.to_json().unwrap();
+	let response_json = SigningRequest::from_json(&request_json)
+		.unwrap()
+		.sign(&AccountKeyring::Alice.pair())
+		.unwrap()
+		.to_json()
+		.unwrap();
+	let response = SigningResponse::from_json(&response_json).unwrap();
+	let xt9: UncheckedExtrinsicV4<_, _> =
+		request.extrinsic(call, params.signed_extra(), &response).unwrap();
+	api.nonce_manager().track_extrinsic(nonce, &xt9.encode());
+	let _block_hash = api
+		.submit_and_watch_extrinsic_until(&xt9.hex_encode(), XtStatus::InBlock)
+		.unwrap();
+	println!("Success: submit offline signed extrinsic");
+
+	thread::sleep(Duration::from_secs(6)); // Wait a little to avoid transaction too low priority error.
+	let call = compose_call!(api.metadata(), "Balances", "transfer", bob, Compact(1000u128));
+	let xt10 = api.compose_unchecked_extrinsic(call).unwrap();
+	assert!(xt10.is_signed());
+	let _block_hash = api
+		.submit_and_watch_extrinsic_until(&xt10.hex_encode(), XtStatus::InBlock)
+		.unwrap();
+	println!("Success: submit extrinsic of the runtime's format, v{}", api.extrinsic_version());
+
+	thread::sleep(Duration::from_secs(6)); // Wait a little to avoid transaction too low priority error.
+	let dest =
+		Value::unnamed_variant("Id", vec![Value::from_bytes(AccountKeyring::Bob.to_account_id())]);
+	let fields =
+		Composite::Named(vec![("dest".into(), dest), ("value".into(), Value::uint(1000u128))]);
+	let call = api.metadata().encode_dynamic_call("Balances", "transfer", fields).unwrap();
+	let xt11 = api.compose_unchecked_extrinsic(call).unwrap();
+	let _block_hash = api
+		.submit_and_watch_extrinsic_until(&xt11.hex_encode(), XtStatus::InBlock)
+		.unwrap();
+	println!("Success: submit dynamically composed call checked against the metadata");
+
+	let pending_extrinsics = api
+		.get_pending_extrinsics::<GenericAddress, RuntimeCall, MultiSignature>()
+		.unwrap();
+	println!("Success: get_pending_extrinsics, {} pending", pending_extrinsics.len());
+
+	// An extrinsic with a nonce far in the future stays in the pool until it is removed.
+	let future_nonce = api.get_nonce().unwrap() + 100;
+	let call = compose_call!(
+		api.metadata(),
+		"Balances",
+		"transfer",
+		MultiAddress::Id(AccountKeyring::Bob.to_account_id()),
+		Compact(1000u128)
+	);
+	let future_xt =
+		compose_extrinsic_offline!(api.signer().unwrap(), call, api.extrinsic_params(future_nonce));
+	let _tx_hash = api.submit_extrinsic(future_xt.hex_encode()).unwrap();
+	let removed = api
+		.remove_extrinsic_by_tag::<GenericAddress, MultiSignature>(
+			&AccountKeyring::Alice.to_account_id(),
+			future_nonce,
+		)
+		.unwrap();
+	assert_eq!(removed.len(), 1);
+	println!("Success: remove_extrinsic_by_tag");
+
+	let (key_pair, suri, _) = sr25519::Pair::generate_with_phrase(None);
+	let public_key = Bytes(key_pair.public().0.to_vec());
+	assert!(!api.has_key(public_key.clone(), "babe").unwrap());
+	api.insert_key("babe", &suri, public_key.clone()).unwrap();
+	assert!(api.has_key(public_key, "babe").unwrap());
+	println!("Success: insert_key and has_key");
+
+	let session_keys = api.rotate_keys().unwrap();
+	assert!(api.has_session_keys(session_keys).unwrap());
+	println!("Success: rotate_keys");
+
 	watch_handle.join().unwrap();
 	until_in_block_handle.join().unwrap();
 	until_finalized_handle.join().unwrap();
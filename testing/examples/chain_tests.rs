@@ -15,6 +15,7 @@
 
 //! Tests for the chain rpc interface functions.
 
+use codec::Encode;
 use kitchensink_runtime::Runtime;
 use sp_keyring::AccountKeyring;
 use substrate_api_client::{
@@ -41,12 +42,19 @@ async fn main() {
 	let _block = api.get_block(None).unwrap().unwrap();
 	let _block = api.get_block(Some(block_hash)).unwrap().unwrap();
 	let _block = api.get_block_by_num(None).unwrap().unwrap();
-	let _block = api.get_block_by_num(Some(2)).unwrap().unwrap();
+	let block = api.get_block_by_num(Some(2)).unwrap().unwrap();
 	let _signed_block = api.get_signed_block(None).unwrap().unwrap();
 	let _signed_block = api.get_signed_block(Some(block_hash)).unwrap().unwrap();
 	let _signed_block = api.get_signed_block_by_num(None).unwrap().unwrap();
 	let _signed_block = api.get_signed_block_by_num(Some(1)).unwrap().unwrap();
 
+	// Decode the extrinsics of a block with the metadata only. Every block starts with
+	// the unsigned timestamp inherent.
+	let timestamp = api.metadata().decode_extrinsic(&block.extrinsics[0].encode()).unwrap();
+	assert!(!timestamp.is_signed());
+	assert_eq!(timestamp.call.pallet_name, "Timestamp");
+	assert_eq!(timestamp.call.call_name, "set");
+
 	// Subscription
 	let mut finalized_head_subscription = api.subscribe_finalized_heads().unwrap();
 	let _some_head = finalized_head_subscription.next().unwrap().unwrap();
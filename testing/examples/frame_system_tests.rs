@@ -15,13 +15,15 @@
 
 //! Tests for the frame system interface functions.
 
-use codec::Decode;
+use codec::{Compact, Decode};
 use frame_support::dispatch::DispatchInfo;
 use kitchensink_runtime::Runtime;
 use sp_keyring::AccountKeyring;
+use sp_runtime::transaction_validity::{InvalidTransaction, TransactionValidityError};
 use substrate_api_client::{
-	rpc::JsonrpseeClient, Api, AssetTipExtrinsicParams, GetAccountInformation, StaticEvent,
-	SubscribeEvents, SubscribeFrameSystem,
+	compose_call, compose_extrinsic_offline, rpc::JsonrpseeClient, Api, AssetTipExtrinsicParams,
+	DispatchError, DryRun, DryRunResult, GetAccountInformation, GetMetadataHash, MultiAddress,
+	StaticEvent, SubscribeEvents, SubscribeFrameSystem,
 };
 
 /// Check out frame_system::Event::ExtrinsicSuccess:
@@ -49,6 +51,37 @@ async fn main() {
 	let _account_info = api.get_account_info(&alice).unwrap().unwrap();
 	let _account_data = api.get_account_data(&alice).unwrap().unwrap();
 
+	// DryRun
+	let signer = AccountKeyring::Alice.pair();
+	let bob = MultiAddress::Id(AccountKeyring::Bob.to_account_id());
+	let nonce = api.get_nonce().unwrap();
+	let call =
+		compose_call!(api.metadata(), "Balances", "transfer", bob.clone(), Compact(1000u128));
+	let xt = compose_extrinsic_offline!(signer, call.clone(), api.extrinsic_params(nonce));
+	assert!(api.dry_run(&xt.hex_encode(), None).unwrap().is_success());
+
+	let xt = compose_extrinsic_offline!(signer, call, api.extrinsic_params(nonce + 100));
+	match api.dry_run(&xt.hex_encode(), None).unwrap() {
+		DryRunResult::TransactionValidityError(TransactionValidityError::Invalid(
+			InvalidTransaction::Future,
+		)) => {},
+		result => panic!("Expected a future nonce, got {:?}", result),
+	}
+
+	let call = compose_call!(api.metadata(), "Balances", "transfer", bob, Compact(u128::MAX));
+	let xt = compose_extrinsic_offline!(signer, call, api.extrinsic_params(nonce));
+	match api.dry_run(&xt.hex_encode(), None).unwrap() {
+		DryRunResult::DispatchError(DispatchError::Module(module_error)) =>
+			assert_eq!(module_error.pallet, "Balances"),
+		result => panic!("Expected a balances error, got {:?}", result),
+	}
+	println!("Success: Dry run");
+
+	let metadata_hash_info = api.get_metadata_hash_info().unwrap();
+	assert_eq!(metadata_hash_info.spec_version, api.runtime_version().spec_version);
+	let _metadata_hash = api.get_metadata_hash().unwrap();
+	println!("Success: Get metadata hash");
+
 	// Subscribe
 	let mut event_subscription = api.subscribe_system_events().unwrap();
 	let _event: ExtrinsicSuccess = api.wait_for_event(&mut event_subscription).unwrap();
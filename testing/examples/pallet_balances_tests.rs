@@ -15,9 +15,13 @@
 
 //! Tests for the pallet balances interface functions.
 
+use codec::Compact;
 use kitchensink_runtime::Runtime;
 use sp_keyring::AccountKeyring;
-use substrate_api_client::{rpc::JsonrpseeClient, Api, AssetTipExtrinsicParams, GetBalance};
+use substrate_api_client::{
+	rpc::JsonrpseeClient, try_compose_call, Api, AssetTipExtrinsicParams, GetBalance,
+	MetadataError, MultiAddress,
+};
 
 #[tokio::main]
 async fn main() {
@@ -28,4 +32,12 @@ async fn main() {
 	api.set_signer(alice_pair);
 
 	let _ed = api.get_existential_deposit().unwrap();
+
+	// Fallible composing.
+	let bob = MultiAddress::Id(AccountKeyring::Bob.to_account_id());
+	let _xt = api.try_balance_transfer(bob.clone(), 1000).unwrap();
+	let result = try_compose_call!(api.metadata(), "Balances", "transfr", bob, Compact(1000u128));
+	assert!(matches!(result, Err(MetadataError::CallNotFound(call)) if call == "transfr"));
+	let result = try_compose_call!(api.metadata(), "Balance", "transfer");
+	assert!(matches!(result, Err(MetadataError::PalletNotFound(_))));
 }
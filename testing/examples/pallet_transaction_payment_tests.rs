@@ -15,11 +15,12 @@
 
 //! Tests for the pallet transaction payment interface functions.
 
+use codec::Compact;
 use kitchensink_runtime::Runtime;
 use sp_keyring::AccountKeyring;
 use substrate_api_client::{
-	rpc::JsonrpseeClient, Api, AssetTipExtrinsicParams, GenericAddress, GetBlock,
-	GetTransactionPayment,
+	compose_call, rpc::JsonrpseeClient, Api, AssetTipExtrinsicParams, EstimateFee, GenericAddress,
+	GetBlock, GetTransactionPayment,
 };
 
 #[tokio::main]
@@ -33,9 +34,26 @@ async fn main() {
 	let bob = AccountKeyring::Bob.to_account_id();
 
 	let block_hash = api.get_block_hash(None).unwrap().unwrap();
-	let xthex_prefixed = api.balance_transfer(GenericAddress::Id(bob), 1000000000000).hex_encode();
+	let xthex_prefixed = api
+		.balance_transfer(GenericAddress::Id(bob.clone()), 1000000000000)
+		.hex_encode();
 
 	// Tests
 	let _fee_details = api.get_fee_details(&xthex_prefixed, Some(block_hash)).unwrap().unwrap();
 	let _payment_info = api.get_payment_info(&xthex_prefixed, Some(block_hash)).unwrap().unwrap();
+
+	let call = compose_call!(
+		api.metadata(),
+		"Balances",
+		"transfer",
+		GenericAddress::Id(bob),
+		Compact(1000000000000u128)
+	);
+	let payment_info = api.get_payment_info(&xthex_prefixed, None).unwrap().unwrap();
+	let estimate = api.estimate_fee(call.clone()).unwrap().unwrap();
+	assert_eq!(estimate.partial_fee, payment_info.partial_fee);
+	assert_eq!(estimate.weight, payment_info.weight);
+
+	let batch_estimate = api.estimate_batch_fee(vec![call.clone(), call]).unwrap().unwrap();
+	assert!(batch_estimate.partial_fee > estimate.partial_fee);
 }
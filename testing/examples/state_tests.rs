@@ -23,11 +23,16 @@ use sp_core::{crypto::Ss58Codec, sr25519};
 use sp_keyring::AccountKeyring;
 use sp_staking::EraIndex;
 use substrate_api_client::{
-	rpc::JsonrpseeClient, Api, AssetTipExtrinsicParams, GetBlock, GetStorage,
+	decoder::Value,
+	rpc::{JsonrpseeClient, SnapshotClient, StateSnapshot},
+	utils::pallet_storage_prefix,
+	Api, AssetTipExtrinsicParams, ExportSnapshot, GetBlock, GetStorage, GetStorageDiff,
+	StorageAddress, SubscribeState,
 };
 
 type Balance = <Runtime as pallet_balances::Config>::Balance;
 type AccountData = GenericAccountData<Balance>;
+type AccountInfo = frame_system::AccountInfo<<Runtime as frame_system::Config>::Index, AccountData>;
 type ErasStakers = Exposure<
 	<Runtime as frame_system::Config>::AccountId,
 	<Runtime as pallet_staking::Config>::CurrencyBalance,
@@ -90,4 +95,90 @@ async fn main() {
 		api.get_storage_proof_by_keys(vec![storage_key.clone()], None).unwrap().unwrap();
 	let _keys = api.get_keys(storage_key, None).unwrap().unwrap();
 	let _constants: Balance = api.get_constant("Balances", "ExistentialDeposit").unwrap();
+
+	// Dynamic storage queries:
+	let _total_issuance = api
+		.get_storage_dynamic("Balances", "TotalIssuance", vec![], None)
+		.unwrap()
+		.unwrap();
+	let _account_info = api
+		.get_storage_dynamic("System", "Account", vec![Value::from_bytes(&alice)], None)
+		.unwrap()
+		.unwrap();
+	let _era_stakers = api
+		.get_storage_dynamic(
+			"Staking",
+			"ErasStakers",
+			vec![Value::uint(EraIndex::default()), Value::from_bytes(alice_stash)],
+			None,
+		)
+		.unwrap()
+		.unwrap();
+
+	// Typed storage addresses, validated once against the metadata:
+	let total_issuance_address =
+		StorageAddress::<(), Balance>::value(api.metadata(), "Balances", "TotalIssuance").unwrap();
+	let account_address =
+		StorageAddress::<_, AccountInfo>::map(api.metadata(), "System", "Account").unwrap();
+	let era_stakers_address =
+		StorageAddress::<(EraIndex, sr25519::Public), ErasStakers>::double_map(
+			api.metadata(),
+			"Staking",
+			"ErasStakers",
+		)
+		.unwrap();
+	assert!(StorageAddress::<(), u8>::value(api.metadata(), "Balances", "TotalIssuance").is_err());
+	let _total_issuance =
+		api.get_storage_by_address(&total_issuance_address, &(), None).unwrap().unwrap();
+	let _account_info =
+		api.get_storage_by_address(&account_address, &alice, None).unwrap().unwrap();
+	let _era_stakers = api
+		.get_storage_by_address(&era_stakers_address, &(EraIndex::default(), alice_stash), None)
+		.unwrap()
+		.unwrap();
+	let _account_proof = api
+		.get_storage_proof_by_address(&account_address, &alice, None)
+		.unwrap()
+		.unwrap();
+	let accounts = api.get_storage_entries_by_address(&account_address, None).unwrap();
+	assert!(accounts.iter().any(|(key, _)| key == &account_address.key(&alice)));
+
+	// Subscribe to several storage keys at once:
+	let number_key = api.metadata().storage_value_key("System", "Number").unwrap();
+	let event_count_key = api.metadata().storage_value_key("System", "EventCount").unwrap();
+	let mut subscription =
+		api.subscribe_storage::<u32>(vec![number_key.clone(), event_count_key]).unwrap();
+	assert_eq!(subscription.keys().len(), 2);
+	let (_block_hash, changes) = subscription.next().unwrap().unwrap();
+	assert!(changes.iter().any(|(key, value)| key == &number_key && value.is_some()));
+	subscription.unsubscribe().unwrap();
+
+	// Compare the storage of a pallet between two blocks:
+	let genesis_hash = api.get_block_hash(Some(0)).unwrap().unwrap();
+	let diff = api
+		.get_storage_diff(pallet_storage_prefix("System"), genesis_hash, block_hash)
+		.unwrap();
+	assert!(!diff.is_empty());
+	let _diff_json = serde_json::to_string(&diff).unwrap();
+
+	// Export a snapshot of the balances storage and query it offline:
+	let snapshot = api
+		.export_snapshot(vec![pallet_storage_prefix("Balances")], block_hash)
+		.unwrap();
+	let mut snapshot_bytes = Vec::new();
+	snapshot.write_to(&mut snapshot_bytes).unwrap();
+	let snapshot = StateSnapshot::read_from(&mut snapshot_bytes.as_slice()).unwrap();
+	let snapshot_api = Api::<sr25519::Pair, _, AssetTipExtrinsicParams<Runtime>, Runtime>::new(
+		SnapshotClient::new(snapshot),
+	)
+	.unwrap();
+	let snapshot_total_issuance: Balance = snapshot_api
+		.get_storage_value("Balances", "TotalIssuance", None)
+		.unwrap()
+		.unwrap();
+	let total_issuance: Balance = api
+		.get_storage_value("Balances", "TotalIssuance", Some(block_hash))
+		.unwrap()
+		.unwrap();
+	assert_eq!(snapshot_total_issuance, total_issuance);
 }